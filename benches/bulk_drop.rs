@@ -0,0 +1,33 @@
+//! Compares dropping a batch of distinct objects through a `Vec` of `Rc`s, which pins the thread
+//! for each `Rc`, and through an `RcVec`, which releases all of them under a single guard.
+
+use circ::{EdgeTaker, Rc, RcObject, RcVec};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+const COUNT: usize = 100_000;
+
+struct Leaf {
+    _value: usize,
+}
+
+unsafe impl RcObject for Leaf {
+    fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+}
+
+fn leaves() -> Vec<Rc<Leaf>> {
+    (0..COUNT).map(|i| Rc::new(Leaf { _value: i })).collect()
+}
+
+fn bulk_drop(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bulk_drop");
+    group.bench_function("vec", |b| {
+        b.iter_batched(leaves, drop, BatchSize::LargeInput)
+    });
+    group.bench_function("rc_vec", |b| {
+        b.iter_batched(|| RcVec::from(leaves()), drop, BatchSize::LargeInput)
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bulk_drop);
+criterion_main!(benches);
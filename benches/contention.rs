@@ -0,0 +1,126 @@
+//! Measures the throughput of `AtomicRc::compare_exchange` retry loops on a single link,
+//! contended by an increasing number of threads.
+//!
+//! Each thread repeatedly replaces the pointer in the link with a new one, retrying on failure.
+//! The pointer is relinked across epochs, so the internal retry on the epoch-only mismatch is
+//! exercised as well as the user-level retry.
+//!
+//! `contention/backoff` uses `compare_exchange`, which backs off before the internal retry.
+//! `contention/tight_spin` is the baseline without the backoff: it uses
+//! `compare_exchange_once` and retries the epoch-only mismatch right away.
+//!
+//! Run with `cargo bench --bench contention`. A baseline on a single-core x86-64 VM, where the
+//! threads are time-sliced rather than running in parallel, so the backoff has little to gain:
+//!
+//! | threads | `backoff` | `tight_spin` |
+//! |---------|-----------|--------------|
+//! | 1       | 167 µs    | 152 µs       |
+//! | 4       | 565 µs    | 583 µs       |
+//! | 16      | 2.64 ms   | 2.76 ms      |
+//! | 64      | 10.6 ms   | 9.72 ms      |
+
+use std::sync::atomic::Ordering;
+use std::sync::Barrier;
+use std::time::{Duration, Instant};
+
+use circ::{cs, AtomicRc, CompareExchangeOnceError, EdgeTaker, Rc, RcObject};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+const OPS_PER_THREAD: u64 = 1000;
+
+struct Node;
+
+unsafe impl RcObject for Node {
+    fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+}
+
+fn replace_many(link: &AtomicRc<Node>) {
+    for _ in 0..OPS_PER_THREAD {
+        let mut desired = Rc::new(Node);
+        loop {
+            let guard = cs();
+            let expected = link.load(Ordering::Acquire, &guard);
+            match link.compare_exchange(
+                expected,
+                desired,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                &guard,
+            ) {
+                Ok(_) => break,
+                Err(e) => desired = e.desired,
+            }
+        }
+    }
+}
+
+fn replace_many_tight_spin(link: &AtomicRc<Node>) {
+    for _ in 0..OPS_PER_THREAD {
+        let mut desired = Rc::new(Node);
+        let guard = cs();
+        let mut expected = link.load(Ordering::Acquire, &guard);
+        loop {
+            match link.compare_exchange_once(
+                expected,
+                desired,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                &guard,
+            ) {
+                Ok(_) => break,
+                // Only the epoch differs, so retry at once with the current pointer.
+                Err(CompareExchangeOnceError::Retry(e)) => {
+                    desired = e.desired;
+                    expected = e.current;
+                }
+                Err(CompareExchangeOnceError::Mismatch(e)) => {
+                    desired = e.desired;
+                    expected = link.load(Ordering::Acquire, &guard);
+                }
+            }
+        }
+    }
+}
+
+fn run(threads: usize, iters: u64, replace_many: fn(&AtomicRc<Node>)) -> Duration {
+    let link = AtomicRc::new(Node);
+    let barrier = Barrier::new(threads + 1);
+    let mut elapsed = Duration::ZERO;
+    for _ in 0..iters {
+        let mut start = None;
+        std::thread::scope(|s| {
+            for _ in 0..threads {
+                s.spawn(|| {
+                    barrier.wait();
+                    replace_many(&link);
+                });
+            }
+            // Start the clock before releasing the threads, which may otherwise finish before
+            // this thread is scheduled again.
+            start = Some(Instant::now());
+            barrier.wait();
+        });
+        // The threads are joined at the end of the scope.
+        elapsed += start.unwrap().elapsed();
+    }
+    elapsed
+}
+
+fn contention(c: &mut Criterion) {
+    let mut group = c.benchmark_group("contention");
+    for threads in [1, 4, 16, 64] {
+        group.throughput(Throughput::Elements(threads as u64 * OPS_PER_THREAD));
+        group.bench_with_input(BenchmarkId::new("backoff", threads), &threads, |b, &t| {
+            b.iter_custom(|iters| run(t, iters, replace_many))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("tight_spin", threads),
+            &threads,
+            |b, &t| b.iter_custom(|iters| run(t, iters, replace_many_tight_spin)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, contention);
+criterion_main!(benches);
@@ -0,0 +1,98 @@
+//! Compares the throughput of a queue allocating its nodes by `Rc::new` and by an `RcPool`,
+//! which reuses the memory blocks of the reclaimed nodes.
+//!
+//! Each iteration enqueues `BATCH` items to a single-threaded linked queue and dequeues them
+//! again, so the nodes are reclaimed (and returned to the pool of this thread) as fast as they
+//! are allocated.
+//!
+//! Run with `cargo bench --bench pool`. A baseline on a single-core x86-64 VM:
+//!
+//! | benchmark       | time   | throughput   |
+//! |-----------------|--------|--------------|
+//! | `pool/unpooled` | 187 µs | 5.49 Melem/s |
+//! | `pool/pooled`   | 172 µs | 5.96 Melem/s |
+
+use std::sync::atomic::Ordering;
+
+use circ::pool::RcPool;
+use circ::{cs, AtomicRc, EdgeTaker, Guard, Rc, RcObject};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+const BATCH: usize = 1024;
+
+struct Node {
+    item: usize,
+    next: AtomicRc<Node>,
+}
+
+unsafe impl RcObject for Node {
+    fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+        out.take(&mut self.next);
+    }
+}
+
+struct Queue {
+    head: AtomicRc<Node>,
+    tail: AtomicRc<Node>,
+}
+
+impl Queue {
+    fn new() -> Self {
+        let sentinel = Rc::new(Node {
+            item: 0,
+            next: AtomicRc::null(),
+        });
+        Self {
+            head: AtomicRc::from(sentinel.clone()),
+            tail: AtomicRc::from(sentinel),
+        }
+    }
+
+    fn push(&self, node: Rc<Node>, guard: &Guard) {
+        let tail = self.tail.load(Ordering::Relaxed, guard);
+        let tail = tail.as_ref().unwrap();
+        tail.next.store(node.clone(), Ordering::Release, guard);
+        self.tail.store(node, Ordering::Release, guard);
+    }
+
+    fn pop(&self, guard: &Guard) -> Option<usize> {
+        let head = self.head.load(Ordering::Relaxed, guard);
+        let next = head.as_ref().unwrap().next.load(Ordering::Acquire, guard);
+        let item = next.as_ref()?.item;
+        self.head.store(next.counted(), Ordering::Release, guard);
+        Some(item)
+    }
+}
+
+fn run(queue: &Queue, mut alloc: impl FnMut(Node) -> Rc<Node>) {
+    let guard = &cs();
+    for item in 0..BATCH {
+        let node = alloc(Node {
+            item,
+            next: AtomicRc::null(),
+        });
+        queue.push(node, guard);
+    }
+    for _ in 0..BATCH {
+        assert!(queue.pop(guard).is_some());
+    }
+}
+
+fn pool(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pool");
+    group.throughput(Throughput::Elements(BATCH as u64));
+    group.bench_function("unpooled", |b| {
+        let queue = Queue::new();
+        b.iter(|| run(&queue, Rc::new))
+    });
+    group.bench_function("pooled", |b| {
+        let queue = Queue::new();
+        let pool = RcPool::new();
+        pool.reserve(BATCH);
+        b.iter(|| run(&queue, |node| pool.alloc(node)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, pool);
+criterion_main!(benches);
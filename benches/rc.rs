@@ -0,0 +1,60 @@
+//! Measures the hot paths of the reference counts on a single thread.
+//!
+//! - `new`: allocating an object shared by `SHARES` `Rc`s, either by `Rc::new` and cloning it,
+//!   or by `Rc::new_many`, which sets the count only once.
+//! - `clone`: cloning an `Rc` and releasing the clone, either by `drop`, which pins the thread
+//!   for each release, or by `Rc::finalize` under a guard pinned once.
+//!
+//! Run with `cargo bench --bench rc`. A baseline on a single-core x86-64 VM:
+//!
+//! | benchmark           | time    |
+//! |---------------------|---------|
+//! | `new/new_and_clone` | 380 ns  |
+//! | `new/new_many`      | 270 ns  |
+//! | `clone/drop`        | 34.1 ns |
+//! | `clone/finalize`    | 24.1 ns |
+
+use std::hint::black_box;
+
+use circ::{cs, EdgeTaker, Rc, RcObject};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const SHARES: usize = 8;
+
+struct Node {
+    _value: usize,
+}
+
+unsafe impl RcObject for Node {
+    fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+}
+
+fn new(c: &mut Criterion) {
+    let mut group = c.benchmark_group("new");
+    group.bench_function("new_and_clone", |b| {
+        b.iter(|| {
+            let rc = Rc::new(Node { _value: 0 });
+            let shares: [Rc<Node>; SHARES] = std::array::from_fn(|_| rc.clone());
+            drop(rc);
+            black_box(shares)
+        })
+    });
+    group.bench_function("new_many", |b| {
+        b.iter(|| black_box(Rc::<Node>::new_many::<SHARES>(Node { _value: 0 })))
+    });
+    group.finish();
+}
+
+fn clone(c: &mut Criterion) {
+    let rc = Rc::new(Node { _value: 0 });
+    let mut group = c.benchmark_group("clone");
+    group.bench_function("drop", |b| b.iter(|| drop(black_box(rc.clone()))));
+    group.bench_function("finalize", |b| {
+        let guard = cs();
+        b.iter(|| black_box(rc.clone()).finalize(&guard))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, new, clone);
+criterion_main!(benches);
@@ -0,0 +1,58 @@
+//! Compares the throughput of the deferred reclamation (`drop`) and the immediate reclamation
+//! (`Rc::drop_now`) of a batch of nodes.
+
+use circ::{AtomicRc, EdgeTaker, Rc, RcObject};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+const BATCH: usize = 1024;
+
+struct Node {
+    next: AtomicRc<Node>,
+}
+
+unsafe impl RcObject for Node {
+    fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+        out.take(&mut self.next);
+    }
+}
+
+fn batch() -> Vec<Rc<Node>> {
+    (0..BATCH)
+        .map(|_| {
+            Rc::new(Node {
+                next: AtomicRc::null(),
+            })
+        })
+        .collect()
+}
+
+fn reclamation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("reclamation");
+    group.bench_function("deferred", |b| {
+        b.iter_batched(
+            batch,
+            |nodes| {
+                for node in nodes {
+                    drop(node);
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("immediate", |b| {
+        b.iter_batched(
+            batch,
+            |nodes| {
+                for node in nodes {
+                    // SAFETY: The nodes are never shared with other threads.
+                    unsafe { node.drop_now() };
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, reclamation);
+criterion_main!(benches);
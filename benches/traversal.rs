@@ -0,0 +1,71 @@
+//! Compares the traversal over a long chain with the checked (`Snapshot::as_ref`) and the
+//! unchecked (`Snapshot::as_ref_unchecked`) dereference.
+
+use std::hint::black_box;
+use std::sync::atomic::Ordering;
+
+use circ::{cs, AtomicRc, EdgeTaker, Rc, RcObject};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const LENGTH: usize = 4096;
+
+struct Node {
+    value: usize,
+    next: AtomicRc<Node>,
+}
+
+unsafe impl RcObject for Node {
+    fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+        out.take(&mut self.next);
+    }
+}
+
+/// Builds a chain of `LENGTH` nodes, whose values decrease to zero at the last node.
+fn chain() -> AtomicRc<Node> {
+    let head = (0..LENGTH).fold(Rc::null(), |next, value| {
+        Rc::new(Node {
+            value,
+            next: AtomicRc::from(next),
+        })
+    });
+    AtomicRc::from(head)
+}
+
+fn traversal(c: &mut Criterion) {
+    let head = chain();
+    let mut group = c.benchmark_group("traversal");
+    group.bench_function("checked", |b| {
+        b.iter(|| {
+            let guard = cs();
+            let mut sum = 0;
+            let mut curr = head.load(Ordering::Acquire, &guard);
+            while let Some(node) = curr.as_ref() {
+                sum += node.value;
+                curr = node.next.load(Ordering::Acquire, &guard);
+            }
+            black_box(sum)
+        })
+    });
+    group.bench_function("unchecked", |b| {
+        b.iter(|| {
+            let guard = cs();
+            let mut sum = 0;
+            let mut curr = head.load(Ordering::Acquire, &guard);
+            loop {
+                // SAFETY: The chain is not empty, and `curr` is never null in the loop, since it
+                // stops at the last node.
+                let node = unsafe { curr.as_ref_unchecked() };
+                sum += node.value;
+                if node.value == 0 {
+                    break;
+                }
+                curr = node.next.load(Ordering::Acquire, &guard);
+            }
+            black_box(sum)
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, traversal);
+criterion_main!(benches);
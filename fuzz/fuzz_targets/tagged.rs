@@ -0,0 +1,13 @@
+//! Checks the invariants of the tag arithmetic of the pointers for arbitrary addresses, tags
+//! and alignments.
+//!
+//! Run with `cargo fuzz run tagged` in the root of the repository.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (u8, usize, usize, usize, usize)| {
+    let (align_log2, addr, tag, high_tag, other_high_tag) = input;
+    circ::fuzzing::check_tag_invariants(align_log2, addr, tag, high_tag, other_high_tag);
+});
@@ -0,0 +1,54 @@
+//! A soft cap on the memory of the objects managed by CIRC.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+static LIMIT: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+/// Sets the cap on the bytes of the live allocations managed by CIRC, which is checked by
+/// [`crate::Rc::try_new`]. The cap is unlimited by default.
+///
+/// The allocations are counted from their creation until their memory blocks are freed
+/// (see [`crate::rc_inner_size`] for the size of each). As the objects are reclaimed lazily by
+/// EBR, the blocks of the unreachable objects are still counted until they are actually freed,
+/// so the cap is soft: [`crate::Rc::try_new`] may fail for a while after dropping the last
+/// references to many objects. [`crate::quiescent_barrier`] forces the reclamation.
+///
+/// The infallible constructors (e.g., [`crate::Rc::new`]) ignore the cap, but their allocations
+/// are still counted.
+///
+/// This is available only with the `alloc-limit` feature, which adds a relaxed atomic operation
+/// to the allocation and deallocation paths.
+pub fn set_alloc_limit(bytes: usize) {
+    LIMIT.store(bytes, Ordering::Relaxed);
+}
+
+/// Returns the bytes of the live allocations managed by CIRC, which is compared with the cap
+/// set by [`set_alloc_limit`].
+pub fn allocated_bytes() -> usize {
+    LIVE_BYTES.load(Ordering::Relaxed)
+}
+
+/// Counts an allocation of `size` bytes, regardless of the cap.
+#[inline]
+pub(crate) fn reserve(size: usize) {
+    LIVE_BYTES.fetch_add(size, Ordering::Relaxed);
+}
+
+/// Counts an allocation of `size` bytes if it does not exceed the cap.
+/// Returns `false` if it would exceed the cap.
+#[inline]
+pub(crate) fn try_reserve(size: usize) -> bool {
+    let limit = LIMIT.load(Ordering::Relaxed);
+    LIVE_BYTES
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |live| {
+            live.checked_add(size).filter(|&live| live <= limit)
+        })
+        .is_ok()
+}
+
+/// Uncounts a freed allocation of `size` bytes.
+#[inline]
+pub(crate) fn release(size: usize) {
+    LIVE_BYTES.fetch_sub(size, Ordering::Relaxed);
+}
@@ -0,0 +1,178 @@
+//! Concurrent hash map which consists of a fixed number of buckets,
+//! each of which is a Harris's lock-free linked list.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+
+use crate::collections::LockFreeList;
+use crate::Guard;
+
+/// A concurrent hash map with a fixed number of buckets.
+///
+/// Each bucket is a [`LockFreeList`], and the key is hashed to pick the bucket on which
+/// the operation takes place. The map is not resized, so the number of buckets should be
+/// chosen considering the expected number of entries.
+///
+/// # Examples
+///
+/// ```
+/// use circ::cs;
+/// use circ::collections::HashMap;
+///
+/// let map = HashMap::new();
+/// let guard = &cs();
+///
+/// assert!(map.insert(1, "one", guard).is_none());
+/// assert_eq!(map.get(&1, guard), Some(&"one"));
+/// assert_eq!(map.remove(&1, guard), Some(&"one"));
+/// assert!(map.get(&1, guard).is_none());
+/// ```
+pub struct HashMap<K, V, S = RandomState> {
+    buckets: Box<[LockFreeList<K, V>]>,
+    hash_builder: S,
+}
+
+impl<K: Ord + Hash, V> HashMap<K, V, RandomState> {
+    /// The number of buckets of a map created by [`HashMap::new`].
+    pub const DEFAULT_BUCKETS: usize = 64;
+
+    /// Creates an empty map with [`HashMap::DEFAULT_BUCKETS`] buckets.
+    pub fn new() -> Self {
+        Self::with_buckets(Self::DEFAULT_BUCKETS)
+    }
+
+    /// Creates an empty map with the given number of buckets.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buckets` is zero.
+    pub fn with_buckets(buckets: usize) -> Self {
+        Self::with_buckets_and_hasher(buckets, RandomState::new())
+    }
+}
+
+impl<K: Ord + Hash, V> Default for HashMap<K, V, RandomState> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Hash, V, S: BuildHasher> HashMap<K, V, S> {
+    /// Creates an empty map with the given number of buckets, using `hash_builder` to hash
+    /// the keys.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buckets` is zero.
+    pub fn with_buckets_and_hasher(buckets: usize, hash_builder: S) -> Self {
+        assert!(buckets > 0, "a hash map must have at least one bucket");
+        Self {
+            buckets: (0..buckets).map(|_| LockFreeList::new()).collect(),
+            hash_builder,
+        }
+    }
+
+    /// Returns the number of buckets.
+    pub fn buckets(&self) -> usize {
+        self.buckets.len()
+    }
+
+    #[inline]
+    fn bucket(&self, key: &K) -> &LockFreeList<K, V> {
+        let hash = self.hash_builder.hash_one(key) as usize;
+        &self.buckets[hash % self.buckets.len()]
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    pub fn get<'g>(&'g self, key: &K, guard: &'g Guard) -> Option<&'g V> {
+        self.bucket(key).get(key, guard)
+    }
+
+    /// Inserts a key-value pair into the map.
+    ///
+    /// If the map already contains the key, the map is not updated and the value which is
+    /// already associated with the key is returned.
+    pub fn insert<'g>(&'g self, key: K, value: V, guard: &'g Guard) -> Option<&'g V> {
+        self.bucket(&key).insert(key, value, guard)
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key was previously
+    /// in the map.
+    pub fn remove<'g>(&'g self, key: &K, guard: &'g Guard) -> Option<&'g V> {
+        self.bucket(key).remove(key, guard)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossbeam_utils::thread;
+    use rand::prelude::*;
+
+    use super::HashMap;
+    use crate::cs;
+
+    #[test]
+    // Under miri, it runs only if requested with `--ignored`, and with smaller counts.
+    #[cfg_attr(miri, ignore)]
+    fn smoke() {
+        #[cfg(miri)]
+        const THREADS: i32 = 4;
+        #[cfg(not(miri))]
+        const THREADS: i32 = 30;
+        #[cfg(miri)]
+        const ELEMENTS_PER_THREADS: i32 = 20;
+        #[cfg(not(miri))]
+        const ELEMENTS_PER_THREADS: i32 = 1000;
+
+        let map = &HashMap::with_buckets(16);
+
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                s.spawn(move |_| {
+                    let rng = &mut rand::thread_rng();
+                    let mut keys: Vec<i32> =
+                        (0..ELEMENTS_PER_THREADS).map(|k| k * THREADS + t).collect();
+                    keys.shuffle(rng);
+                    for i in keys {
+                        assert!(map.insert(i, i.to_string(), &cs()).is_none());
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        thread::scope(|s| {
+            for t in 0..(THREADS / 2) {
+                s.spawn(move |_| {
+                    let rng = &mut rand::thread_rng();
+                    let mut keys: Vec<i32> =
+                        (0..ELEMENTS_PER_THREADS).map(|k| k * THREADS + t).collect();
+                    keys.shuffle(rng);
+                    let mut guard = cs();
+                    for i in keys {
+                        assert_eq!(i.to_string(), *map.remove(&i, &guard).unwrap());
+                        guard = cs();
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                s.spawn(move |_| {
+                    let mut guard = cs();
+                    for i in (0..ELEMENTS_PER_THREADS).map(|k| k * THREADS + t) {
+                        if t < THREADS / 2 {
+                            assert!(map.get(&i, &guard).is_none());
+                        } else {
+                            assert_eq!(i.to_string(), *map.get(&i, &guard).unwrap());
+                        }
+                        guard = cs();
+                    }
+                });
+            }
+        })
+        .unwrap();
+    }
+}
@@ -0,0 +1,811 @@
+//! Concurrent map based on Harris's lock-free linked list
+//! (<https://www.cl.cam.ac.uk/research/srg/netos/papers/2001-caslists.pdf>).
+
+use std::cmp::Ordering::{Equal, Greater, Less};
+use std::sync::atomic::{AtomicIsize, Ordering};
+
+use crate::{AtomicRc, EdgeTaker, Guard, Rc, RcObject, Snapshot};
+
+struct Node<K, V> {
+    next: AtomicRc<Self>,
+    key: K,
+    value: V,
+}
+
+unsafe impl<K, V> RcObject for Node<K, V> {
+    fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+        out.take(&mut self.next);
+    }
+}
+
+impl<K, V> Node<K, V> {
+    /// Creates a new node.
+    fn new(key: K, value: V) -> Self {
+        Self {
+            next: AtomicRc::null(),
+            key,
+            value,
+        }
+    }
+}
+
+struct Cursor<'g, K, V> {
+    // The link which points to `curr`. It is either the head of the list or
+    // the `next` field of the previous node.
+    prev: &'g AtomicRc<Node<K, V>>,
+    // Tag of `curr` should always be zero so when `curr` is stored in a `prev`, we don't store a
+    // tagged pointer and cause cleanup to fail.
+    curr: Snapshot<'g, Node<K, V>>,
+}
+
+impl<'g, K: Ord, V> Cursor<'g, K, V> {
+    /// Creates a cursor.
+    fn new(head: &'g AtomicRc<Node<K, V>>, guard: &'g Guard) -> Self {
+        Self {
+            prev: head,
+            curr: head.load(Ordering::Acquire, guard),
+        }
+    }
+
+    /// Clean up a chain of logically removed nodes in each traversal.
+    #[inline]
+    fn find_harris(&mut self, key: &K, guard: &'g Guard) -> Result<Option<&'g V>, ()> {
+        // Finding phase
+        // - cursor.curr: first untagged node w/ key >= search key (4)
+        // - cursor.prev: the ref of .next in previous untagged node (1 -> 2)
+        // 1 -> 2 -x-> 3 -x-> 4 -> 5 -> ∅  (search key: 4)
+        let mut prev_next = self.curr;
+        let found = loop {
+            let Some(curr_node) = self.curr.as_ref() else {
+                break None;
+            };
+            let next = curr_node.next.load(Ordering::Acquire, guard);
+
+            if next.tag() != 0 {
+                // We add a 0 tag here so that `self.curr`s tag is always 0.
+                self.curr = next.with_tag(0);
+                continue;
+            }
+
+            match curr_node.key.cmp(key) {
+                Less => {
+                    self.prev = &curr_node.next;
+                    self.curr = next;
+                    prev_next = next;
+                }
+                Equal => break Some(&curr_node.value),
+                Greater => break None,
+            }
+        };
+
+        // If prev and curr WERE adjacent, no need to clean up
+        if prev_next.ptr_eq(self.curr) {
+            return Ok(found);
+        }
+
+        // cleanup tagged nodes between anchor and curr
+        self.prev
+            .compare_exchange(
+                prev_next,
+                self.curr.counted(),
+                Ordering::Release,
+                Ordering::Relaxed,
+                guard,
+            )
+            .map_err(|_| ())?;
+
+        Ok(found)
+    }
+
+    /// Harris-Michael variant of the find, which unlinks each logically removed node as soon
+    /// as it is found, instead of a chain of them at once.
+    #[inline]
+    fn find_michael(&mut self, key: &K, guard: &'g Guard) -> Result<Option<&'g V>, ()> {
+        loop {
+            let Some(curr_node) = self.curr.as_ref() else {
+                return Ok(None);
+            };
+            let next = curr_node.next.load(Ordering::Acquire, guard);
+
+            if next.tag() != 0 {
+                // `curr` is logically removed. Unlink it, or restart if `prev` has changed.
+                let next = next.with_tag(0);
+                self.prev
+                    .compare_exchange(
+                        self.curr,
+                        next.counted(),
+                        Ordering::Release,
+                        Ordering::Relaxed,
+                        guard,
+                    )
+                    .map_err(|_| ())?;
+                self.curr = next;
+                continue;
+            }
+
+            match curr_node.key.cmp(key) {
+                Less => {
+                    self.prev = &curr_node.next;
+                    self.curr = next;
+                }
+                Equal => return Ok(Some(&curr_node.value)),
+                Greater => return Ok(None),
+            }
+        }
+    }
+
+    /// Inserts a value.
+    #[inline]
+    fn insert(self, node: Rc<Node<K, V>>, guard: &Guard) -> Result<(), Rc<Node<K, V>>> {
+        node.as_ref()
+            .unwrap()
+            .next
+            .swap(self.curr.counted(), Ordering::Relaxed);
+
+        match self.prev.compare_exchange(
+            self.curr,
+            node,
+            Ordering::Release,
+            Ordering::Relaxed,
+            guard,
+        ) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(e.desired),
+        }
+    }
+
+    /// Removes the current node.
+    #[inline]
+    fn remove(self, guard: &Guard) -> Result<(), ()> {
+        let curr_node = self.curr.as_ref().unwrap();
+
+        let next = curr_node.next.load(Ordering::Acquire, guard);
+        let e = curr_node.next.compare_exchange_tag(
+            next.with_tag(0),
+            1,
+            Ordering::AcqRel,
+            Ordering::Relaxed,
+            guard,
+        );
+        if e.is_err() {
+            return Err(());
+        }
+
+        let _ = self.prev.compare_exchange(
+            self.curr,
+            next.counted(),
+            Ordering::Release,
+            Ordering::Relaxed,
+            guard,
+        );
+
+        Ok(())
+    }
+}
+
+/// An iterator over a range of entries of a [`LockFreeList`].
+///
+/// See [`LockFreeList::range`] for the details.
+pub struct Range<'g, K, V> {
+    curr: Snapshot<'g, Node<K, V>>,
+    hi: &'g K,
+    guard: &'g Guard,
+}
+
+impl<'g, K: Ord, V> Iterator for Range<'g, K, V> {
+    type Item = (&'g K, &'g V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let curr_node = self.curr.as_ref()?;
+            if curr_node.key >= *self.hi {
+                self.curr = Snapshot::null();
+                return None;
+            }
+
+            let next = curr_node.next.load(Ordering::Acquire, self.guard);
+            self.curr = next.with_tag(0);
+            // A tagged `next` means that the node is logically removed.
+            if next.tag() == 0 {
+                return Some((&curr_node.key, &curr_node.value));
+            }
+        }
+    }
+}
+
+/// An iterator over all entries of a [`LockFreeList`], within a single critical section.
+///
+/// See [`LockFreeList::snapshot_iter`] for the details.
+pub struct SnapshotIter<'g, K, V> {
+    curr: Snapshot<'g, Node<K, V>>,
+    guard: &'g Guard,
+}
+
+impl<'g, K, V> Iterator for SnapshotIter<'g, K, V> {
+    type Item = (&'g K, &'g V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let curr_node = self.curr.as_ref()?;
+            let next = curr_node.next.load(Ordering::Acquire, self.guard);
+            self.curr = next.with_tag(0);
+            // A tagged `next` means that the node is logically removed.
+            if next.tag() == 0 {
+                return Some((&curr_node.key, &curr_node.value));
+            }
+        }
+    }
+}
+
+/// An iterator over all entries of a [`LockFreeList`], which unlinks the logically removed nodes
+/// on its way.
+///
+/// See [`LockFreeList::cleanup_iter`] for the details.
+pub struct CleanupIter<'g, K, V> {
+    // The link which points to `curr`, as in `Cursor`.
+    prev: &'g AtomicRc<Node<K, V>>,
+    curr: Snapshot<'g, Node<K, V>>,
+    guard: &'g Guard,
+}
+
+impl<'g, K, V> Iterator for CleanupIter<'g, K, V> {
+    type Item = (&'g K, &'g V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let curr_node = self.curr.as_ref()?;
+            let next = curr_node.next.load(Ordering::Acquire, self.guard);
+            if next.tag() == 0 {
+                self.prev = &curr_node.next;
+                self.curr = next;
+                return Some((&curr_node.key, &curr_node.value));
+            }
+
+            // `curr` is logically removed. Try to unlink it, as `Cursor::find_michael` does.
+            let next = next.with_tag(0);
+            if self
+                .prev
+                .compare_exchange(
+                    self.curr,
+                    next.counted(),
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                    self.guard,
+                )
+                .is_err()
+            {
+                // `prev` has changed (e.g., its node is removed too). Instead of restarting,
+                // skip `curr` and leave it to the subsequent traversals.
+                self.prev = &curr_node.next;
+            }
+            self.curr = next;
+        }
+    }
+}
+
+/// A concurrent ordered map based on Harris's lock-free linked list.
+///
+/// The entries are sorted by their keys, and logically removed entries are physically unlinked
+/// by subsequent traversals.
+///
+/// # Examples
+///
+/// ```
+/// use circ::cs;
+/// use circ::collections::LockFreeList;
+///
+/// let list = LockFreeList::new();
+/// let guard = &cs();
+///
+/// assert!(list.insert(1, "one", guard).is_none());
+/// assert_eq!(list.insert(1, "uno", guard), Some(&"one"));
+/// assert_eq!(list.get(&1, guard), Some(&"one"));
+/// assert_eq!(list.remove(&1, guard), Some(&"one"));
+/// assert!(list.get(&1, guard).is_none());
+/// ```
+pub struct LockFreeList<K, V> {
+    head: AtomicRc<Node<K, V>>,
+    // The number of entries, adjusted by the successful insertions and logical removals. It may
+    // be transiently negative, if an entry is removed before its insertion is counted.
+    len: AtomicIsize,
+}
+
+impl<K: Ord, V> Default for LockFreeList<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> LockFreeList<K, V> {
+    /// Creates a new empty list.
+    pub fn new() -> Self {
+        Self {
+            head: AtomicRc::null(),
+            len: AtomicIsize::new(0),
+        }
+    }
+
+    /// Returns the number of entries in the list.
+    ///
+    /// It is exact if no insertion or removal is in progress. Otherwise, it is counted a moment
+    /// after an entry is linked or logically removed, so it may be transiently off by the
+    /// number of the concurrent operations.
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed).max(0) as usize
+    }
+
+    /// Returns `true` if the list contains no entries, in the same sense as
+    /// [`LockFreeList::len`].
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    fn find<'g, F>(
+        &'g self,
+        key: &K,
+        find: F,
+        guard: &'g Guard,
+    ) -> (Option<&'g V>, Cursor<'g, K, V>)
+    where
+        F: Fn(&mut Cursor<'g, K, V>, &K, &'g Guard) -> Result<Option<&'g V>, ()>,
+    {
+        loop {
+            let mut cursor = Cursor::new(&self.head, guard);
+            if let Ok(r) = find(&mut cursor, key, guard) {
+                return (r, cursor);
+            }
+        }
+    }
+
+    #[inline]
+    fn insert_with<'g, F>(&'g self, key: K, value: V, find: F, guard: &'g Guard) -> Option<&'g V>
+    where
+        F: Fn(&mut Cursor<'g, K, V>, &K, &'g Guard) -> Result<Option<&'g V>, ()>,
+    {
+        let mut node = Rc::new(Node::new(key, value));
+        loop {
+            let (found, cursor) =
+                self.find(node.as_ref().map(|node| &node.key).unwrap(), &find, guard);
+            if found.is_some() {
+                return found;
+            }
+
+            match cursor.insert(node, guard) {
+                Err(n) => node = n,
+                Ok(()) => {
+                    self.len.fetch_add(1, Ordering::Relaxed);
+                    return None;
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn remove_with<'g, F>(&'g self, key: &K, find: F, guard: &'g Guard) -> Option<&'g V>
+    where
+        F: Fn(&mut Cursor<'g, K, V>, &K, &'g Guard) -> Result<Option<&'g V>, ()>,
+    {
+        loop {
+            let (found, cursor) = self.find(key, &find, guard);
+            found?;
+
+            match cursor.remove(guard) {
+                Err(()) => continue,
+                Ok(_) => {
+                    // Only the thread whose CAS marked the node removes the entry.
+                    self.len.fetch_sub(1, Ordering::Relaxed);
+                    return found;
+                }
+            }
+        }
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    pub fn get<'g>(&'g self, key: &K, guard: &'g Guard) -> Option<&'g V> {
+        self.find(key, Cursor::find_harris, guard).0
+    }
+
+    /// Inserts a key-value pair into the list.
+    ///
+    /// If the list already contains the key, the list is not updated and the value which is
+    /// already associated with the key is returned.
+    pub fn insert<'g>(&'g self, key: K, value: V, guard: &'g Guard) -> Option<&'g V> {
+        self.insert_with(key, value, Cursor::find_harris, guard)
+    }
+
+    /// Returns a reference to the value corresponding to the key, or inserts the value computed
+    /// by `f` if the list does not contain the key, and returns a reference to it.
+    ///
+    /// `f` is called at most once, only if the key is not found by the first traversal. If
+    /// another thread inserts the same key in the meantime, the insertion loses the race: the
+    /// computed value is dropped, and the value of the winner is returned. Thus, all the callers
+    /// racing on a key observe the same value.
+    pub fn get_or_insert_with<'g, F>(&'g self, key: K, f: F, guard: &'g Guard) -> &'g V
+    where
+        F: FnOnce() -> V,
+    {
+        let (found, mut cursor) = self.find(&key, Cursor::find_harris, guard);
+        if let Some(value) = found {
+            return value;
+        }
+
+        let mut node = Rc::new(Node::new(key, f()));
+        // The guard keeps the node alive after it is moved into the list.
+        let new = node.snapshot(guard).as_ref().unwrap();
+        loop {
+            match cursor.insert(node, guard) {
+                Err(n) => node = n,
+                Ok(()) => {
+                    self.len.fetch_add(1, Ordering::Relaxed);
+                    return &new.value;
+                }
+            }
+
+            let (found, next) = self.find(&new.key, Cursor::find_harris, guard);
+            if let Some(value) = found {
+                return value;
+            }
+            cursor = next;
+        }
+    }
+
+    /// Removes a key from the list, returning the value at the key if the key was previously
+    /// in the list.
+    pub fn remove<'g>(&'g self, key: &K, guard: &'g Guard) -> Option<&'g V> {
+        self.remove_with(key, Cursor::find_harris, guard)
+    }
+
+    /// Returns a reference to the value corresponding to the key, using the Harris-Michael
+    /// traversal.
+    ///
+    /// The `michael_*` methods unlink each logically removed node as soon as they find it,
+    /// while the default ones unlink a chain of them at once. The former makes the removed nodes
+    /// reclaimable more eagerly, at the cost of more CASes on contended traversals.
+    pub fn michael_get<'g>(&'g self, key: &K, guard: &'g Guard) -> Option<&'g V> {
+        self.find(key, Cursor::find_michael, guard).0
+    }
+
+    /// Inserts a key-value pair into the list, using the Harris-Michael traversal.
+    ///
+    /// See [`LockFreeList::insert`] and [`LockFreeList::michael_get`].
+    pub fn michael_insert<'g>(&'g self, key: K, value: V, guard: &'g Guard) -> Option<&'g V> {
+        self.insert_with(key, value, Cursor::find_michael, guard)
+    }
+
+    /// Removes a key from the list, using the Harris-Michael traversal.
+    ///
+    /// See [`LockFreeList::remove`] and [`LockFreeList::michael_get`].
+    pub fn michael_remove<'g>(&'g self, key: &K, guard: &'g Guard) -> Option<&'g V> {
+        self.remove_with(key, Cursor::find_michael, guard)
+    }
+
+    /// Returns an iterator over the entries whose keys are in `lo..hi`, in ascending order of the
+    /// keys.
+    ///
+    /// The iterator is weakly consistent: it never yields an entry twice, nor an entry removed
+    /// before the iteration reaches it, but it may or may not yield the entries concurrently
+    /// inserted or removed during the iteration.
+    pub fn range<'g>(&'g self, lo: &K, hi: &'g K, guard: &'g Guard) -> Range<'g, K, V> {
+        let (_, cursor) = self.find(lo, Cursor::find_harris, guard);
+        Range {
+            curr: cursor.curr,
+            hi,
+            guard,
+        }
+    }
+
+    /// Returns an iterator over all entries, in ascending order of the keys.
+    ///
+    /// The whole scan takes place in the critical section of `guard`, which cannot be
+    /// repinned while the iterator borrows it. Thus, every entry it yields was in the list at
+    /// some point during the iteration, and no node it visits is reclaimed in the middle of
+    /// the scan, even if it is removed concurrently. Unlike [`LockFreeList::range`], it starts
+    /// from the head without finding a position, so it never helps unlinking the removed nodes.
+    ///
+    /// Note that holding a single guard for a long scan delays the advancement of the global
+    /// epoch, and thus the reclamation of every object retired in the meantime by any thread.
+    /// For a long scan over a busy list, consider splitting it into several calls to
+    /// [`LockFreeList::range`], repinning the guard in between (e.g., with
+    /// [`Guard::reactivate`]), which bounds the memory usage at the cost of the consistency
+    /// across the calls.
+    pub fn snapshot_iter<'g>(&'g self, guard: &'g Guard) -> SnapshotIter<'g, K, V> {
+        SnapshotIter {
+            curr: self.head.load(Ordering::Acquire, guard).with_tag(0),
+            guard,
+        }
+    }
+
+    /// Returns an iterator over all entries, in ascending order of the keys, which also unlinks
+    /// the logically removed nodes it encounters.
+    ///
+    /// This spreads the physical removal over the readers, instead of leaving it to the
+    /// writers' traversals. Each unlink is a single CAS attempt: if it fails due to a concurrent
+    /// update, the iterator just skips the node and moves on, so some removed nodes may remain
+    /// linked after the iteration.
+    ///
+    /// Like [`LockFreeList::snapshot_iter`], the whole scan takes place in the critical section
+    /// of `guard`, and the iterator is weakly consistent: every entry it yields was in the list
+    /// at some point during the iteration, but it may or may not yield the entries concurrently
+    /// inserted or removed.
+    pub fn cleanup_iter<'g>(&'g self, guard: &'g Guard) -> CleanupIter<'g, K, V> {
+        CleanupIter {
+            prev: &self.head,
+            curr: self.head.load(Ordering::Acquire, guard),
+            guard,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossbeam_utils::thread;
+    use rand::prelude::*;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::LockFreeList;
+    use crate::cs;
+
+    /// Counts the nodes physically linked in the list, including the logically removed ones.
+    fn count_nodes<K, V>(list: &LockFreeList<K, V>) -> usize {
+        let guard = &cs();
+        let mut count = 0;
+        let mut curr = list.head.load(Ordering::Acquire, guard);
+        while let Some(node) = curr.as_ref() {
+            count += 1;
+            curr = node.next.load(Ordering::Acquire, guard).with_tag(0);
+        }
+        count
+    }
+
+    #[test]
+    fn range() {
+        let list = LockFreeList::new();
+        let guard = &cs();
+        for key in (0..20).rev() {
+            assert!(list.insert(key, key * 10, guard).is_none());
+        }
+        for key in [6, 8] {
+            assert!(list.remove(&key, guard).is_some());
+        }
+
+        let entries = list.range(&5, &10, guard).collect::<Vec<_>>();
+        assert_eq!(entries, [(&5, &50), (&7, &70), (&9, &90)]);
+        assert_eq!(list.range(&10, &10, guard).count(), 0);
+        assert_eq!(list.range(&15, &100, guard).count(), 5);
+    }
+
+    #[test]
+    fn len_after_workload() {
+        #[cfg(miri)]
+        const THREADS: usize = 4;
+        #[cfg(not(miri))]
+        const THREADS: usize = 8;
+        #[cfg(miri)]
+        const KEYS: usize = 20;
+        #[cfg(not(miri))]
+        const KEYS: usize = 200;
+
+        let list = &LockFreeList::new();
+        assert!(list.is_empty());
+
+        // Every thread inserts the same keys and removes the odd ones, so most of the operations
+        // fail or race with each other.
+        thread::scope(|s| {
+            for _ in 0..THREADS {
+                s.spawn(move |_| {
+                    let mut keys = (0..KEYS).collect::<Vec<_>>();
+                    keys.shuffle(&mut rand::thread_rng());
+                    for &key in &keys {
+                        list.insert(key, key, &cs());
+                    }
+                    for key in keys.into_iter().filter(|key| key % 2 == 1) {
+                        list.remove(&key, &cs());
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        // Only the even keys are live, whether or not their removed neighbors are unlinked yet.
+        assert_eq!(list.len(), KEYS / 2);
+        assert!(list
+            .snapshot_iter(&cs())
+            .map(|(&key, _)| key)
+            .eq((0..KEYS).step_by(2)));
+        for key in (0..KEYS).step_by(2) {
+            assert!(list.michael_remove(&key, &cs()).is_some());
+        }
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn get_or_insert_with_race() {
+        #[cfg(miri)]
+        const THREADS: usize = 4;
+        #[cfg(not(miri))]
+        const THREADS: usize = 8;
+        #[cfg(miri)]
+        const KEYS: usize = 10;
+        #[cfg(not(miri))]
+        const KEYS: usize = 100;
+
+        let list = &LockFreeList::new();
+        let calls = &AtomicUsize::new(0);
+
+        // Every thread races to insert its own value for each key.
+        let observed = thread::scope(|s| {
+            let handles = (0..THREADS)
+                .map(|t| {
+                    s.spawn(move |_| {
+                        (0..KEYS)
+                            .map(|key| {
+                                *list.get_or_insert_with(
+                                    key,
+                                    || {
+                                        calls.fetch_add(1, Ordering::Relaxed);
+                                        t
+                                    },
+                                    &cs(),
+                                )
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|h| h.join().unwrap())
+                .collect::<Vec<_>>()
+        })
+        .unwrap();
+
+        assert_eq!(list.len(), KEYS);
+        let guard = &cs();
+        for key in 0..KEYS {
+            let winner = *list.get(&key, guard).unwrap();
+            assert!(observed.iter().all(|values| values[key] == winner));
+        }
+        // The winner of each key has computed its value, and the losers may have too.
+        assert!((KEYS..=THREADS * KEYS).contains(&calls.load(Ordering::Relaxed)));
+        assert_eq!(
+            *list.get_or_insert_with(0, || unreachable!(), guard),
+            observed[0][0]
+        );
+    }
+
+    #[test]
+    fn michael_smoke() {
+        #[cfg(miri)]
+        const THREADS: i32 = 4;
+        #[cfg(not(miri))]
+        const THREADS: i32 = 16;
+        #[cfg(miri)]
+        const ELEMENTS_PER_THREADS: i32 = 20;
+        #[cfg(not(miri))]
+        const ELEMENTS_PER_THREADS: i32 = 500;
+
+        let map = &LockFreeList::new();
+        let keys = |t| {
+            let mut keys: Vec<i32> = (0..ELEMENTS_PER_THREADS).map(|k| k * THREADS + t).collect();
+            keys.shuffle(&mut rand::thread_rng());
+            keys
+        };
+
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                s.spawn(move |_| {
+                    for i in keys(t) {
+                        assert!(map.michael_insert(i, i.to_string(), &cs()).is_none());
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        // Removals and lookups of disjoint keys run concurrently, so the lookups traverse the
+        // nodes being removed.
+        thread::scope(|s| {
+            for t in 0..(THREADS / 2) {
+                s.spawn(move |_| {
+                    for i in keys(t) {
+                        assert_eq!(i.to_string(), *map.michael_remove(&i, &cs()).unwrap());
+                    }
+                });
+            }
+            for t in (THREADS / 2)..THREADS {
+                s.spawn(move |_| {
+                    for i in keys(t) {
+                        assert_eq!(i.to_string(), *map.michael_get(&i, &cs()).unwrap());
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        let guard = &cs();
+        for t in 0..THREADS {
+            for i in keys(t) {
+                assert_eq!(map.get(&i, guard).is_some(), t >= THREADS / 2);
+            }
+        }
+    }
+
+    #[test]
+    fn snapshot_iter_with_concurrent_updates() {
+        #[cfg(miri)]
+        const KEYS: i32 = 50;
+        #[cfg(not(miri))]
+        const KEYS: i32 = 2000;
+        #[cfg(miri)]
+        const SCANS: usize = 5;
+        #[cfg(not(miri))]
+        const SCANS: usize = 200;
+
+        let map = &LockFreeList::new();
+        for i in (0..KEYS).step_by(2) {
+            assert!(map.insert(i, i.to_string(), &cs()).is_none());
+        }
+
+        thread::scope(|s| {
+            // Insert the odd keys and remove the even ones, while the others are scanning.
+            s.spawn(move |_| {
+                for i in 0..KEYS {
+                    if i % 2 == 0 {
+                        assert!(map.remove(&i, &cs()).is_some());
+                    } else {
+                        assert!(map.insert(i, i.to_string(), &cs()).is_none());
+                    }
+                }
+            });
+            for _ in 0..2 {
+                s.spawn(move |_| {
+                    for _ in 0..SCANS {
+                        let guard = &cs();
+                        let mut prev = None;
+                        // Dereferencing a reclaimed node would fail the debug assertions.
+                        for (key, value) in map.snapshot_iter(guard) {
+                            assert!(prev < Some(key));
+                            assert_eq!(*value, key.to_string());
+                            prev = Some(key);
+                        }
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        let guard = &cs();
+        let keys = map
+            .snapshot_iter(guard)
+            .map(|(k, _)| *k)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, (1..KEYS).step_by(2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn cleanup_iter_unlinks_marked_nodes() {
+        let list = LockFreeList::new();
+        let guard = &cs();
+        for key in 0..10 {
+            assert!(list.insert(key, key * 10, guard).is_none());
+        }
+
+        // Mark the even nodes (and thus the consecutive ones at the front) as removed, without
+        // unlinking them as `remove` would.
+        let mut curr = list.head.load(Ordering::Acquire, guard);
+        while let Some(node) = curr.as_ref() {
+            if node.key % 2 == 0 || node.key < 3 {
+                node.next.fetch_max_tag(1, Ordering::AcqRel, guard);
+            }
+            curr = node.next.load(Ordering::Acquire, guard).with_tag(0);
+        }
+        assert_eq!(count_nodes(&list), 10);
+
+        let entries = list.cleanup_iter(guard).collect::<Vec<_>>();
+        assert_eq!(entries, [(&3, &30), (&5, &50), (&7, &70), (&9, &90)]);
+        assert_eq!(count_nodes(&list), 4);
+        assert_eq!(list.cleanup_iter(guard).count(), 4);
+    }
+}
@@ -0,0 +1,33 @@
+//! Concurrent data structures built on top of CIRC.
+//!
+//! These collections are ready-made examples of how [`crate::AtomicRc`] and [`crate::Snapshot`]
+//! are used to implement non-blocking data structures, and can be used as they are.
+
+mod hash_map;
+mod list;
+mod queue;
+
+pub use hash_map::*;
+pub use list::*;
+pub use queue::*;
+
+/// The scheduling point set by [`set_sched_hook`].
+#[cfg(feature = "shuttle")]
+static SCHED_HOOK: std::sync::OnceLock<fn()> = std::sync::OnceLock::new();
+
+/// Sets the function called before each access to a shared location of [`Queue`], so that the
+/// Shuttle tests (`tests/shuttle_queue.rs`) can switch to another thread at these points.
+#[cfg(feature = "shuttle")]
+#[doc(hidden)]
+pub fn set_sched_hook(hook: fn()) {
+    let _ = SCHED_HOOK.set(hook);
+}
+
+/// A scheduling point for the Shuttle tests. It does nothing without the `shuttle` feature.
+#[inline(always)]
+fn sched() {
+    #[cfg(feature = "shuttle")]
+    if let Some(hook) = SCHED_HOOK.get() {
+        hook();
+    }
+}
@@ -0,0 +1,306 @@
+//! Concurrent queue based on Ramalhete and Correia's "DoubleLink" lock-free queue
+//! (<https://concurrencyfreaks.blogspot.com/2017/01/doublelink-low-overhead-lock-free-queue.html>).
+
+use std::cell::UnsafeCell;
+use std::sync::atomic::Ordering;
+
+use crossbeam_utils::CachePadded;
+
+use super::sched;
+use crate::{AtomicRc, EdgeTaker, Guard, Rc, RcObject, Snapshot, Weak};
+
+struct Node<T> {
+    // Taken by the thread which dequeues the node, and `None` for a sentinel.
+    item: UnsafeCell<Option<T>>,
+    // A weak pointer to the previous node, so that the links do not form a cycle.
+    prev: Weak<Node<T>>,
+    next: CachePadded<AtomicRc<Node<T>>>,
+}
+
+// The item is accessed only by the thread which creates the node, and then by the only one which
+// dequeues it, so it just needs to be sent between them.
+unsafe impl<T: Send> Send for Node<T> {}
+unsafe impl<T: Send> Sync for Node<T> {}
+
+unsafe impl<T> RcObject for Node<T> {
+    fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+        out.take(&mut *self.next);
+    }
+}
+
+impl<T> Node<T> {
+    fn sentinel() -> Self {
+        Self {
+            item: UnsafeCell::new(None),
+            prev: Weak::null(),
+            next: CachePadded::new(AtomicRc::null()),
+        }
+    }
+
+    fn new(item: T) -> Self {
+        Self {
+            item: UnsafeCell::new(Some(item)),
+            prev: Weak::null(),
+            next: CachePadded::new(AtomicRc::null()),
+        }
+    }
+}
+
+/// A concurrent multi-producer multi-consumer FIFO queue.
+///
+/// Each enqueued node keeps a [`Weak`] pointer to its predecessor, with which an enqueuer helps
+/// the previous enqueue to link the predecessor to its successor. [`Queue::pop`] moves the item
+/// out of the dequeued node, which becomes the sentinel of the queue. The node is reclaimed
+/// after the next one is dequeued and no thread which may have loaded it is in its critical
+/// section.
+///
+/// # Examples
+///
+/// ```
+/// use circ::cs;
+/// use circ::collections::Queue;
+///
+/// let queue = Queue::new();
+/// let guard = &cs();
+///
+/// queue.push(1, guard);
+/// queue.push(2, guard);
+/// assert_eq!(queue.pop(guard), Some(1));
+/// assert_eq!(queue.pop(guard), Some(2));
+/// assert!(queue.pop(guard).is_none());
+/// ```
+pub struct Queue<T> {
+    head: CachePadded<AtomicRc<Node<T>>>,
+    tail: CachePadded<AtomicRc<Node<T>>>,
+    /// If set, `pop` unlinks the head with a plain store instead of a CAS. This is an
+    /// intentionally injected bug to check that the Shuttle tests are able to find one.
+    #[cfg(feature = "shuttle")]
+    faulty: bool,
+}
+
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Queue<T> {
+    /// Creates a new empty queue.
+    pub fn new() -> Self {
+        let sentinel = Rc::new(Node::sentinel());
+        // `sentinel.prev` must not point to itself. It would form a cycle after the first
+        // enqueue, which is never reclaimed.
+        Self {
+            head: CachePadded::new(AtomicRc::from(sentinel.clone())),
+            tail: CachePadded::new(AtomicRc::from(sentinel)),
+            #[cfg(feature = "shuttle")]
+            faulty: false,
+        }
+    }
+
+    /// Creates a new empty queue whose `pop` is broken on purpose, for the Shuttle tests.
+    #[cfg(feature = "shuttle")]
+    #[doc(hidden)]
+    pub fn with_injected_bug() -> Self {
+        Self {
+            faulty: true,
+            ..Self::new()
+        }
+    }
+
+    /// Pushes an item to the back of the queue.
+    pub fn push(&self, item: T, guard: &Guard) {
+        // One for `tail`, and the other for the `next` of the predecessor.
+        let [mut node, sub] = Rc::new_many(Node::new(item));
+
+        loop {
+            sched();
+            let ltail = self.tail.load(Ordering::Acquire, guard);
+            // The node is not shared with other threads until it is installed in `tail`.
+            unsafe { node.deref_mut() }.prev = ltail.downgrade().counted();
+
+            // Help the previous enqueue to link its predecessor to `ltail`.
+            sched();
+            if let Some(lprev) = ltail
+                .as_ref()
+                .unwrap()
+                .prev
+                .snapshot(guard)
+                .upgrade()
+                .and_then(Snapshot::as_ref)
+            {
+                sched();
+                if lprev.next.load(Ordering::SeqCst, guard).is_null() {
+                    sched();
+                    // A popper may reach `ltail` through this link, so publish its item.
+                    lprev.next.store(ltail.counted(), Ordering::Release, guard);
+                }
+            }
+
+            sched();
+            match self
+                .tail
+                .compare_exchange(ltail, node, Ordering::SeqCst, Ordering::SeqCst, guard)
+            {
+                Ok(_) => {
+                    sched();
+                    ltail
+                        .as_ref()
+                        .unwrap()
+                        .next
+                        .store(sub, Ordering::Release, guard);
+                    return;
+                }
+                Err(e) => node = e.desired,
+            }
+        }
+    }
+
+    /// Pops an item from the front of the queue, or returns `None` if the queue is empty.
+    pub fn pop(&self, guard: &Guard) -> Option<T> {
+        loop {
+            sched();
+            let lhead = self.head.load(Ordering::Acquire, guard);
+            sched();
+            let lnext = lhead.as_ref().unwrap().next.load(Ordering::Acquire, guard);
+            // The successor of the sentinel is not linked yet if the queue is empty, or if the
+            // first enqueue is in progress.
+            lnext.as_ref()?;
+
+            sched();
+            #[cfg(feature = "shuttle")]
+            if self.faulty {
+                self.head.store(lnext.counted(), Ordering::SeqCst, guard);
+                return unsafe { (*lnext.as_ref().unwrap().item.get()).take() };
+            }
+            if self
+                .head
+                .compare_exchange(
+                    lhead,
+                    lnext.counted(),
+                    Ordering::SeqCst,
+                    Ordering::SeqCst,
+                    guard,
+                )
+                .is_ok()
+            {
+                // Only the thread which has advanced `head` to `lnext` takes its item.
+                return unsafe { (*lnext.as_ref().unwrap().item.get()).take() };
+            }
+        }
+    }
+
+    /// Returns `true` if the queue is empty at the moment.
+    pub fn is_empty(&self, guard: &Guard) -> bool {
+        let lhead = self.head.load(Ordering::Acquire, guard);
+        lhead
+            .as_ref()
+            .unwrap()
+            .next
+            .load(Ordering::Acquire, guard)
+            .is_null()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+
+    use super::Queue;
+    use crate::{cs, quiescent_barrier};
+
+    #[test]
+    fn simple() {
+        let queue = Queue::new();
+        let guard = &cs();
+        assert!(queue.is_empty(guard));
+        assert!(queue.pop(guard).is_none());
+        for i in 1..=3 {
+            queue.push(i, guard);
+        }
+        assert!(!queue.is_empty(guard));
+        for i in 1..=3 {
+            assert_eq!(queue.pop(guard), Some(i));
+        }
+        assert!(queue.pop(guard).is_none());
+    }
+
+    #[test]
+    fn pop_moves_item_out() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+        struct Item(usize);
+
+        impl Drop for Item {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let queue = Queue::new();
+        for i in 0..3 {
+            queue.push(Item(i), &cs());
+        }
+        let first = queue.pop(&cs());
+        assert_eq!(first.as_ref().map(|item| item.0), Some(0));
+        // The popped item is owned by the caller, even after its node is reclaimed.
+        drop(queue.pop(&cs()));
+        quiescent_barrier();
+        assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+        drop(first);
+        assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+
+        // The items left in the queue are dropped with it, and the popped ones are not again.
+        drop(queue);
+        quiescent_barrier();
+        assert_eq!(DROPS.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn smoke() {
+        #[cfg(miri)]
+        const THREADS: usize = 4;
+        #[cfg(not(miri))]
+        const THREADS: usize = 16;
+        #[cfg(miri)]
+        const ELEMENTS_PER_THREAD: usize = 20;
+        #[cfg(not(miri))]
+        const ELEMENTS_PER_THREAD: usize = 1000;
+
+        let queue = &Queue::new();
+        let found = &(0..THREADS * ELEMENTS_PER_THREAD)
+            .map(|_| AtomicU32::new(0))
+            .collect::<Vec<_>>();
+
+        // Pushes and pops run concurrently.
+        std::thread::scope(|s| {
+            for t in 0..THREADS {
+                s.spawn(move || {
+                    for i in 0..ELEMENTS_PER_THREAD {
+                        queue.push(t * ELEMENTS_PER_THREAD + i, &cs());
+                    }
+                });
+                s.spawn(move || {
+                    let mut popped = 0;
+                    // The items pushed by a thread are popped in order.
+                    let mut last = vec![None; THREADS];
+                    while popped < ELEMENTS_PER_THREAD {
+                        let guard = cs();
+                        let Some(item) = queue.pop(&guard) else {
+                            std::thread::yield_now();
+                            continue;
+                        };
+                        let producer = item / ELEMENTS_PER_THREAD;
+                        assert!(last[producer] < Some(item));
+                        last[producer] = Some(item);
+                        assert_eq!(found[item].fetch_add(1, Ordering::Relaxed), 0);
+                        popped += 1;
+                    }
+                });
+            }
+        });
+
+        assert!(queue.pop(&cs()).is_none());
+        assert!(found.iter().all(|f| f.load(Ordering::Relaxed) == 1));
+    }
+}
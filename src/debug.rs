@@ -0,0 +1,197 @@
+//! Utilities for inspecting the shapes of the object graphs, e.g., to find out why some objects
+//! are not reclaimed, and the contention on the links (with the `debug-counters` feature).
+
+#[cfg(feature = "debug-counters")]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use rustc_hash::FxHashSet;
+
+use crate::{cs, Rc, RcObject, Snapshot};
+
+/// Calls `visit` with each object reachable from `root`, following the edges visited by
+/// [`RcObject::visit_edges`] in depth-first order.
+///
+/// Each object is visited once, even if it is reachable through multiple paths or a cycle.
+/// This is handy for debugging memory leaks caused by unexpected cycles: an object which is
+/// reachable from itself is never reclaimed by reference counting.
+///
+/// The traversal is not synchronized with concurrent updates, so it may or may not visit the
+/// objects linked or unlinked in the meantime. It takes place in a single critical section, so
+/// the visited objects are not reclaimed until it returns. Nothing is visited if `root` is null.
+///
+/// # Examples
+///
+/// ```
+/// use circ::debug::trace_reachable;
+/// use circ::{AtomicRc, EdgeTaker, Guard, Rc, RcObject, Snapshot};
+/// use std::sync::atomic::Ordering;
+///
+/// struct Node {
+///     next: AtomicRc<Node>,
+/// }
+///
+/// unsafe impl RcObject for Node {
+///     fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+///         out.take(&mut self.next);
+///     }
+///
+///     fn visit_edges<'g>(&'g self, visit: &mut dyn FnMut(Snapshot<'g, Self>), guard: &'g Guard) {
+///         visit(self.next.load(Ordering::Acquire, guard));
+///     }
+/// }
+///
+/// let head = Rc::new(Node {
+///     next: AtomicRc::new(Node {
+///         next: AtomicRc::null(),
+///     }),
+/// });
+///
+/// // Link the tail back to the head, which would leak both of them.
+/// let guard = circ::cs();
+/// let tail = head.as_ref().unwrap().next.load(Ordering::Acquire, &guard);
+/// tail.as_ref().unwrap().next.store(head.clone(), Ordering::Release, &guard);
+///
+/// let mut count = 0;
+/// trace_reachable(&head, |_| count += 1);
+/// assert_eq!(count, 2);
+///
+/// // Break the cycle.
+/// tail.as_ref().unwrap().next.store(Rc::null(), Ordering::Release, &guard);
+/// ```
+pub fn trace_reachable<T: RcObject>(root: &Rc<T>, mut visit: impl FnMut(&T)) {
+    let guard = cs();
+    let mut visited = FxHashSet::default();
+    let mut stack = vec![root.snapshot(&guard)];
+    while let Some(curr) = stack.pop() {
+        let Some(obj) = curr.as_ref() else {
+            continue;
+        };
+        if !visited.insert(obj as *const T) {
+            continue;
+        }
+        // `obj` is protected by `guard`, and `visit` cannot keep the reference beyond the call.
+        visit(obj);
+        let start = stack.len();
+        obj.visit_edges(&mut |next: Snapshot<'_, T>| stack.push(next), &guard);
+        // Visit the first edge first.
+        stack[start..].reverse();
+    }
+}
+
+#[cfg(feature = "debug-counters")]
+static CAS_ATTEMPTS: AtomicU64 = AtomicU64::new(0);
+#[cfg(feature = "debug-counters")]
+static CAS_FAILURES: AtomicU64 = AtomicU64::new(0);
+#[cfg(feature = "debug-counters")]
+static CAS_EPOCH_RETRIES: AtomicU64 = AtomicU64::new(0);
+
+#[inline(always)]
+pub(crate) fn count_cas_attempt() {
+    #[cfg(feature = "debug-counters")]
+    CAS_ATTEMPTS.fetch_add(1, Ordering::Relaxed);
+}
+
+#[inline(always)]
+pub(crate) fn count_cas_failure() {
+    #[cfg(feature = "debug-counters")]
+    CAS_FAILURES.fetch_add(1, Ordering::Relaxed);
+}
+
+#[inline(always)]
+pub(crate) fn count_cas_epoch_retry() {
+    #[cfg(feature = "debug-counters")]
+    CAS_EPOCH_RETRIES.fetch_add(1, Ordering::Relaxed);
+}
+
+/// The numbers of the hardware CAS operations performed by the compare-and-exchange methods of
+/// [`crate::AtomicRc`] (and `AtomicRcVersioned`) in the whole process, returned by
+/// [`cas_stats`].
+///
+/// Like [`crate::Stats`], the counters are updated with relaxed atomics, so a snapshot taken
+/// while other threads are running is not necessarily consistent.
+#[cfg(feature = "debug-counters")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CasStats {
+    /// The number of the CAS operations attempted so far.
+    pub attempts: u64,
+    /// The number of the CAS operations which failed because the current pointer or tag was
+    /// different from the expected one, and thus were reported to the caller.
+    pub failures: u64,
+    /// The number of the CAS operations which failed only because of the epoch recorded on the
+    /// pointer, and thus were retried internally (or reported as
+    /// [`crate::CompareExchangeOnceError::Retry`]).
+    pub epoch_retries: u64,
+}
+
+/// Returns the current CAS statistics.
+///
+/// A high ratio of `failures` to `attempts` indicates that the links are contended, and a high
+/// ratio of `epoch_retries` indicates that the same pointers are frequently relinked.
+///
+/// This is available only with the `debug-counters` feature, which adds a relaxed atomic
+/// operation to each CAS. Otherwise, the counting compiles down to nothing.
+#[cfg(feature = "debug-counters")]
+pub fn cas_stats() -> CasStats {
+    CasStats {
+        attempts: CAS_ATTEMPTS.load(Ordering::Relaxed),
+        failures: CAS_FAILURES.load(Ordering::Relaxed),
+        epoch_retries: CAS_EPOCH_RETRIES.load(Ordering::Relaxed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use super::trace_reachable;
+    use crate::{AtomicRc, EdgeTaker, Guard, Rc, RcObject, Snapshot};
+
+    struct Tree {
+        key: u32,
+        children: Vec<AtomicRc<Tree>>,
+    }
+
+    unsafe impl RcObject for Tree {
+        fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+            out.take_all(self.children.iter_mut());
+        }
+
+        fn visit_edges<'g>(&'g self, visit: &mut dyn FnMut(Snapshot<'g, Self>), guard: &'g Guard) {
+            for child in &self.children {
+                visit(child.load(Ordering::Acquire, guard));
+            }
+        }
+    }
+
+    fn tree(key: u32, children: Vec<Rc<Tree>>) -> Rc<Tree> {
+        Rc::new(Tree {
+            key,
+            children: children.into_iter().map(AtomicRc::from).collect(),
+        })
+    }
+
+    #[test]
+    fn trace_small_tree() {
+        //     0
+        //   /   \
+        //  1     2
+        //  |    / \
+        //  3   4   3 (shared)
+        let shared = tree(3, vec![]);
+        let root = tree(
+            0,
+            vec![
+                tree(1, vec![shared.clone()]),
+                tree(2, vec![tree(4, vec![]), shared]),
+            ],
+        );
+
+        let mut keys = Vec::new();
+        trace_reachable(&root, |node| keys.push(node.key));
+        assert_eq!(keys, [0, 1, 3, 2, 4]);
+
+        keys.clear();
+        trace_reachable(&Rc::null(), |node: &Tree| keys.push(node.key));
+        assert!(keys.is_empty());
+    }
+}
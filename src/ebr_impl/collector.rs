@@ -79,7 +79,6 @@ impl LocalHandle {
     }
 
     /// Returns `true` if the handle is pinned.
-    #[cfg(test)]
     #[inline]
     pub(crate) fn is_pinned(&self) -> bool {
         unsafe { (*self.local).is_pinned() }
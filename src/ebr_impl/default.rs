@@ -25,6 +25,15 @@ pub fn cs() -> Guard {
     with_handle(|handle| handle.pin())
 }
 
+/// Returns `true` if the current thread is in an EBR critical section.
+///
+/// It is useful for libraries which may be called either inside or outside of a critical
+/// section, so that they enter one only if necessary.
+#[inline]
+pub fn is_pinned() -> bool {
+    with_handle(|handle| handle.is_pinned())
+}
+
 /// Returns the default global collector.
 pub fn default_collector() -> &'static Collector {
     collector()
@@ -40,6 +49,13 @@ where
         .unwrap_or_else(|_| f(&collector().register()))
 }
 
+/// Blocks until all functions deferred before this call have been executed.
+///
+/// See [`Local::barrier`](super::internal::Local::barrier).
+pub(crate) fn barrier() {
+    with_handle(|handle| unsafe { (*handle.local).barrier() })
+}
+
 #[inline]
 pub(crate) fn global_epoch() -> usize {
     default_collector().global_epoch().value()
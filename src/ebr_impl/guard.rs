@@ -1,5 +1,6 @@
 use core::fmt;
 use core::mem;
+use core::ops::Deref;
 
 use scopeguard::defer;
 
@@ -128,6 +129,33 @@ impl Guard {
         f()
     }
 
+    /// Returns a dummy guard that can be sent and shared across threads.
+    ///
+    /// The returned guard does not keep any thread pinned, and functions deferred with it are
+    /// executed immediately. Because it never touches a thread-local
+    /// participant, it is [`Send`] and [`Sync`], so it can be handed to helper threads (e.g.,
+    /// scoped threads performing a teardown) or to FFI code.
+    ///
+    /// # Safety
+    ///
+    /// The data structures accessed with this guard must have a single logical owner for the
+    /// whole lifetime of the guard: no other thread may concurrently modify or reclaim the
+    /// pointers loaded with it. Typically, this holds during an exclusive teardown of a data
+    /// structure, after all other users have finished.
+    #[inline]
+    pub unsafe fn unprotected_static() -> UnprotectedGuard {
+        UnprotectedGuard {
+            guard: unprotected(),
+        }
+    }
+
+    /// Returns `true` if this guard keeps the current thread pinned, i.e., it is not a dummy
+    /// guard such as [`Guard::unprotected_static`].
+    #[inline]
+    pub fn is_pinned(&self) -> bool {
+        !self.local.is_null()
+    }
+
     /// Increases the manual collection counter, and perform collection if the counter reaches
     /// the threshold which is set by `set_manual_collection_interval`.
     pub(crate) fn incr_manual_collection(&self) {
@@ -152,6 +180,34 @@ impl fmt::Debug for Guard {
     }
 }
 
+/// A dummy guard which can be sent and shared across threads.
+///
+/// It is created by [`Guard::unprotected_static`], and can be used as a [`Guard`] by
+/// dereferencing it.
+pub struct UnprotectedGuard {
+    guard: Guard,
+}
+
+// SAFETY: The inner guard is not bound to any participant, so it performs no thread-local
+// bookkeeping and executes deferred functions immediately.
+unsafe impl Send for UnprotectedGuard {}
+unsafe impl Sync for UnprotectedGuard {}
+
+impl Deref for UnprotectedGuard {
+    type Target = Guard;
+
+    #[inline]
+    fn deref(&self) -> &Guard {
+        &self.guard
+    }
+}
+
+impl fmt::Debug for UnprotectedGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("UnprotectedGuard { .. }")
+    }
+}
+
 /// Returns a reference to a dummy guard that allows unprotected access to atomic pointers.
 ///
 /// This guard should be used in special occasions only. Note that it doesn't actually keep any
@@ -167,3 +223,80 @@ pub unsafe fn unprotected() -> Guard {
         local: core::ptr::null(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use crossbeam_utils::thread;
+
+    use super::{unprotected, Guard};
+    use crate::{cs, is_pinned, AtomicRc, EdgeTaker, Rc, RcObject};
+
+    struct Node {
+        next: AtomicRc<Self>,
+    }
+
+    unsafe impl RcObject for Node {
+        fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+            out.take(&mut self.next);
+        }
+    }
+
+    #[test]
+    fn unprotected_static_teardown() {
+        const THREADS: usize = 4;
+        let slots: Vec<AtomicRc<Node>> = (0..THREADS)
+            .map(|_| {
+                AtomicRc::new(Node {
+                    next: AtomicRc::new(Node {
+                        next: AtomicRc::null(),
+                    }),
+                })
+            })
+            .collect();
+
+        // All the other users have finished, so the slots are exclusively owned by this scope.
+        let guard = unsafe { Guard::unprotected_static() };
+        let guard = &guard;
+        thread::scope(|s| {
+            for slot in &slots {
+                s.spawn(move |_| {
+                    let node = slot.load(Ordering::Relaxed, guard);
+                    assert!(!node
+                        .as_ref()
+                        .unwrap()
+                        .next
+                        .load(Ordering::Relaxed, guard)
+                        .is_null());
+                    slot.store(Rc::null(), Ordering::Relaxed, guard);
+                });
+            }
+        })
+        .unwrap();
+
+        assert!(slots
+            .iter()
+            .all(|slot| slot.load(Ordering::Relaxed, guard).is_null()));
+    }
+
+    #[test]
+    fn nested_pins() {
+        assert!(!is_pinned());
+        {
+            let outer = cs();
+            assert!(outer.is_pinned() && is_pinned());
+            {
+                let inner = cs();
+                assert!(inner.is_pinned() && is_pinned());
+            }
+            assert!(is_pinned());
+        }
+        assert!(!is_pinned());
+
+        let guard = unsafe { unprotected() };
+        assert!(!guard.is_pinned());
+        assert!(!unsafe { Guard::unprotected_static() }.is_pinned());
+        assert!(!is_pinned());
+    }
+}
@@ -36,13 +36,19 @@
 //! destroyed as soon as the data structure gets dropped.
 
 use super::RawShared;
-use core::cell::{Cell, UnsafeCell};
+use core::cell::{Cell, RefCell, UnsafeCell};
 use core::mem::{forget, replace, ManuallyDrop};
-use core::sync::atomic::{compiler_fence, Ordering};
+use core::sync::atomic::{compiler_fence, AtomicBool, AtomicUsize, Ordering};
 use core::{fmt, ptr};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SendError, Sender};
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
-use crossbeam_utils::CachePadded;
+use crossbeam_utils::{Backoff, CachePadded};
 use memoffset::offset_of;
+use scopeguard::defer;
 
 use super::collector::{Collector, LocalHandle};
 use super::deferred::Deferred;
@@ -56,6 +62,276 @@ static mut MAX_OBJECTS: usize = 64;
 
 static mut MANUAL_EVENTS_BETWEEN_COLLECT: usize = 64;
 
+/// The number of pins between collections. Zero disables the pin-driven collection.
+static PIN_INTERVAL: AtomicUsize = AtomicUsize::new(0);
+
+/// Sets how often pinning a participant triggers a collection, which attempts to advance the
+/// global epoch and executes the expired deferred functions on unpinning.
+///
+/// With `n > 0`, every `n`-th outermost pin of each thread triggers a collection. A smaller
+/// interval reclaims garbage sooner and lowers the peak memory usage, at the cost of more
+/// frequent collections on the pinning path. `0` disables the pin-driven collection.
+///
+/// By default, it is disabled (`0`), and collections are driven only by deferring functions
+/// (e.g., when the thread-local bag of deferred functions is full) and by [`Guard::flush`].
+pub fn set_pin_interval(n: usize) {
+    PIN_INTERVAL.store(n, Ordering::Relaxed);
+}
+
+/// A callback registered with [`register_epoch_callback`].
+type EpochCallback = Arc<dyn Fn(u32) + Send + Sync>;
+
+/// The registered epoch callbacks. The list is replaced as a whole on a registration, so that
+/// the callbacks are called without holding the lock, and may register other callbacks.
+static EPOCH_CALLBACKS: RwLock<Option<Arc<Vec<EpochCallback>>>> = RwLock::new(None);
+
+/// Whether any epoch callback is registered, to skip the lock on the pinning path otherwise.
+static HAS_EPOCH_CALLBACKS: AtomicBool = AtomicBool::new(false);
+
+/// Registers a callback which is called whenever a thread observes that the global epoch has
+/// advanced, with the new epoch.
+///
+/// The callback is called on the observing thread, when it pins itself (e.g., by [`cs`] or
+/// [`Guard::reactivate`]) for the first time in a new epoch. Thus, each thread delivers its
+/// own observations: the same advance is reported once by each thread which pins after it, and
+/// a thread which does not pin in the meantime reports nothing. The delivery is best-effort
+/// and may be coalesced: several advances between two pins of a thread are reported once, with
+/// the latest epoch, and the first pin of a thread reports nothing. The epoch is truncated to
+/// `u32`, so only the changes of it are meaningful.
+///
+/// It lets the caches keyed on the objects of CIRC prune their stale entries (e.g., the
+/// entries whose [`crate::Weak`]s fail to upgrade) opportunistically, as an advance of the
+/// epoch may allow more objects to be reclaimed. The callback runs in the critical section of
+/// the new pin, so it should be short. It may also be called while the thread exits, when its
+/// thread-locals may be destroyed already. Callbacks cannot be unregistered.
+///
+/// A thread whose epoch is refreshed in the middle of a long destruction, without unpinning,
+/// does not call the callbacks then, and reports the advance on its next pin instead.
+///
+/// # Panics
+///
+/// If a callback panics, the panic is caught and discarded (after the panic hook reports it), so
+/// that the other callbacks are still called and the pinning call returns normally. The pins
+/// which call the callbacks include the internal ones of this crate, such as the pin which
+/// destructs an object after its last [`crate::Rc`] is dropped, and they must not unwind.
+///
+/// [`cs`]: crate::cs
+/// [`Guard::reactivate`]: crate::Guard::reactivate
+pub fn register_epoch_callback(callback: Box<dyn Fn(u32) + Send + Sync>) {
+    let mut callbacks = EPOCH_CALLBACKS
+        .write()
+        .unwrap_or_else(PoisonError::into_inner);
+    let mut new = callbacks.as_deref().cloned().unwrap_or_default();
+    new.push(Arc::from(callback));
+    *callbacks = Some(Arc::new(new));
+    HAS_EPOCH_CALLBACKS.store(true, Ordering::Release);
+}
+
+/// Calls the registered epoch callbacks with `epoch`, discarding their panics.
+#[cold]
+fn notify_epoch_callbacks(epoch: Epoch) {
+    let callbacks = EPOCH_CALLBACKS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+    for callback in callbacks.iter().flat_map(|callbacks| callbacks.iter()) {
+        // The pin may be an internal one which must not unwind (e.g., the one which
+        // destructs an object whose strong count has reached zero).
+        let _ = catch_unwind(AssertUnwindSafe(|| callback(epoch.value() as u32)));
+    }
+}
+
+/// Whether the reclaimer thread is running. See [`set_reclaim_thread`].
+static RECLAIM_THREAD: AtomicBool = AtomicBool::new(false);
+
+/// The number of reclaimer threads spawned so far, identifying the current one.
+static RECLAIMER_GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+/// The reclaimer thread and the channel to send the functions to it.
+static RECLAIMER: Mutex<Option<Reclaimer>> = Mutex::new(None);
+
+thread_local! {
+    /// Whether the current thread is the reclaimer thread.
+    static IS_RECLAIMER: Cell<bool> = const { Cell::new(false) };
+
+    /// The sender to the reclaimer of the cached generation, so that handing a function off
+    /// does not lock [`RECLAIMER`].
+    static RECLAIM_SENDER: RefCell<Option<(usize, Sender<Message>)>> = const { RefCell::new(None) };
+}
+
+struct Reclaimer {
+    generation: usize,
+    sender: Sender<Message>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Drop for Reclaimer {
+    /// Shuts the reclaimer down: it executes the functions handed to it so far, and exits.
+    fn drop(&mut self) {
+        // The senders cached by the other threads keep the channel open, so ask it to stop.
+        let _ = self.sender.send(Message::Stop);
+        if let Some(handle) = self.handle.take() {
+            if let Err(panic) = handle.join() {
+                if !thread::panicking() {
+                    std::panic::resume_unwind(panic);
+                }
+            }
+        }
+    }
+}
+
+enum Message {
+    Run(SendDeferred),
+    Stop,
+}
+
+/// A deferred function to be executed on the reclaimer thread.
+///
+/// If it is dropped without being executed (e.g., left in the channel when the reclaimer
+/// exits), it is executed by the dropping thread instead, so that no object is leaked.
+struct SendDeferred(Option<Deferred>);
+
+/// `run_on_reclaimer()` requires that it is safe for another thread to execute the function.
+unsafe impl Send for SendDeferred {}
+
+impl SendDeferred {
+    fn call(mut self) {
+        if let Some(deferred) = self.0.take() {
+            deferred.call();
+        }
+    }
+}
+
+impl Drop for SendDeferred {
+    fn drop(&mut self) {
+        if let Some(deferred) = self.0.take() {
+            deferred.call();
+        }
+    }
+}
+
+/// How often the reclaimer thread moves its own deferred functions into the global queue.
+const RECLAIM_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Sets whether a dedicated background thread reclaims the objects whose types opt in with
+/// [`RcObject::DEFER_TO_BACKGROUND`](crate::RcObject::DEFER_TO_BACKGROUND).
+///
+/// By default, it is disabled (`false`), and an expired object is destructed inline, on the
+/// thread whose collection (or immediate recursive destruction) finds it. It may add jitter to
+/// latency-critical threads, as destructing a large structure takes a while even if it is
+/// done iteratively.
+///
+/// With `true`, a reclaimer thread is spawned. Only the objects of the opted-in types move to
+/// it: once such an object becomes safe to destruct, the other threads only hand its
+/// destruction to the reclaimer through a channel, in the order in which they found it, and
+/// the reclaimer destructs the objects one by one. The successors which become unreachable in
+/// turn are destructed on the reclaimer as well, as long as they are immediately reclaimable.
+/// The ones it has to defer are moved into the global queue periodically, and handed back to
+/// it when they expire. The objects of the other types are still destructed inline.
+///
+/// With `false`, the reclaimer thread (if any) is shut down: this call blocks until the
+/// reclaimer has destructed all the objects handed to it, and then joins it. The functions
+/// which the reclaimer deferred in turn are moved into the global queue on its exit, and
+/// executed inline by the following collections. Note that the reclaimer is not shut down
+/// automatically, so the objects handed to it may never be destructed if the process exits
+/// first (see [`crate::shutdown`]).
+///
+/// # Panics
+///
+/// Panics if a destructor executed on the reclaimer thread panicked.
+pub fn set_reclaim_thread(enable: bool) {
+    let mut slot = RECLAIMER.lock().unwrap();
+    if enable {
+        if slot.is_none() {
+            let (sender, receiver) = mpsc::channel();
+            let handle = thread::Builder::new()
+                .name("circ-reclaim".into())
+                .spawn(move || reclaim_loop(receiver))
+                .expect("failed to spawn the reclaimer thread");
+            let generation = RECLAIMER_GENERATION.fetch_add(1, Ordering::Relaxed) + 1;
+            *slot = Some(Reclaimer {
+                generation,
+                sender,
+                handle: Some(handle),
+            });
+            RECLAIM_THREAD.store(true, Ordering::Release);
+        }
+    } else if let Some(reclaimer) = slot.take() {
+        RECLAIM_THREAD.store(false, Ordering::Relaxed);
+        // The reclaimer itself may be handing off functions, so release the lock before
+        // joining it.
+        drop(slot);
+        drop(reclaimer);
+    }
+}
+
+fn reclaim_loop(receiver: Receiver<Message>) {
+    IS_RECLAIMER.with(|is_reclaimer| is_reclaimer.set(true));
+    let mut last_flush = Instant::now();
+    loop {
+        match receiver.recv_timeout(RECLAIM_INTERVAL) {
+            Ok(Message::Run(deferred)) => deferred.call(),
+            Ok(Message::Stop) | Err(RecvTimeoutError::Disconnected) => break,
+            Err(RecvTimeoutError::Timeout) => {}
+        }
+        // Periodically publish the functions deferred by the executed ones, even if the
+        // channel is never idle. They are collected by the other threads, so that the
+        // reclaimer does not execute the expired functions of the types which did not opt in.
+        if last_flush.elapsed() >= RECLAIM_INTERVAL {
+            let guard = super::default::cs();
+            unsafe { &*guard.local }.push_to_global(&guard);
+            last_flush = Instant::now();
+        }
+    }
+    // Dropping the receiver executes the functions handed off concurrently with the shutdown.
+    drop(receiver);
+}
+
+/// Returns `true` if the current thread is the reclaimer thread.
+pub(crate) fn is_reclaimer() -> bool {
+    IS_RECLAIMER.try_with(Cell::get).unwrap_or(false)
+}
+
+/// Returns `true` if the reclaimer thread is running, and the current thread is not the one.
+pub(crate) fn reclaimer_available() -> bool {
+    RECLAIM_THREAD.load(Ordering::Relaxed) && !is_reclaimer()
+}
+
+/// Executes `f` on the reclaimer thread if it is available (see [`reclaimer_available`]), or
+/// right away on the current thread otherwise.
+///
+/// # Safety
+///
+/// It should be safe for another thread to execute `f`.
+pub(crate) unsafe fn run_on_reclaimer<F: FnOnce()>(f: F) {
+    let mut deferred = Some(SendDeferred(Some(Deferred::new(f))));
+    if reclaimer_available() {
+        let generation = RECLAIMER_GENERATION.load(Ordering::Acquire);
+        // The cached sender is gone if the current thread is exiting.
+        let _ = RECLAIM_SENDER.try_with(|cached| {
+            let mut cached = cached.borrow_mut();
+            if !matches!(*cached, Some((cached, _)) if cached == generation) {
+                *cached = RECLAIMER
+                    .lock()
+                    .unwrap()
+                    .as_ref()
+                    .map(|reclaimer| (reclaimer.generation, reclaimer.sender.clone()));
+            }
+            if let Some((_, sender)) = cached.as_ref() {
+                let message = Message::Run(deferred.take().unwrap());
+                // If the reclaimer has exited, the function is given back.
+                if let Err(SendError(Message::Run(message))) = sender.send(message) {
+                    deferred = Some(message);
+                }
+            }
+        });
+    }
+    // The reclaimer is unavailable, or being shut down.
+    if let Some(deferred) = deferred {
+        deferred.call();
+    }
+}
+
 /// A bag of deferred functions.
 pub(crate) struct Bag(Vec<Deferred>);
 
@@ -335,7 +611,6 @@ impl Local {
 
     /// Returns `true` if the current participant is pinned.
     #[inline]
-    #[cfg(test)]
     pub(crate) fn is_pinned(&self) -> bool {
         self.guard_count.get() > 0
     }
@@ -353,6 +628,8 @@ impl Local {
             deferred = d;
             self.schedule_collection();
         }
+        #[cfg(feature = "stats")]
+        crate::stats::PEAK_DEFERRED.fetch_max(bag.0.len(), Ordering::Relaxed);
         self.incr_advance(guard);
     }
 
@@ -361,6 +638,38 @@ impl Local {
         self.schedule_collection();
     }
 
+    /// Blocks until all functions deferred before this call, either in the global queue or in
+    /// the local bag, have been executed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the participant is pinned, because it would block the epoch advancement.
+    pub(crate) fn barrier(&self) {
+        assert!(
+            self.guard_count.get() == 0,
+            "a barrier must not be called in a critical section"
+        );
+
+        // The global queue executes sealed bags in FIFO order, so the marker is executed only
+        // after all the bags pushed before it.
+        let done = Arc::new(AtomicBool::new(false));
+        {
+            let guard = self.pin();
+            let done = done.clone();
+            unsafe { guard.defer_unchecked(move || done.store(true, Ordering::Release)) };
+            guard.flush();
+        }
+
+        let backoff = Backoff::new();
+        while !done.load(Ordering::Acquire) {
+            let guard = self.pin();
+            // The collection takes place on unpinning.
+            self.schedule_collection();
+            drop(guard);
+            backoff.snooze();
+        }
+    }
+
     pub(crate) fn push_to_global(&self, guard: &Guard) {
         let bag = unsafe { &mut *self.bag.get() };
 
@@ -442,9 +751,21 @@ impl Local {
             };
 
             // Reset the advance couter if epoch has advanced.
-            if new_epoch != self.prev_epoch.get() {
-                self.prev_epoch.set(new_epoch);
+            let prev_epoch = self.prev_epoch.replace(new_epoch);
+            if new_epoch != prev_epoch {
                 self.advance_count.set(0);
+                if prev_epoch != Epoch::starting() && HAS_EPOCH_CALLBACKS.load(Ordering::Acquire) {
+                    notify_epoch_callbacks(new_epoch);
+                }
+            }
+
+            let interval = PIN_INTERVAL.load(Ordering::Relaxed);
+            if interval != 0 {
+                let pin_count = self.pin_count.get().wrapping_add(1);
+                self.pin_count.set(pin_count);
+                if pin_count % interval == 0 {
+                    self.schedule_collection();
+                }
             }
         }
 
@@ -455,8 +776,25 @@ impl Local {
     #[inline]
     pub(crate) fn unpin(&self) {
         let guard_count = self.guard_count.get();
+        // A deferred function executed by the collection below may panic. Unpin the participant
+        // even in that case, as it would otherwise stay pinned forever and block the epoch
+        // advancement of all threads.
+        defer! {
+            self.guard_count.set(guard_count - 1);
+            if guard_count == 1 {
+                self.epoch.store(Epoch::starting(), Ordering::Release);
+
+                if self.handle_count.get() == 0 {
+                    self.finalize();
+                }
+            }
+        }
+
         if guard_count == 1 && !self.collecting.get() {
             self.collecting.set(true);
+            defer! {
+                self.collecting.set(false);
+            }
             while self.must_collect.get() {
                 self.must_collect.set(false);
                 debug_assert!(self.epoch.load(Ordering::Relaxed).is_pinned());
@@ -464,16 +802,6 @@ impl Local {
                 self.global().collect(&guard);
                 self.repin_without_collect();
             }
-            self.collecting.set(false);
-        }
-
-        self.guard_count.set(guard_count - 1);
-        if guard_count == 1 {
-            self.epoch.store(Epoch::starting(), Ordering::Release);
-
-            if self.handle_count.get() == 0 {
-                self.finalize();
-            }
         }
     }
 
@@ -488,6 +816,9 @@ impl Local {
     }
 
     /// Repins the local epoch without checking a scheduled collection.
+    ///
+    /// It does not call the epoch callbacks, as it runs in the middle of a destruction. It leaves
+    /// `prev_epoch` as it is, so the next `pin` reports the advance instead.
     #[inline]
     pub(crate) fn repin_without_collect(&self) -> Epoch {
         let epoch = self.epoch.load(Ordering::Relaxed);
@@ -569,14 +900,18 @@ impl Local {
 
 impl IsElement<Local> for Local {
     fn entry_of(local: &Local) -> &Entry {
-        let entry_ptr = (local as *const Local as usize + offset_of!(Local, entry)) as *const Entry;
+        let entry_ptr = unsafe {
+            (local as *const Local)
+                .byte_add(offset_of!(Local, entry))
+                .cast::<Entry>()
+        };
         unsafe { &*entry_ptr }
     }
 
     unsafe fn element_of(entry: &Entry) -> &Local {
-        // offset_of! macro uses unsafe, but it's unnecessary in this context.
-        #[allow(unused_unsafe)]
-        let local_ptr = (entry as *const Entry as usize - offset_of!(Local, entry)) as *const Local;
+        let local_ptr = (entry as *const Entry)
+            .byte_sub(offset_of!(Local, entry))
+            .cast::<Local>();
         &*local_ptr
     }
 
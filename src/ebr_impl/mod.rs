@@ -60,4 +60,8 @@ mod sync;
 pub use default::*;
 pub use epoch::*;
 pub use guard::*;
+pub(crate) use internal::{is_reclaimer, reclaimer_available, run_on_reclaimer};
+pub use internal::{register_epoch_callback, set_pin_interval, set_reclaim_thread};
+#[cfg(fuzzing)]
+pub use pointers::check_tag_invariants;
 pub use pointers::*;
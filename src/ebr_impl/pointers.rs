@@ -1,15 +1,15 @@
 use core::hash::Hash;
 use core::marker::PhantomData;
-use core::mem::align_of;
+use core::mem::{align_of, transmute};
 use core::ptr::null_mut;
-use core::sync::atomic::AtomicUsize;
-use static_assertions::const_assert_eq;
-use std::fmt::{Debug, Formatter, Pointer};
-
-use atomic::{Atomic, Ordering};
+use core::sync::atomic::{AtomicPtr, Ordering};
+use std::fmt::{Debug, DebugStruct, Formatter, Pointer};
 
 use super::Guard;
 
+// Tag bits are always manipulated with the strict provenance APIs (e.g., `map_addr`),
+// so that the pointers keep their provenance under Miri.
+#[repr(transparent)]
 pub struct Tagged<T: ?Sized> {
     ptr: *mut T,
 }
@@ -20,6 +20,23 @@ impl<T> Debug for Tagged<T> {
     }
 }
 
+impl<T> Tagged<T> {
+    /// Starts a [`DebugStruct`] with the address, the user tag and the nullity of the pointer.
+    /// The epoch tag in the high bits is hidden, as it is meaningless for clients.
+    pub(crate) fn debug_struct<'a, 'b>(
+        &self,
+        f: &'a mut Formatter<'b>,
+        name: &str,
+    ) -> DebugStruct<'a, 'b> {
+        let mut debug = f.debug_struct(name);
+        debug
+            .field("addr", &self.as_raw())
+            .field("tag", &self.tag())
+            .field("null", &self.is_null());
+        debug
+    }
+}
+
 impl<T> Pointer for Tagged<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         Pointer::fmt(&self.as_raw(), f)
@@ -80,19 +97,17 @@ impl<T> Tagged<T> {
     }
 
     pub fn tag(&self) -> usize {
-        let ptr = self.ptr as usize;
-        ptr & low_bits::<T>()
+        self.ptr.addr() & low_bits::<T>()
     }
 
     pub fn high_tag(&self) -> usize {
-        let ptr = self.ptr as usize;
-        (ptr & Self::high_bits()) >> Self::high_bits_pos()
+        (self.ptr.addr() & Self::high_bits()) >> Self::high_bits_pos()
     }
 
     /// Converts the pointer to a raw pointer (without the tag).
     pub fn as_raw(&self) -> *mut T {
-        let ptr = self.ptr as usize;
-        (ptr & !low_bits::<T>() & !Self::high_bits()) as *mut T
+        self.ptr
+            .map_addr(|addr| addr & !low_bits::<T>() & !Self::high_bits())
     }
 
     pub fn with_tag(&self, tag: usize) -> Self {
@@ -100,25 +115,34 @@ impl<T> Tagged<T> {
     }
 
     pub fn with_high_tag(&self, tag: usize) -> Self {
-        Self::from(
-            (self.ptr as usize & !Self::high_bits()
-                | ((tag & ((1 << HIGH_TAG_WIDTH) - 1)) << Self::high_bits_pos()))
-                as *mut T,
-        )
+        Self::from(self.ptr.map_addr(|addr| {
+            addr & !Self::high_bits()
+                | ((tag & ((1 << HIGH_TAG_WIDTH) - 1)) << Self::high_bits_pos())
+        }))
     }
 
-    /// # Safety
-    ///
-    /// The pointer (without high and low tag bits) must be a valid location to dereference.
-    pub unsafe fn deref<'g>(&self) -> &'g T {
-        &*self.as_raw()
+    /// Casts the pointer to a pointer of another type, keeping the tags.
+    pub fn cast<U>(self) -> Tagged<U> {
+        Tagged::from(self.ptr.cast::<U>())
+    }
+
+    /// Returns the address of the pointer including the tags, exposing its provenance.
+    #[cfg(feature = "versioned")]
+    pub(crate) fn expose_addr(&self) -> usize {
+        self.ptr.expose_provenance()
+    }
+
+    /// Creates a pointer from an address returned by [`Tagged::expose_addr`].
+    #[cfg(feature = "versioned")]
+    pub(crate) fn with_exposed_addr(addr: usize) -> Self {
+        Self::from(core::ptr::with_exposed_provenance_mut::<T>(addr))
     }
 
     /// # Safety
     ///
     /// The pointer (without high and low tag bits) must be a valid location to dereference.
-    pub unsafe fn deref_mut<'g>(&mut self) -> &'g mut T {
-        &mut *self.as_raw()
+    pub unsafe fn deref<'g>(&self) -> &'g T {
+        &*self.as_raw()
     }
 
     /// # Safety
@@ -141,6 +165,12 @@ impl<T> Tagged<T> {
         // accessed epoch for the pointer.
         self.with_high_tag(0).ptr == other.with_high_tag(0).ptr
     }
+
+    /// Returns `true` if the two pointer values are identical, including the epoch tags.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn raw_bits_eq(self, other: Self) -> bool {
+        self.ptr == other.ptr
+    }
 }
 
 /// Returns a bitmask containing the unused least significant bits of an aligned pointer to `T`.
@@ -150,11 +180,112 @@ const fn low_bits<T>() -> usize {
 
 /// Returns the pointer with the given tag
 fn with_tag<T>(ptr: *mut T, tag: usize) -> *mut T {
-    ((ptr as usize & !low_bits::<T>()) | (tag & low_bits::<T>())) as *mut T
+    ptr.map_addr(|addr| (addr & !low_bits::<T>()) | (tag & low_bits::<T>()))
+}
+
+/// An atomic [`Tagged`] pointer.
+///
+/// Unlike a generic atomic cell over `Tagged<T>`, it is backed by an [`AtomicPtr`], so that
+/// the loaded pointers keep their provenance.
+#[repr(transparent)]
+pub(crate) struct AtomicTagged<T> {
+    inner: AtomicPtr<T>,
+}
+
+impl<T> AtomicTagged<T> {
+    #[inline]
+    pub fn new(ptr: Tagged<T>) -> Self {
+        Self {
+            inner: AtomicPtr::new(ptr.ptr),
+        }
+    }
+
+    #[inline]
+    pub fn null() -> Self {
+        Self::new(Tagged::null())
+    }
+
+    #[inline]
+    pub fn load(&self, order: Ordering) -> Tagged<T> {
+        Tagged::from(self.inner.load(order))
+    }
+
+    #[inline]
+    pub fn store(&self, ptr: Tagged<T>, order: Ordering) {
+        self.inner.store(ptr.ptr, order)
+    }
+
+    #[inline]
+    pub fn swap(&self, ptr: Tagged<T>, order: Ordering) -> Tagged<T> {
+        Tagged::from(self.inner.swap(ptr.ptr, order))
+    }
+
+    #[inline]
+    pub fn compare_exchange(
+        &self,
+        current: Tagged<T>,
+        new: Tagged<T>,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<Tagged<T>, Tagged<T>> {
+        self.inner
+            .compare_exchange(current.ptr, new.ptr, success, failure)
+            .map(Tagged::from)
+            .map_err(Tagged::from)
+    }
+
+    #[inline]
+    pub fn compare_exchange_weak(
+        &self,
+        current: Tagged<T>,
+        new: Tagged<T>,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<Tagged<T>, Tagged<T>> {
+        self.inner
+            .compare_exchange_weak(current.ptr, new.ptr, success, failure)
+            .map(Tagged::from)
+            .map_err(Tagged::from)
+    }
+
+    /// Bitwise "or" with the low tag bits of the current pointer, returning the previous value.
+    #[inline]
+    pub fn fetch_or(&self, tag: usize, order: Ordering) -> Tagged<T> {
+        // `AtomicPtr::fetch_or` needs Rust 1.91, so emulate it with a CAS loop.
+        let tag = low_bits::<T>() & tag;
+        let failure = match order {
+            Ordering::Release | Ordering::Relaxed => Ordering::Relaxed,
+            Ordering::AcqRel | Ordering::Acquire => Ordering::Acquire,
+            _ => Ordering::SeqCst,
+        };
+        let mut current = self.inner.load(failure);
+        loop {
+            let new = current.map_addr(|addr| addr | tag);
+            match self
+                .inner
+                .compare_exchange_weak(current, new, order, failure)
+            {
+                Ok(prev) => return Tagged::from(prev),
+                Err(prev) => current = prev,
+            }
+        }
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut Tagged<T> {
+        // SAFETY: `Tagged<T>` is a transparent wrapper of `*mut T`.
+        unsafe { transmute::<&mut *mut T, &mut Tagged<T>>(self.inner.get_mut()) }
+    }
+}
+
+impl<T> Default for AtomicTagged<T> {
+    fn default() -> Self {
+        Self::null()
+    }
 }
 
 pub(crate) struct RawAtomic<T> {
-    inner: Atomic<Tagged<T>>,
+    inner: AtomicTagged<T>,
 }
 
 unsafe impl<T: Send + Sync> Send for RawAtomic<T> {}
@@ -163,7 +294,7 @@ unsafe impl<T: Send + Sync> Sync for RawAtomic<T> {}
 impl<T> RawAtomic<T> {
     pub fn null() -> Self {
         Self {
-            inner: Atomic::new(Tagged::null()),
+            inner: AtomicTagged::null(),
         }
     }
 
@@ -204,12 +335,7 @@ impl<T> RawAtomic<T> {
     }
 
     pub fn fetch_or<'g>(&self, tag: usize, order: Ordering, _: &'g Guard) -> RawShared<'g, T> {
-        // HACK: The size and alignment of `Atomic<Tagged<T>>` will be same with `AtomicUsize`.
-        const_assert_eq!(size_of::<Atomic<Tagged<u8>>>(), size_of::<AtomicUsize>());
-        const_assert_eq!(align_of::<Atomic<Tagged<u8>>>(), align_of::<AtomicUsize>());
-        let inner = unsafe { &*(&self.inner as *const _ as *const AtomicUsize) };
-        let prev = inner.fetch_or(low_bits::<T>() & tag, order);
-        RawShared::from(prev as *const _)
+        RawShared::from(self.inner.fetch_or(tag, order))
     }
 }
 
@@ -311,3 +437,100 @@ impl<'g, T> RawShared<'g, T> {
         self.inner.ptr_eq(other.inner)
     }
 }
+
+/// Checks the invariants of the tag arithmetic on a pointer at `addr` to a type whose alignment
+/// is `1 << (align_log2 % 13)`, panicking if any of them is violated.
+///
+/// It is shared by the unit tests and the fuzz target (`fuzz/fuzz_targets/tagged.rs`).
+#[cfg(any(test, fuzzing))]
+pub fn check_tag_invariants(
+    align_log2: u8,
+    addr: usize,
+    tag: usize,
+    high_tag: usize,
+    other_high_tag: usize,
+) {
+    macro_rules! check_aligned {
+        ($($log2:literal => $align:literal),*) => {
+            match align_log2 % 13 {
+                $($log2 => {
+                    #[repr(align($align))]
+                    struct Aligned;
+                    check::<Aligned>(addr, tag, high_tag, other_high_tag)
+                })*
+                _ => unreachable!(),
+            }
+        };
+    }
+
+    fn check<T>(addr: usize, tag: usize, high_tag: usize, other_high_tag: usize) {
+        let high_mask = (1 << HIGH_TAG_WIDTH) - 1;
+        // An untagged pointer to `T` is aligned and has no epoch tag.
+        let addr = addr & !low_bits::<T>() & !Tagged::<T>::high_bits();
+        let original = Tagged::from(core::ptr::without_provenance_mut::<T>(addr));
+        assert_eq!(original.as_raw().addr(), addr);
+        assert_eq!((original.tag(), original.high_tag()), (0, 0));
+
+        let epoched = original.with_high_tag(high_tag);
+        assert_eq!(epoched.high_tag(), high_tag & high_mask);
+        assert_eq!(epoched.as_raw(), original.as_raw());
+        assert_eq!(epoched.tag(), 0);
+
+        let tagged = epoched.with_tag(tag);
+        assert_eq!(tagged.tag(), tag & low_bits::<T>());
+        assert_eq!(tagged.as_raw(), original.as_raw());
+        // Tagging never changes the epoch tag, and vice versa.
+        assert_eq!(tagged.high_tag(), epoched.high_tag());
+        let reepoched = tagged.with_high_tag(other_high_tag);
+        assert_eq!(reepoched.tag(), tagged.tag());
+        assert_eq!(reepoched.high_tag(), other_high_tag & high_mask);
+        assert_eq!(reepoched.as_raw(), original.as_raw());
+
+        // The epoch tag is invisible to the comparison, but the user tag is not.
+        assert!(reepoched.ptr_eq(tagged));
+        assert!(tagged.ptr_eq(original.with_tag(tag)));
+        assert_eq!(tagged.ptr_eq(original), tag & low_bits::<T>() == 0,);
+        assert_eq!(tagged.is_null(), addr == 0);
+        assert_eq!(tagged.with_tag(0).tag(), 0);
+    }
+
+    check_aligned!(
+        0 => 1, 1 => 2, 2 => 4, 3 => 8, 4 => 16, 5 => 32, 6 => 64,
+        7 => 128, 8 => 256, 9 => 512, 10 => 1024, 11 => 2048, 12 => 4096
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check_tag_invariants;
+
+    #[test]
+    fn tag_invariants() {
+        #[cfg(not(miri))]
+        const SAMPLES: usize = 10_000;
+        #[cfg(miri)]
+        const SAMPLES: usize = 100;
+
+        let edges = [0, 1, usize::MAX, usize::MAX >> 1, 1 << (usize::BITS - 1)];
+        for align_log2 in 0..13 {
+            for &addr in &edges {
+                for &tag in &edges {
+                    check_tag_invariants(align_log2, addr, tag, tag, !tag);
+                }
+            }
+        }
+
+        // A xorshift generator, to cover arbitrary bit patterns deterministically.
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as usize
+        };
+        for _ in 0..SAMPLES {
+            let align_log2 = next() as u8;
+            check_tag_invariants(align_log2, next(), next(), next(), next());
+        }
+    }
+}
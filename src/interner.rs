@@ -0,0 +1,181 @@
+//! An interner of reference-counted objects, which shares the equal objects.
+
+use std::borrow::Borrow;
+use std::collections::HashSet;
+use std::fmt::{Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+
+use crate::{Rc, RcObject};
+
+/// A thread-safe interner which returns the same [`Rc`] for the equal values.
+///
+/// It is handy for building a directed acyclic graph with structural sharing: the identical
+/// sub-nodes are allocated only once, and shared by the strong references to them.
+///
+/// The interner holds a strong reference to each interned object, so the objects are
+/// reclaimed only after the interner is dropped (or [`Interner::clear`]ed).
+///
+/// # Examples
+///
+/// ```
+/// use circ::{EdgeTaker, Interner, RcObject};
+///
+/// #[derive(PartialEq, Eq, Hash)]
+/// struct Leaf(u32);
+///
+/// unsafe impl RcObject for Leaf {
+///     fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+/// }
+///
+/// let interner = Interner::new();
+/// let a = interner.intern(Leaf(1));
+/// let b = interner.intern(Leaf(1));
+/// assert!(a.ptr_eq(&b));
+/// assert!(!a.ptr_eq(&interner.intern(Leaf(2))));
+/// ```
+pub struct Interner<T: RcObject> {
+    set: Mutex<HashSet<Interned<T>>>,
+}
+
+/// A non-null [`Rc`] which is hashed and compared by the object.
+struct Interned<T: RcObject>(Rc<T>);
+
+impl<T: RcObject> Interned<T> {
+    #[inline]
+    fn obj(&self) -> &T {
+        self.0.as_ref().unwrap()
+    }
+}
+
+impl<T: RcObject> Borrow<T> for Interned<T> {
+    #[inline]
+    fn borrow(&self) -> &T {
+        self.obj()
+    }
+}
+
+impl<T: RcObject + PartialEq> PartialEq for Interned<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.obj() == other.obj()
+    }
+}
+
+impl<T: RcObject + Eq> Eq for Interned<T> {}
+
+impl<T: RcObject + Hash> Hash for Interned<T> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.obj().hash(state)
+    }
+}
+
+impl<T: RcObject + Eq + Hash> Interner<T> {
+    /// Creates a new empty interner.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            set: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Returns an [`Rc`] to the interned object equal to `value`, interning a new object
+    /// holding `value` if there is none.
+    pub fn intern(&self, value: T) -> Rc<T> {
+        let mut set = self.set.lock().unwrap();
+        if let Some(interned) = set.get(&value) {
+            return interned.0.clone();
+        }
+        let rc = Rc::new(value);
+        set.insert(Interned(rc.clone()));
+        rc
+    }
+
+    /// Returns an [`Rc`] to the interned object equal to `value`, or `None` if there is none.
+    pub fn get(&self, value: &T) -> Option<Rc<T>> {
+        let set = self.set.lock().unwrap();
+        set.get(value).map(|interned| interned.0.clone())
+    }
+
+    /// Returns the number of the interned objects.
+    pub fn len(&self) -> usize {
+        self.set.lock().unwrap().len()
+    }
+
+    /// Returns `true` if there is no interned object.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Releases all the interned objects. The objects are reclaimed once the other strong
+    /// references to them are dropped as well.
+    pub fn clear(&self) {
+        // Release the references after unlocking, as it may run destructors.
+        let set = std::mem::take(&mut *self.set.lock().unwrap());
+        drop(set);
+    }
+}
+
+impl<T: RcObject + Eq + Hash> Default for Interner<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: RcObject> Debug for Interner<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Interner")
+            .field("len", &self.set.lock().unwrap().len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Interner;
+    use crate::{AtomicRc, EdgeTaker, Rc, RcObject};
+
+    #[derive(PartialEq, Eq, Hash)]
+    struct Leaf(u32);
+
+    unsafe impl RcObject for Leaf {
+        fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+    }
+
+    #[test]
+    fn intern_shares_equal_values() {
+        let interner = Interner::new();
+        let a = interner.intern(Leaf(1));
+        let b = interner.intern(Leaf(1));
+        let c = interner.intern(Leaf(2));
+        assert!(a.ptr_eq(&b));
+        assert!(!a.ptr_eq(&c));
+        assert_eq!(interner.len(), 2);
+        assert!(interner.get(&Leaf(1)).unwrap().ptr_eq(&a));
+        assert!(interner.get(&Leaf(3)).is_none());
+
+        // Build a DAG whose parents share the interned leaf.
+        struct Parent(AtomicRc<Leaf>);
+
+        unsafe impl RcObject for Parent {
+            fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+                out.take(&mut self.0);
+            }
+        }
+
+        let parents = (0..2)
+            .map(|_| Rc::new(Parent(AtomicRc::from(interner.intern(Leaf(1))))))
+            .collect::<Vec<_>>();
+        assert_eq!(interner.len(), 2);
+        drop(parents);
+        drop(b);
+
+        interner.clear();
+        assert!(interner.is_empty());
+        // The strong counts are decremented lazily.
+        crate::quiescent_barrier();
+        assert!(a.is_unique());
+    }
+}
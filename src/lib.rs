@@ -1,10 +1,166 @@
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "alloc-limit")]
+mod alloc_limit;
+pub mod collections;
+pub mod debug;
 pub(crate) mod ebr_impl;
+mod interner;
+pub mod local;
+#[cfg(feature = "dashmap")]
+pub mod map;
+mod once;
+pub mod pool;
+mod rc_vec;
+#[cfg(feature = "serde")]
+pub mod serde;
+#[cfg(feature = "stats")]
+mod stats;
 mod strong;
 mod utils;
+#[cfg(feature = "versioned")]
+mod versioned;
 mod weak;
+mod weak_cache;
 
-pub use ebr_impl::{cs, Guard};
+#[cfg(feature = "alloc-limit")]
+pub use alloc_limit::{allocated_bytes, set_alloc_limit};
+pub use ebr_impl::{
+    cs, is_pinned, register_epoch_callback, set_reclaim_thread, Guard, UnprotectedGuard,
+};
+pub use interner::Interner;
+pub use once::OnceRc;
+pub use rc_vec::RcVec;
+#[cfg(feature = "stats")]
+pub use stats::{stats, Stats};
+
+/// Internals exposed to the fuzz targets in `fuzz/`.
+#[cfg(fuzzing)]
+#[doc(hidden)]
+pub mod fuzzing {
+    pub use crate::ebr_impl::check_tag_invariants;
+}
+
+/// Utilities for the tests of the data structures built on CIRC.
+///
+/// This is available only with the `test-utils` feature, which enables the `stats` feature as
+/// well.
+#[cfg(feature = "test-utils")]
+pub mod test {
+    /// Returns the number of the objects which are allocated but not destructed yet, in the
+    /// whole process.
+    ///
+    /// As the objects are reclaimed lazily, call [`crate::quiescent_barrier`] before checking
+    /// it, e.g., to assert that a data structure leaks no node after it is dropped:
+    ///
+    /// ```
+    /// # use circ::{AtomicRc, EdgeTaker, RcObject};
+    /// # struct Node { next: AtomicRc<Node> }
+    /// # unsafe impl RcObject for Node {
+    /// #     fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) { out.take(&mut self.next); }
+    /// # }
+    /// let before = circ::test::live_object_count();
+    /// let head = AtomicRc::new(Node { next: AtomicRc::new(Node { next: AtomicRc::null() }) });
+    /// drop(head);
+    /// circ::quiescent_barrier();
+    /// assert_eq!(circ::test::live_object_count(), before);
+    /// ```
+    ///
+    /// The count includes the objects of every thread, so the other tests running concurrently
+    /// in the same process may affect it.
+    pub fn live_object_count() -> usize {
+        crate::stats().live()
+    }
+}
+
+/// Configurations of the backend EBR.
+///
+/// EBR is the only reclamation backend of this crate, and the pointers are not generic over it:
+/// [`Guard`] is a concrete type, and a [`Snapshot`] is protected by the critical section of the
+/// guard as a whole, rather than by a per-pointer shield (as hazard pointers would require).
+/// Thus, the backend cannot be selected at runtime, and only its parameters can be configured.
+/// Routing `pin` and `defer` through a trait object would add an indirect call to the hot
+/// paths, which are inlined now.
+pub mod ebr {
+    pub use crate::ebr_impl::set_pin_interval;
+}
+pub mod raw {
+    //! Unsafe building blocks for the extensions which manage the allocations of CIRC by
+    //! themselves (e.g., custom atomic pointer types).
+    //!
+    //! An allocation from [`alloc_inner`] has the same layout as the ones behind [`Rc`], with
+    //! the object at offset zero, followed by the reference counts. It owns the given number of
+    //! strong references, which must be balanced: the allocation is either freed with
+    //! [`free_inner`] by its only owner, or handed over to exactly that many [`Rc`]s with
+    //! [`Rc::from_ptr`], which release it through the backend EBR. An allocation handed over to
+    //! [`Rc`] must never be freed with [`free_inner`], and vice versa.
+    //!
+    //! [`Rc`]: crate::Rc
+    //! [`Rc::from_ptr`]: crate::Rc::from_ptr
+    //!
+    //! # Examples
+    //!
+    //! A minimal uniquely owned pointer, which can be shared as an [`Rc`] later on:
+    //!
+    //! ```
+    //! use std::ops::Deref;
+    //!
+    //! use circ::raw::{alloc_inner, free_inner, RcInner};
+    //! use circ::{EdgeTaker, Rc, RcObject};
+    //!
+    //! struct UniqueRc<T: RcObject>(*mut RcInner<T>);
+    //!
+    //! impl<T: RcObject> UniqueRc<T> {
+    //!     fn new(obj: T) -> Self {
+    //!         // SAFETY: The allocation is either freed in `drop` or handed over to an `Rc`.
+    //!         Self(unsafe { alloc_inner(obj, 1) })
+    //!     }
+    //!
+    //!     fn share(self) -> Rc<T> {
+    //!         let ptr = std::mem::ManuallyDrop::new(self).0;
+    //!         // SAFETY: The single strong reference is handed over to the `Rc`.
+    //!         unsafe { Rc::from_ptr(RcInner::data_ptr(ptr)) }
+    //!     }
+    //! }
+    //!
+    //! impl<T: RcObject> Deref for UniqueRc<T> {
+    //!     type Target = T;
+    //!
+    //!     fn deref(&self) -> &T {
+    //!         unsafe { (*self.0).data() }
+    //!     }
+    //! }
+    //!
+    //! impl<T: RcObject> Drop for UniqueRc<T> {
+    //!     fn drop(&mut self) {
+    //!         // SAFETY: It is never shared with other threads.
+    //!         unsafe { free_inner(self.0) }
+    //!     }
+    //! }
+    //!
+    //! struct Leaf(u32);
+    //!
+    //! unsafe impl RcObject for Leaf {
+    //!     fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+    //! }
+    //!
+    //! let unique = UniqueRc::new(Leaf(1));
+    //! assert_eq!((*unique).0, 1);
+    //! drop(unique);
+    //!
+    //! let shared = UniqueRc::new(Leaf(2)).share();
+    //! assert_eq!(shared.as_ref().unwrap().0, 2);
+    //! assert!(shared.is_unique());
+    //! ```
+
+    pub use crate::utils::{alloc_inner, free_inner, RcInner};
+}
 pub use strong::*;
+pub use utils::{
+    assert_not_trivially_copyable, available_tag_bits, quiescent_barrier, rc_inner_align,
+    rc_inner_size, set_ird_order, shutdown, Order,
+};
+#[cfg(feature = "versioned")]
+pub use versioned::*;
 pub use weak::*;
+pub use weak_cache::WeakCache;
@@ -0,0 +1,480 @@
+//! Single-threaded reference counting without atomics and EBR.
+//!
+//! [`LocalRc`] and [`LocalAtomicRc`] are the thread-local counterparts of [`crate::Rc`] and
+//! [`crate::AtomicRc`]. They are neither `Send` nor `Sync`, so the counts are plain [`Cell`]s
+//! and no critical section is needed to read them. An object is destructed as soon as its last
+//! reference is dropped, and the subsequent chain of unreachable objects is destructed
+//! iteratively with the edges taken by [`RcObject::pop_edges`], like the immediate recursive
+//! destruction of CIRC. Thus, dropping a long chain does not overflow the stack.
+//!
+//! The objects are described with the same [`RcObject`] trait as the ones of CIRC: the
+//! [`LocalRc`] edges are taken with [`EdgeTaker::take_local`], and the [`crate::Rc`] edges with
+//! [`EdgeTaker::take`] as usual. Then [`RcObject::on_reclaim`] is called, and the object is
+//! dropped. The thread is pinned only while the unreachable objects are being destructed, for
+//! `on_reclaim` and the [`crate::Rc`] edges, which are released through the EBR as usual.
+//!
+//! The converse does not hold: an object of CIRC must not have [`LocalRc`] edges, as it may be
+//! reclaimed on another thread. Its destruction panics if it calls [`EdgeTaker::take_local`].
+//!
+//! # Examples
+//!
+//! ```
+//! use circ::local::{LocalAtomicRc, LocalRc};
+//! use circ::{EdgeTaker, RcObject};
+//!
+//! struct ListNode {
+//!     item: usize,
+//!     next: LocalAtomicRc<Self>,
+//! }
+//!
+//! unsafe impl RcObject for ListNode {
+//!     fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+//!         out.take_local(self.next.take());
+//!     }
+//! }
+//!
+//! let head = (0..100_000).fold(LocalRc::null(), |next, item| {
+//!     LocalRc::new(ListNode {
+//!         item,
+//!         next: LocalAtomicRc::from(next),
+//!     })
+//! });
+//! assert_eq!(head.as_ref().unwrap().item, 99_999);
+//! drop(head);
+//! ```
+
+use std::cell::Cell;
+use std::fmt::{Debug, Formatter, Pointer};
+use std::marker::PhantomData;
+use std::mem::{forget, take, ManuallyDrop};
+use std::ptr;
+
+use crate::utils::release_edges;
+use crate::{cs, EdgeTaker, Guard, RcObject};
+
+struct LocalInner<T> {
+    strong: Cell<u32>,
+    data: T,
+}
+
+/// A type-erased edge taken with [`EdgeTaker::take_local`], whose strong reference is not
+/// released yet. If it is dropped, e.g., with an object of CIRC, it is released right away.
+pub(crate) struct LocalEdge {
+    ptr: *mut (),
+    release: unsafe fn(*mut (), &mut Vec<LocalEdge>, &mut Option<Guard>),
+}
+
+impl LocalEdge {
+    pub(crate) fn new<T: RcObject>(rc: LocalRc<T>) -> Self {
+        Self {
+            ptr: rc.into_raw().cast(),
+            release: release_one::<T>,
+        }
+    }
+
+    /// Releases the strong reference, and pushes the edges of the destructed object (if any)
+    /// into `pending` instead of releasing them.
+    unsafe fn release(self, pending: &mut Vec<LocalEdge>, guard: &mut Option<Guard>) {
+        let edge = ManuallyDrop::new(self);
+        (edge.release)(edge.ptr, pending, guard);
+    }
+}
+
+impl Drop for LocalEdge {
+    fn drop(&mut self) {
+        let mut pending = Vec::new();
+        let mut guard = None;
+        unsafe {
+            (self.release)(self.ptr, &mut pending, &mut guard);
+            release_all(pending, &mut guard);
+        }
+    }
+}
+
+/// Releases the edges in `pending`, and destructs the unreachable objects iteratively.
+unsafe fn release_all(mut pending: Vec<LocalEdge>, guard: &mut Option<Guard>) {
+    while let Some(edge) = pending.pop() {
+        edge.release(&mut pending, guard);
+    }
+}
+
+/// Releases a strong reference of `ptr`, and destructs the object if it was the last one.
+///
+/// The thread is pinned with `guard` when the first object is destructed.
+unsafe fn release_one<T: RcObject>(
+    ptr: *mut (),
+    pending: &mut Vec<LocalEdge>,
+    guard: &mut Option<Guard>,
+) {
+    let inner = ptr.cast::<LocalInner<T>>();
+    if inner.is_null() {
+        return;
+    }
+    let strong = (*inner).strong.get() - 1;
+    (*inner).strong.set(strong);
+    if strong == 0 {
+        let guard = guard.get_or_insert_with(cs);
+        let mut inner = Box::from_raw(inner);
+        let mut popped = Vec::new();
+        let mut out = EdgeTaker::new(&mut popped);
+        inner.data.pop_edges(&mut out);
+        pending.append(&mut out.into_local());
+        inner.data.on_reclaim(guard);
+        drop(inner);
+        release_edges(popped, guard);
+    }
+}
+
+/// A pointer to a thread-local reference-counted object, which may be null.
+pub struct LocalRc<T: RcObject> {
+    ptr: *mut LocalInner<T>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: RcObject> LocalRc<T> {
+    /// Constructs a null `LocalRc` pointer.
+    #[inline]
+    pub fn null() -> Self {
+        Self::from_raw(ptr::null_mut())
+    }
+
+    /// Constructs a new `LocalRc` by allocating a new reference-counted object.
+    #[inline]
+    pub fn new(obj: T) -> Self {
+        Self::from_raw(Box::into_raw(Box::new(LocalInner {
+            strong: Cell::new(1),
+            data: obj,
+        })))
+    }
+
+    #[inline]
+    fn from_raw(ptr: *mut LocalInner<T>) -> Self {
+        Self {
+            ptr,
+            _marker: PhantomData,
+        }
+    }
+
+    #[inline]
+    fn into_raw(self) -> *mut LocalInner<T> {
+        let ptr = self.ptr;
+        forget(self);
+        ptr
+    }
+
+    /// Returns `true` if the pointer is null.
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        self.ptr.is_null()
+    }
+
+    /// Dereferences the pointer and returns an immutable reference if it is not null.
+    #[inline]
+    pub fn as_ref(&self) -> Option<&T> {
+        unsafe { self.ptr.as_ref() }.map(|inner| &inner.data)
+    }
+
+    /// Returns the number of strong references to the object, or zero if it is null.
+    #[inline]
+    pub fn strong_count(&self) -> u32 {
+        unsafe { self.ptr.as_ref() }.map_or(0, |inner| inner.strong.get())
+    }
+
+    /// Returns `true` if the two pointers point to the same object (or both are null).
+    #[inline]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        ptr::eq(self.ptr, other.ptr)
+    }
+}
+
+impl<T: RcObject> Clone for LocalRc<T> {
+    /// # Panics
+    ///
+    /// Panics if the strong count overflows `u32`.
+    #[inline]
+    fn clone(&self) -> Self {
+        if let Some(inner) = unsafe { self.ptr.as_ref() } {
+            let strong = inner.strong.get().checked_add(1);
+            inner
+                .strong
+                .set(strong.expect("the strong count of a `LocalRc` overflowed"));
+        }
+        Self::from_raw(self.ptr)
+    }
+}
+
+impl<T: RcObject> Drop for LocalRc<T> {
+    #[inline]
+    fn drop(&mut self) {
+        if !self.ptr.is_null() {
+            drop(LocalEdge::new(take(self)));
+        }
+    }
+}
+
+impl<T: RcObject> Default for LocalRc<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::null()
+    }
+}
+
+impl<T: RcObject + Debug> Debug for LocalRc<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("LocalRc").field(&self.as_ref()).finish()
+    }
+}
+
+impl<T: RcObject> Pointer for LocalRc<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Pointer::fmt(&self.ptr, f)
+    }
+}
+
+/// A mutable memory location that contains a [`LocalRc<T>`].
+///
+/// Despite the name, which follows [`crate::AtomicRc`], it is just a non-atomic cell.
+pub struct LocalAtomicRc<T: RcObject> {
+    link: Cell<*mut LocalInner<T>>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: RcObject> LocalAtomicRc<T> {
+    /// Constructs a new `LocalAtomicRc` by allocating a new reference-counted object.
+    #[inline]
+    pub fn new(obj: T) -> Self {
+        Self::from(LocalRc::new(obj))
+    }
+
+    /// Constructs a new `LocalAtomicRc` containing a null pointer.
+    #[inline]
+    pub fn null() -> Self {
+        Self::from(LocalRc::null())
+    }
+
+    /// Loads a new [`LocalRc`] from this cell, incrementing the strong count.
+    #[inline]
+    pub fn load(&self) -> LocalRc<T> {
+        let rc = LocalRc::from_raw(self.link.get());
+        let cloned = rc.clone();
+        forget(rc);
+        cloned
+    }
+
+    /// Stores `ptr` into this cell, releasing the previous one.
+    #[inline]
+    pub fn store(&self, ptr: LocalRc<T>) {
+        drop(self.swap(ptr));
+    }
+
+    /// Stores `new` into this cell, returning the previous one.
+    #[inline]
+    pub fn swap(&self, new: LocalRc<T>) -> LocalRc<T> {
+        LocalRc::from_raw(self.link.replace(new.into_raw()))
+    }
+
+    /// Takes the stored pointer, leaving a null pointer, e.g., for [`EdgeTaker::take_local`].
+    #[inline]
+    pub fn take(&mut self) -> LocalRc<T> {
+        self.swap(LocalRc::null())
+    }
+
+    /// Dereferences the stored pointer and returns an immutable reference if it is not null.
+    ///
+    /// The reference is valid until this cell is modified, which the borrow of `self` cannot
+    /// prevent, as the cell is modified with a shared reference.
+    ///
+    /// # Safety
+    ///
+    /// The cell must not be modified while the returned reference is alive, unless another
+    /// [`LocalRc`] keeps the object alive.
+    #[inline]
+    pub unsafe fn as_ref(&self) -> Option<&T> {
+        self.link.get().as_ref().map(|inner| &inner.data)
+    }
+}
+
+impl<T: RcObject> From<LocalRc<T>> for LocalAtomicRc<T> {
+    #[inline]
+    fn from(value: LocalRc<T>) -> Self {
+        Self {
+            link: Cell::new(value.into_raw()),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: RcObject> Drop for LocalAtomicRc<T> {
+    #[inline]
+    fn drop(&mut self) {
+        drop(LocalRc::from_raw(self.link.get()));
+    }
+}
+
+impl<T: RcObject> Default for LocalAtomicRc<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::null()
+    }
+}
+
+impl<T: RcObject> Debug for LocalAtomicRc<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("LocalAtomicRc")
+            .field(&self.link.get())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::rc::Rc;
+
+    use super::{LocalAtomicRc, LocalRc};
+    use crate::{AtomicRc, EdgeTaker, Guard, RcObject};
+
+    struct Node {
+        next: LocalAtomicRc<Node>,
+        drops: Rc<Cell<usize>>,
+    }
+
+    unsafe impl RcObject for Node {
+        fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+            out.take_local(self.next.take());
+        }
+    }
+
+    impl Drop for Node {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+        }
+    }
+
+    #[test]
+    fn drop_deep_list() {
+        #[cfg(not(miri))]
+        const LENGTH: usize = 1_000_000;
+        #[cfg(miri)]
+        const LENGTH: usize = 1_000;
+
+        let drops = Rc::new(Cell::new(0));
+        let head = (0..LENGTH).fold(LocalRc::null(), |next, _| {
+            LocalRc::new(Node {
+                next: LocalAtomicRc::from(next),
+                drops: drops.clone(),
+            })
+        });
+
+        // A shared suffix is kept alive by the other reference.
+        let second = head.as_ref().unwrap().next.load();
+        assert_eq!(second.strong_count(), 2);
+        drop(head);
+        assert_eq!(drops.get(), 1);
+        assert_eq!(second.strong_count(), 1);
+
+        drop(second);
+        assert_eq!(drops.get(), LENGTH);
+    }
+
+    #[test]
+    fn store_and_swap() {
+        let drops = Rc::new(Cell::new(0));
+        let node = || {
+            LocalRc::new(Node {
+                next: LocalAtomicRc::null(),
+                drops: drops.clone(),
+            })
+        };
+
+        let cell = LocalAtomicRc::from(node());
+        let first = cell.load();
+        cell.store(node());
+        assert_eq!(drops.get(), 0);
+        drop(first);
+        assert_eq!(drops.get(), 1);
+
+        let second = cell.swap(LocalRc::null());
+        assert!(unsafe { cell.as_ref() }.is_none());
+        drop(second);
+        assert_eq!(drops.get(), 2);
+    }
+
+    #[test]
+    fn reclaim_like_circ() {
+        struct Leaf;
+
+        unsafe impl RcObject for Leaf {
+            fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+        }
+
+        struct Mixed {
+            next: LocalAtomicRc<Mixed>,
+            leaf: AtomicRc<Leaf>,
+            reclaims: Rc<Cell<usize>>,
+        }
+
+        unsafe impl RcObject for Mixed {
+            fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+                out.take_local(self.next.take());
+                out.take(&mut self.leaf);
+            }
+
+            fn on_reclaim(&mut self, _: &Guard) {
+                // The edges are taken before.
+                assert!(unsafe { self.next.as_ref() }.is_none());
+                self.reclaims.set(self.reclaims.get() + 1);
+            }
+        }
+
+        let reclaims = Rc::new(Cell::new(0));
+        let leaf = crate::Rc::new(Leaf);
+        let head = (0..3).fold(LocalRc::null(), |next, _| {
+            LocalRc::new(Mixed {
+                next: LocalAtomicRc::from(next),
+                leaf: AtomicRc::from(leaf.clone()),
+                reclaims: reclaims.clone(),
+            })
+        });
+
+        drop(head);
+        assert_eq!(reclaims.get(), 3);
+        // The `Rc` edges are released through the EBR.
+        assert!(leaf.is_unique());
+    }
+
+    #[test]
+    fn clone_overflow_panics() {
+        let drops = Rc::new(Cell::new(0));
+        let rc = LocalRc::new(Node {
+            next: LocalAtomicRc::null(),
+            drops: drops.clone(),
+        });
+        let strong = unsafe { &(*rc.ptr).strong };
+        strong.set(u32::MAX);
+        assert!(catch_unwind(AssertUnwindSafe(|| rc.clone())).is_err());
+        assert_eq!(strong.get(), u32::MAX);
+
+        strong.set(1);
+        drop(rc);
+        assert_eq!(drops.get(), 1);
+    }
+
+    #[test]
+    fn take_local_in_circ_object_panics() {
+        let drops = Rc::new(Cell::new(0));
+        let local = LocalRc::new(Node {
+            next: LocalAtomicRc::null(),
+            drops: drops.clone(),
+        });
+        let circ = crate::Rc::new(Node {
+            next: LocalAtomicRc::from(local.clone()),
+            drops: drops.clone(),
+        });
+        assert!(catch_unwind(AssertUnwindSafe(|| unsafe { circ.drop_now() })).is_err());
+        // The edge is leaked instead of being released, possibly on another thread.
+        assert_eq!(local.strong_count(), 2);
+        assert_eq!(drops.get(), 0);
+    }
+}
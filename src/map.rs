@@ -0,0 +1,62 @@
+//! Helpers for storing [`Rc`]s in a [`DashMap`], available with the `dashmap` feature.
+//!
+//! An [`Rc<T>`] is [`Send`] and [`Sync`] if `T` is, so it can be a value of a `DashMap` as is.
+//! A value removed or overwritten by the map is dropped without a [`Guard`], which is sound:
+//! [`Rc`]'s `Drop` pins the current thread by itself, only if the last strong reference is
+//! released and the object has to be reclaimed. Still, it pins once for each such value, and
+//! the shard lock of the map is held during the drop.
+//!
+//! [`guarded_remove`] removes an entry under a guard the caller already holds instead, and lets
+//! the caller read the removed value until the guard is dropped.
+
+use std::hash::{BuildHasher, Hash};
+
+use dashmap::DashMap;
+
+use crate::{Guard, Rc, RcObject, Snapshot};
+
+/// Removes the entry of `key` from `map`, and releases the removed [`Rc`] under `guard`.
+///
+/// Returns the removed key and a [`Snapshot`] of the removed value, which remains readable in
+/// the critical section of `guard` even if it was the last reference to the object, or `None`
+/// if `map` did not contain `key`.
+///
+/// # Examples
+///
+/// ```
+/// use circ::map::guarded_remove;
+/// use circ::{cs, EdgeTaker, Rc, RcObject};
+/// use dashmap::DashMap;
+///
+/// struct Session {
+///     user: String,
+/// }
+///
+/// unsafe impl RcObject for Session {
+///     fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+/// }
+///
+/// let sessions = DashMap::new();
+/// sessions.insert(1, Rc::new(Session { user: "alice".to_string() }));
+///
+/// let guard = &cs();
+/// let (_, session) = guarded_remove(&sessions, &1, guard).unwrap();
+/// assert_eq!(session.as_ref().unwrap().user, "alice");
+/// assert!(guarded_remove(&sessions, &1, guard).is_none());
+/// ```
+pub fn guarded_remove<'g, K, T, S>(
+    map: &DashMap<K, Rc<T>, S>,
+    key: &K,
+    guard: &'g Guard,
+) -> Option<(K, Snapshot<'g, T>)>
+where
+    K: Eq + Hash,
+    T: RcObject,
+    S: BuildHasher + Clone,
+{
+    let (key, value) = map.remove(key)?;
+    let snapshot = value.snapshot(guard);
+    // The object is not reclaimed until `guard` is dropped, so `snapshot` remains valid.
+    value.finalize(guard);
+    Some((key, snapshot))
+}
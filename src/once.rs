@@ -0,0 +1,137 @@
+//! A write-once cell of a reference-counted object, which is readable without a guard.
+
+use std::fmt::{Debug, Formatter};
+use std::mem::ManuallyDrop;
+use std::sync::OnceLock;
+
+use crate::{Rc, RcObject};
+
+/// A thread-safe cell which holds an object initialized at most once, like [`OnceLock`].
+///
+/// Once initialized, the object is never reclaimed: the strong reference held by the cell is
+/// never released, even if the `OnceRc` itself is dropped. Thus, reading it requires neither a
+/// [`crate::Guard`] nor any reference count traffic, and the returned reference is `'static`.
+/// This suits read-mostly global singletons shared across many threads (e.g., a configuration).
+///
+/// # Examples
+///
+/// ```
+/// use circ::{EdgeTaker, OnceRc, RcObject};
+///
+/// struct Config {
+///     verbose: bool,
+/// }
+///
+/// unsafe impl RcObject for Config {
+///     fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+/// }
+///
+/// static CONFIG: OnceRc<Config> = OnceRc::new();
+///
+/// assert!(CONFIG.get().is_none());
+/// let config = CONFIG.get_or_init(|| Config { verbose: true });
+/// assert!(config.verbose);
+/// assert!(std::ptr::eq(CONFIG.get().unwrap(), config));
+/// ```
+pub struct OnceRc<T: RcObject> {
+    cell: OnceLock<ManuallyDrop<Rc<T>>>,
+}
+
+impl<T: RcObject + 'static> OnceRc<T> {
+    /// Creates a new empty cell.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            cell: OnceLock::new(),
+        }
+    }
+
+    /// Returns a reference to the object, or `None` if the cell is not initialized yet.
+    #[inline]
+    pub fn get(&self) -> Option<&'static T> {
+        self.cell.get().map(|rc| Self::leak(rc))
+    }
+
+    /// Returns a reference to the object, initializing it with `f` if the cell is empty.
+    ///
+    /// If several threads call it concurrently, only one of them runs `f`, and the others
+    /// block until it is done. All of them get the same object.
+    #[inline]
+    pub fn get_or_init<F: FnOnce() -> T>(&self, f: F) -> &'static T {
+        Self::leak(self.cell.get_or_init(|| ManuallyDrop::new(Rc::new(f()))))
+    }
+
+    /// Returns a new [`Rc`] to the object, or a null one if the cell is not initialized yet.
+    #[inline]
+    pub fn rc(&self) -> Rc<T> {
+        self.cell.get().map_or_else(Rc::null, |rc| Rc::clone(rc))
+    }
+
+    #[inline]
+    fn leak(rc: &Rc<T>) -> &'static T {
+        let obj = rc.as_ref().unwrap();
+        // SAFETY: The strong reference in the cell is never released, so the object is never
+        // reclaimed.
+        unsafe { &*(obj as *const T) }
+    }
+}
+
+impl<T: RcObject + 'static> Default for OnceRc<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: RcObject + Debug + 'static> Debug for OnceRc<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("OnceRc").field(&self.get()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::OnceRc;
+    use crate::{EdgeTaker, RcObject};
+
+    #[test]
+    fn racing_initialization() {
+        const THREADS: usize = 16;
+        static INITS: AtomicUsize = AtomicUsize::new(0);
+
+        struct Config(usize);
+
+        unsafe impl RcObject for Config {
+            fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+        }
+
+        static CONFIG: OnceRc<Config> = OnceRc::new();
+
+        let addrs = std::thread::scope(|s| {
+            let handles = (0..THREADS)
+                .map(|t| {
+                    s.spawn(move || {
+                        let config = CONFIG.get_or_init(|| {
+                            INITS.fetch_add(1, Ordering::Relaxed);
+                            Config(t)
+                        });
+                        config as *const Config as usize
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|h| h.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        assert_eq!(INITS.load(Ordering::Relaxed), 1);
+        assert!(addrs.iter().all(|addr| *addr == addrs[0]));
+        let config = CONFIG.get().unwrap();
+        assert!(config.0 < THREADS);
+        assert_eq!(config as *const Config as usize, addrs[0]);
+        assert!(std::ptr::eq(CONFIG.rc().as_ref().unwrap(), config));
+    }
+}
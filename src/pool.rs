@@ -0,0 +1,224 @@
+//! Thread-local pools of the memory blocks for [`Rc`] objects.
+//!
+//! A data structure churning its nodes (e.g., a queue which allocates a node for each push)
+//! spends much of its time in the global allocator. An object allocated by [`RcPool::alloc`] is
+//! marked as pooled, and when it is reclaimed, its memory block is kept in the pool of the
+//! reclaiming thread instead of being freed. A later [`RcPool::alloc`] on that thread reuses
+//! the block.
+//!
+//! The pools are keyed by the [`Layout`] of the blocks, so a block may be reused for any type
+//! with the same size and alignment. Each thread keeps up to [`MAX_CACHED_BLOCKS`] blocks for
+//! each layout, and frees the rest to the global allocator. The cached blocks are freed when
+//! the thread exits.
+//!
+//! Note that the blocks are cached by the thread which reclaims the objects, which is not
+//! necessarily the one that allocated them. A consumer thread popping the nodes pushed by a
+//! producer thread may end up with all the cached blocks. [`RcPool::reserve`] fills the pool
+//! of the current thread in advance. The reclaimer thread (see [`crate::set_reclaim_thread`])
+//! never allocates, so it frees the blocks of the objects it destructs instead of caching them.
+//!
+//! # Examples
+//!
+//! ```
+//! use circ::pool::RcPool;
+//! use circ::{EdgeTaker, RcObject};
+//!
+//! struct Node {
+//!     item: usize,
+//! }
+//!
+//! unsafe impl RcObject for Node {
+//!     fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+//! }
+//!
+//! let pool = RcPool::new();
+//! pool.reserve(16);
+//! assert_eq!(pool.cached(), 16);
+//!
+//! let node = pool.alloc(Node { item: 42 });
+//! assert_eq!(node.as_ref().unwrap().item, 42);
+//! assert_eq!(pool.cached(), 15);
+//! ```
+
+use std::alloc::{alloc, dealloc, handle_alloc_error, Layout};
+use std::cell::RefCell;
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+
+use rustc_hash::FxHashMap;
+
+use crate::ebr_impl::is_reclaimer;
+use crate::utils::{Raw, RcInner};
+use crate::{Rc, RcObject};
+
+/// The maximum number of the memory blocks that a thread keeps for each layout.
+pub const MAX_CACHED_BLOCKS: usize = 4096;
+
+#[derive(Default)]
+struct FreeLists {
+    lists: FxHashMap<Layout, Vec<*mut u8>>,
+}
+
+impl Drop for FreeLists {
+    fn drop(&mut self) {
+        for (layout, blocks) in self.lists.drain() {
+            for block in blocks {
+                unsafe { dealloc(block, layout) };
+            }
+        }
+    }
+}
+
+thread_local! {
+    static FREE_LISTS: RefCell<FreeLists> = RefCell::default();
+}
+
+fn alloc_block(layout: Layout) -> *mut u8 {
+    // `RcInner` contains a 64-bit header, so the layout is never zero-sized.
+    let block = unsafe { alloc(layout) };
+    if block.is_null() {
+        handle_alloc_error(layout);
+    }
+    block
+}
+
+/// Takes a memory block of `layout` from the pool of the current thread, or allocates a new one
+/// if the pool is empty.
+pub(crate) fn take_block(layout: Layout) -> *mut u8 {
+    FREE_LISTS
+        .try_with(|lists| lists.borrow_mut().lists.get_mut(&layout)?.pop())
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| alloc_block(layout))
+}
+
+/// Returns a memory block of `layout` to the pool of the current thread, or frees it if the pool
+/// is full or already destroyed (i.e., the thread is exiting), or if the current thread is the
+/// reclaimer, whose pool would only grow.
+///
+/// # Safety
+///
+/// `block` must be allocated with `layout` by the global allocator, and must not be accessed
+/// anymore.
+pub(crate) unsafe fn recycle_block(block: *mut u8, layout: Layout) {
+    if is_reclaimer() {
+        return dealloc(block, layout);
+    }
+    let cached = FREE_LISTS.try_with(|lists| {
+        let mut lists = lists.borrow_mut();
+        let list = lists.lists.entry(layout).or_default();
+        if list.len() < MAX_CACHED_BLOCKS {
+            list.push(block);
+            true
+        } else {
+            false
+        }
+    });
+    if cached != Ok(true) {
+        dealloc(block, layout);
+    }
+}
+
+/// An allocator of [`Rc`] objects of `T` which reuses the memory blocks of the reclaimed ones.
+///
+/// See the [module-level documentation](self) for the details. A pool itself holds no memory:
+/// the blocks are kept in thread-local pools, shared by all `RcPool`s of the types with the same
+/// layout.
+pub struct RcPool<T> {
+    _marker: PhantomData<fn(T)>,
+}
+
+impl<T> Default for RcPool<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Debug for RcPool<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RcPool")
+            .field("cached", &self.cached())
+            .finish()
+    }
+}
+
+impl<T> RcPool<T> {
+    /// Creates a new `RcPool`.
+    pub const fn new() -> Self {
+        Self {
+            _marker: PhantomData,
+        }
+    }
+
+    fn layout() -> Layout {
+        Layout::new::<RcInner<T>>()
+    }
+
+    /// Allocates `additional` memory blocks in the pool of the current thread in advance, so
+    /// that the next `additional` allocations on this thread do not hit the global allocator.
+    ///
+    /// The reserved blocks are not limited by [`MAX_CACHED_BLOCKS`].
+    pub fn reserve(&self, additional: usize) {
+        let layout = Self::layout();
+        FREE_LISTS.with(|lists| {
+            let mut lists = lists.borrow_mut();
+            let list = lists.lists.entry(layout).or_default();
+            list.reserve(additional);
+            list.extend((0..additional).map(|_| alloc_block(layout)));
+        });
+    }
+
+    /// Returns the number of the memory blocks for `T` cached in the pool of the current thread.
+    pub fn cached(&self) -> usize {
+        FREE_LISTS
+            .try_with(|lists| {
+                lists
+                    .borrow()
+                    .lists
+                    .get(&Self::layout())
+                    .map_or(0, Vec::len)
+            })
+            .unwrap_or(0)
+    }
+}
+
+impl<T: RcObject> RcPool<T> {
+    /// Constructs a new [`Rc`] like [`Rc::new`], reusing a memory block in the pool of the
+    /// current thread if any.
+    ///
+    /// When the object is reclaimed, its memory block is returned to the pool of the reclaiming
+    /// thread.
+    #[inline]
+    pub fn alloc(&self, obj: T) -> Rc<T> {
+        Rc::from_raw(Raw::from(RcInner::alloc_pooled(obj, 1)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RcPool;
+    use crate::{EdgeTaker, Rc, RcObject};
+
+    struct Node {
+        _item: [usize; 3],
+    }
+
+    unsafe impl RcObject for Node {
+        fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+    }
+
+    #[test]
+    fn alloc_takes_reserved_blocks() {
+        let pool = RcPool::<Node>::new();
+        assert_eq!(pool.cached(), 0);
+        pool.reserve(2);
+        assert_eq!(pool.cached(), 2);
+
+        let nodes = (0..3)
+            .map(|i| pool.alloc(Node { _item: [i; 3] }))
+            .collect::<Vec<Rc<_>>>();
+        // The third one is allocated by the global allocator.
+        assert_eq!(pool.cached(), 0);
+        drop(nodes);
+    }
+}
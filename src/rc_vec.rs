@@ -0,0 +1,168 @@
+//! A vector of reference-counted pointers, which releases them in bulk.
+
+use std::fmt::{Debug, Formatter};
+use std::mem;
+use std::ops::Deref;
+
+use crate::{cs, Rc, RcObject};
+
+/// A vector of [`Rc`]s which releases all of them under a single [`crate::Guard`] on drop.
+///
+/// Dropping a `Vec<Rc<T>>` drops each [`Rc`] separately, and each of them checks whether the
+/// current thread is pinned (and pins it if not). `RcVec` instead pins the thread once and
+/// [`Rc::finalize`]s the elements with the shared guard, which makes tearing down a large batch
+/// of objects cheaper.
+///
+/// It dereferences to `[Rc<T>]` for reads. To modify the elements in place, convert it into a
+/// `Vec` with [`RcVec::into_vec`].
+///
+/// # Examples
+///
+/// ```
+/// use circ::{EdgeTaker, Rc, RcObject, RcVec};
+///
+/// struct Leaf(u32);
+///
+/// unsafe impl RcObject for Leaf {
+///     fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+/// }
+///
+/// let leaves = (0..4).map(|i| Rc::new(Leaf(i))).collect::<RcVec<_>>();
+/// assert_eq!(leaves.len(), 4);
+/// assert_eq!(leaves[2].as_ref().unwrap().0, 2);
+/// // Releases all the leaves under a single guard.
+/// drop(leaves);
+/// ```
+pub struct RcVec<T: RcObject> {
+    vec: Vec<Rc<T>>,
+}
+
+impl<T: RcObject> RcVec<T> {
+    /// Creates a new empty `RcVec`.
+    #[inline]
+    pub fn new() -> Self {
+        Self { vec: Vec::new() }
+    }
+
+    /// Creates a new empty `RcVec` with at least the specified capacity.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            vec: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Appends a pointer to the back of the vector.
+    #[inline]
+    pub fn push(&mut self, ptr: Rc<T>) {
+        self.vec.push(ptr);
+    }
+
+    /// Removes the last pointer from the vector and returns it, or `None` if it is empty.
+    #[inline]
+    pub fn pop(&mut self) -> Option<Rc<T>> {
+        self.vec.pop()
+    }
+
+    /// Converts this `RcVec` into a `Vec`, whose elements are dropped one by one as usual.
+    #[inline]
+    pub fn into_vec(mut self) -> Vec<Rc<T>> {
+        mem::take(&mut self.vec)
+    }
+}
+
+impl<T: RcObject> Drop for RcVec<T> {
+    fn drop(&mut self) {
+        if self.vec.is_empty() {
+            return;
+        }
+        let guard = cs();
+        for ptr in self.vec.drain(..) {
+            ptr.finalize(&guard);
+        }
+    }
+}
+
+impl<T: RcObject> Deref for RcVec<T> {
+    type Target = [Rc<T>];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.vec
+    }
+}
+
+impl<T: RcObject> Default for RcVec<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: RcObject> From<Vec<Rc<T>>> for RcVec<T> {
+    #[inline]
+    fn from(vec: Vec<Rc<T>>) -> Self {
+        Self { vec }
+    }
+}
+
+impl<T: RcObject> FromIterator<Rc<T>> for RcVec<T> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = Rc<T>>>(iter: I) -> Self {
+        Self {
+            vec: Vec::from_iter(iter),
+        }
+    }
+}
+
+impl<T: RcObject> Extend<Rc<T>> for RcVec<T> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = Rc<T>>>(&mut self, iter: I) {
+        self.vec.extend(iter);
+    }
+}
+
+impl<T: RcObject + Debug> Debug for RcVec<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.vec.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::RcVec;
+    use crate::{EdgeTaker, Rc, RcObject};
+
+    #[test]
+    fn drop_releases_all_elements() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+        struct Leaf;
+
+        unsafe impl RcObject for Leaf {
+            fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+        }
+
+        impl Drop for Leaf {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let shared = Rc::new(Leaf);
+        let mut leaves = (0..100).map(|_| Rc::new(Leaf)).collect::<RcVec<_>>();
+        leaves.push(shared.clone());
+        assert_eq!(leaves.len(), 101);
+        drop(leaves);
+
+        crate::quiescent_barrier();
+        assert_eq!(DROPS.load(Ordering::Relaxed), 100);
+        // The shared object is released, but still alive.
+        assert!(shared.is_unique());
+        drop(shared);
+        crate::quiescent_barrier();
+        assert_eq!(DROPS.load(Ordering::Relaxed), 101);
+    }
+}
@@ -0,0 +1,227 @@
+//! Serialization of object graphs with [`serde`], available with the `serde` feature.
+//!
+//! [`Rc`] and [`AtomicRc`] implement `Serialize` and `Deserialize` if their objects do. Instead
+//! of writing an object once for each pointer to it, which would duplicate the shared parts of
+//! a DAG, an object is written only at its first occurrence in the depth-first order, together
+//! with a numeric id, and the other pointers to it are written as the id only. On
+//! deserialization, the pointers with the same id become clones of the same [`Rc`], so a DAG
+//! built with [`Rc::clone`] round-trips with its shape.
+//!
+//! The ids are tracked in a thread-local table, from the outermost pointer being serialized
+//! until it is done, so the sharing is preserved within a single [`Rc`] or [`AtomicRc`]. To
+//! preserve it across several pointers in another value (e.g., a `Vec<Rc<T>>` or a struct with
+//! several roots), wrap the value with [`GraphSerializer`] and [`GraphDeserializer`], which keep
+//! a single table for the whole value.
+//!
+//! In the serde data model, a null pointer is written as `None`, the first occurrence of an
+//! object as `Some((id, Some(object)))`, and the other occurrences as `Some((id, None))`. The
+//! tags of the pointers are not preserved. A cycle is written finitely, as a reference back to
+//! the object being written, but it fails to deserialize, as the reference precedes the
+//! completion of the object.
+//!
+//! The serialization pins the current thread until it is done, so the objects it has visited
+//! are not reclaimed and their addresses are not reused in the meantime. Still, it is not an
+//! atomic snapshot of the graph if other threads modify the graph concurrently.
+//!
+//! # Examples
+//!
+//! ```
+//! use circ::serde::{GraphDeserializer, GraphSerializer};
+//! use circ::{EdgeTaker, Rc, RcObject};
+//!
+//! struct Leaf(u32);
+//!
+//! unsafe impl RcObject for Leaf {
+//!     fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+//! }
+//!
+//! impl serde::Serialize for Leaf {
+//!     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+//!         self.0.serialize(serializer)
+//!     }
+//! }
+//!
+//! impl<'de> serde::Deserialize<'de> for Leaf {
+//!     fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+//!         u32::deserialize(deserializer).map(Leaf)
+//!     }
+//! }
+//!
+//! let leaf = Rc::new(Leaf(7));
+//! let roots = vec![leaf.clone(), leaf];
+//! let json = serde_json::to_string(&GraphSerializer(&roots)).unwrap();
+//! assert_eq!(json, "[[0,7],[0,null]]");
+//!
+//! let roots = serde_json::from_str::<GraphDeserializer<Vec<Rc<Leaf>>>>(&json)
+//!     .unwrap()
+//!     .into_inner();
+//! assert!(roots[0].ptr_eq(&roots[1]));
+//! ```
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::hash_map::Entry;
+use std::sync::atomic::Ordering;
+use std::thread::LocalKey;
+
+use ::serde::de::Error as _;
+use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
+use rustc_hash::FxHashMap;
+
+use crate::{cs, AtomicRc, Guard, Rc, RcObject, Snapshot};
+
+type Table<V> = RefCell<Option<FxHashMap<u64, V>>>;
+
+thread_local! {
+    /// The ids of the objects written by the current serialization, keyed on their addresses.
+    static WRITTEN: Table<u64> = const { RefCell::new(None) };
+    /// The objects read by the current deserialization, as `Rc<T>`s keyed on their ids.
+    static READ: Table<Box<dyn Any>> = const { RefCell::new(None) };
+}
+
+/// Starts a table if none is active on the current thread, and ends it when dropped.
+struct Scope<V: 'static> {
+    table: &'static LocalKey<Table<V>>,
+    owner: bool,
+    _guard: Option<Guard>,
+}
+
+impl<V> Scope<V> {
+    fn enter(table: &'static LocalKey<Table<V>>, pin: bool) -> Self {
+        let owner = table.with_borrow_mut(|table| {
+            table.is_none() && {
+                *table = Some(FxHashMap::default());
+                true
+            }
+        });
+        Self {
+            table,
+            owner,
+            _guard: (owner && pin).then(cs),
+        }
+    }
+}
+
+impl<V> Drop for Scope<V> {
+    fn drop(&mut self) {
+        if self.owner {
+            // Drop the entries (e.g., `Rc`s) outside of the borrow.
+            drop(self.table.with_borrow_mut(Option::take));
+        }
+    }
+}
+
+/// Wraps a value to serialize all the pointers in it with a single table of ids, so that an
+/// object shared by several of them is written once.
+///
+/// See [the module documentation](self) for the format.
+pub struct GraphSerializer<'a, V: ?Sized>(pub &'a V);
+
+impl<V: Serialize + ?Sized> Serialize for GraphSerializer<'_, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let _scope = Scope::enter(&WRITTEN, true);
+        self.0.serialize(serializer)
+    }
+}
+
+/// Wraps a value to deserialize all the pointers in it with a single table of ids, so that the
+/// pointers written by [`GraphSerializer`] with the same id become clones of the same [`Rc`].
+pub struct GraphDeserializer<V>(pub V);
+
+impl<V> GraphDeserializer<V> {
+    /// Returns the deserialized value.
+    pub fn into_inner(self) -> V {
+        self.0
+    }
+}
+
+impl<'de, V: Deserialize<'de>> Deserialize<'de> for GraphDeserializer<V> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let _scope = Scope::enter(&READ, false);
+        V::deserialize(deserializer).map(Self)
+    }
+}
+
+fn serialize_ptr<T, S>(ptr: Snapshot<'_, T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: RcObject + Serialize,
+    S: Serializer,
+{
+    let Some(obj) = ptr.as_ref() else {
+        return serializer.serialize_none();
+    };
+    let _scope = Scope::enter(&WRITTEN, true);
+    let (id, first) = WRITTEN.with_borrow_mut(|written| {
+        let written = written.as_mut().unwrap();
+        let next = written.len() as u64;
+        match written.entry(ptr.as_ptr() as usize as u64) {
+            Entry::Occupied(entry) => (*entry.get(), false),
+            Entry::Vacant(entry) => (*entry.insert(next), true),
+        }
+    });
+    serializer.serialize_some(&(id, first.then_some(obj)))
+}
+
+fn deserialize_ptr<'de, T, D>(deserializer: D) -> Result<Rc<T>, D::Error>
+where
+    T: RcObject + Deserialize<'de> + 'static,
+    D: Deserializer<'de>,
+{
+    let _scope = Scope::enter(&READ, false);
+    let Some((id, obj)) = Option::<(u64, Option<T>)>::deserialize(deserializer)? else {
+        return Ok(Rc::null());
+    };
+    match obj {
+        Some(obj) => {
+            let rc = Rc::new(obj);
+            let fresh = READ.with_borrow_mut(|read| match read.as_mut().unwrap().entry(id) {
+                Entry::Occupied(_) => false,
+                Entry::Vacant(entry) => {
+                    entry.insert(Box::new(rc.clone()));
+                    true
+                }
+            });
+            if fresh {
+                Ok(rc)
+            } else {
+                Err(D::Error::custom(format_args!(
+                    "object {id} is defined twice"
+                )))
+            }
+        }
+        None => READ
+            .with_borrow(|read| {
+                let rc = read.as_ref().unwrap().get(&id)?;
+                rc.downcast_ref::<Rc<T>>().cloned()
+            })
+            .ok_or_else(|| {
+                D::Error::custom(format_args!(
+                    "object {id} is referred to before it is defined, or with another type"
+                ))
+            }),
+    }
+}
+
+impl<T: RcObject + Serialize> Serialize for Rc<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_ptr(self.snapshot(&cs()), serializer)
+    }
+}
+
+impl<'de, T: RcObject + Deserialize<'de> + 'static> Deserialize<'de> for Rc<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_ptr(deserializer)
+    }
+}
+
+impl<T: RcObject + Serialize> Serialize for AtomicRc<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_ptr(self.load(Ordering::Acquire, &cs()), serializer)
+    }
+}
+
+impl<'de, T: RcObject + Deserialize<'de> + 'static> Deserialize<'de> for AtomicRc<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_ptr(deserializer).map(AtomicRc::from)
+    }
+}
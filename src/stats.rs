@@ -0,0 +1,48 @@
+//! Aggregate reclamation statistics.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub(crate) static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+pub(crate) static DISPOSED: AtomicUsize = AtomicUsize::new(0);
+pub(crate) static PEAK_DEFERRED: AtomicUsize = AtomicUsize::new(0);
+pub(crate) static IRD_RECURSIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// A snapshot of the reclamation statistics of the whole process, returned by [`stats`].
+///
+/// The counters are updated with relaxed atomics, so a snapshot taken while other threads are
+/// running is not necessarily consistent (e.g., `disposed` may be momentarily larger than the
+/// `allocated` which has been read before).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// The number of objects allocated so far.
+    pub allocated: usize,
+    /// The number of objects destructed so far.
+    pub disposed: usize,
+    /// The largest number of functions that a thread-local bag has held before being passed to
+    /// the global queue.
+    pub peak_deferred: usize,
+    /// The number of successors destructed by the immediate recursive destruction, as opposed
+    /// to being deferred.
+    pub ird_recursions: usize,
+}
+
+impl Stats {
+    /// Returns the estimated number of objects which are not destructed yet.
+    #[inline]
+    pub fn live(&self) -> usize {
+        self.allocated.saturating_sub(self.disposed)
+    }
+}
+
+/// Returns the current reclamation statistics.
+///
+/// This is available only with the `stats` feature, which adds a few relaxed atomic operations
+/// to the allocation and reclamation paths.
+pub fn stats() -> Stats {
+    Stats {
+        allocated: ALLOCATED.load(Ordering::Relaxed),
+        disposed: DISPOSED.load(Ordering::Relaxed),
+        peak_deferred: PEAK_DEFERRED.load(Ordering::Relaxed),
+        ird_recursions: IRD_RECURSIONS.load(Ordering::Relaxed),
+    }
+}
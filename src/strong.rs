@@ -3,15 +3,19 @@ use std::{
     fmt::{Debug, Formatter, Pointer},
     hash::{Hash, Hasher},
     marker::PhantomData,
-    mem::{forget, size_of, take, transmute},
+    mem::{align_of, forget, size_of, take, transmute},
+    ptr::NonNull,
     sync::atomic::{AtomicUsize, Ordering},
 };
 
-use atomic::Atomic;
+use crossbeam_utils::Backoff;
 use static_assertions::const_assert;
 
-use crate::ebr_impl::{global_epoch, Guard, Tagged};
-use crate::utils::{try_ird_with_raw, DisposeContext, Raw, RcInner};
+use crate::ebr_impl::{cs, global_epoch, AtomicTagged, Guard, Tagged};
+use crate::local::{LocalEdge, LocalRc};
+use crate::utils::{
+    available_tag_bits, try_ird_with_raw, DisposeContext, IrdQueue, Raw, RcInner, MAX_WEAK_COUNT,
+};
 use crate::{Weak, WeakSnapshot};
 
 /// A common trait for reference-counted object types.
@@ -50,8 +54,42 @@ use crate::{Weak, WeakSnapshot};
 ///         out.take(&mut self.right);
 ///     }
 /// }
+///
+/// // An enum node takes the edges of the current variant.
+/// enum ExprNode {
+///     Leaf(usize),
+///     Neg(AtomicRc<Self>),
+///     Add { lhs: AtomicRc<Self>, rhs: AtomicRc<Self> },
+/// }
+///
+/// unsafe impl RcObject for ExprNode {
+///     fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+///         match self {
+///             ExprNode::Leaf(_) => {}
+///             ExprNode::Neg(operand) => out.take(operand),
+///             ExprNode::Add { lhs, rhs } => {
+///                 out.take(lhs);
+///                 out.take(rhs);
+///             }
+///         }
+///     }
+/// }
+///
+/// // A newtype of a node delegates to the inner node. If the inner type customizes the other
+/// // items of `RcObject` (e.g., `on_reclaim`), forward them as well.
+/// #[repr(transparent)]
+/// struct Head(ListNode);
+///
+/// unsafe impl RcObject for Head {
+///     fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+///         self.0.pop_edges(out);
+///     }
+/// }
 /// ```
 ///
+/// A `#[repr(transparent)]` newtype is layout-compatible with the inner type, so the pointers
+/// can also be reinterpreted between them with [`Rc::cast`] and [`Snapshot::cast`].
+///
 /// # Safety
 ///
 /// `out` should take the `AtomicRc`s and `Rc`s obtained from only the given object.
@@ -65,26 +103,138 @@ pub unsafe trait RcObject: Sized {
     /// `AtomicRc` schedule the decrement and destruction anyway. However, it may
     /// impact performance and memory usage, especially if the structure forms a long chain.
     fn pop_edges(&mut self, out: &mut EdgeTaker<'_>);
+
+    /// Runs custom cleanup logic on the object, right before it is dropped by CIRC.
+    ///
+    /// This method is called after [`RcObject::pop_edges`], with the guard of the critical section
+    /// in which the reclamation is taking place. It is useful when the cleanup needs to schedule
+    /// deferred work or release other [`Rc`]s efficiently (e.g., with [`Rc::finalize`]),
+    /// which is impossible in a plain `Drop` implementation.
+    ///
+    /// It may allocate new objects (e.g., to recycle a cleaned copy of this one into a free
+    /// list), and release other objects, with or without `guard`. The released objects are not
+    /// destructed within this call: their destruction is deferred like any other, and executed
+    /// by a later collection, which may take place on the same thread right after the current
+    /// one. Thus, the callbacks never nest, and their recursion depth does not grow with the
+    /// objects they release. Note that the object itself is being destructed, and it cannot be
+    /// revived by storing a pointer to it anywhere.
+    ///
+    /// By default, it does nothing.
+    #[inline]
+    fn on_reclaim(&mut self, guard: &Guard) {
+        let _ = guard;
+    }
+
+    /// Calls `visit` with each outgoing edge to an object of the same type, without taking it.
+    ///
+    /// This is a non-destructive companion to [`RcObject::pop_edges`], used only for debugging
+    /// (see [`crate::debug::trace_reachable`]). The edges to the objects of other types are not
+    /// visited.
+    ///
+    /// By default, it visits nothing.
+    #[inline]
+    fn visit_edges<'g>(&'g self, visit: &mut dyn FnMut(Snapshot<'g, Self>), guard: &'g Guard) {
+        let _ = (visit, guard);
+    }
+
+    /// Whether the objects of this type are destructed on the reclaimer thread, if it is
+    /// enabled with [`crate::set_reclaim_thread`].
+    ///
+    /// It suits the types which are expensive to drop (e.g., a node owning a large buffer), so
+    /// that their destruction does not add latency to the application threads. The types
+    /// which are cheap to drop are better destructed inline, avoiding the round trip to the
+    /// reclaimer.
+    ///
+    /// By default, it is `false`.
+    const DEFER_TO_BACKGROUND: bool = false;
+}
+
+/// A shorthand for the bounds of the objects managed by CIRC, for generic data structures.
+///
+/// It is implemented for every [`RcObject`], so generic code may write `T: Node` instead of
+/// spelling out the bounds. See [`ThreadSafeNode`] for the objects shared across threads.
+pub trait Node: RcObject {}
+
+impl<T: RcObject> Node for T {}
+
+/// A shorthand for the bounds of the objects managed by CIRC and shared across threads, i.e.,
+/// [`Node`] + [`Send`] + [`Sync`].
+///
+/// With it, the [`Rc`], [`AtomicRc`] and [`Snapshot`] pointers to the objects are [`Send`] and
+/// [`Sync`] as well.
+pub trait ThreadSafeNode: Node + Send + Sync {}
+
+impl<T: Node + Send + Sync> ThreadSafeNode for T {}
+
+/// A unit object has no outgoing edges. It is useful as a token or a marker, whose identity
+/// is the only thing that matters.
+unsafe impl RcObject for () {
+    fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
 }
 
 pub(crate) struct TryIRD {
     rc: Raw<()>,
-    ird: unsafe fn(Raw<()>, DisposeContext, u32),
+    ird: for<'d> unsafe fn(Raw<()>, DisposeContext<'d>, &mut IrdQueue<'d>, u32),
 }
 
 impl TryIRD {
-    pub(crate) unsafe fn try_ird(self, ctx: DisposeContext<'_>, succ_epoch: u32) {
-        (self.ird)(self.rc, ctx, succ_epoch)
+    pub(crate) fn is_null(&self) -> bool {
+        self.rc.is_null()
+    }
+
+    pub(crate) unsafe fn try_ird<'d>(
+        self,
+        ctx: DisposeContext<'d>,
+        queue: &mut IrdQueue<'d>,
+        succ_epoch: u32,
+    ) {
+        (self.ird)(self.rc, ctx, queue, succ_epoch)
     }
 }
 
 pub struct EdgeTaker<'r> {
     popped: &'r mut Vec<TryIRD>,
+    start: usize,
+    /// The edges taken with [`EdgeTaker::take_local`]. Unless the destruction of
+    /// [`crate::local`] takes them over, they are released when the `EdgeTaker` is dropped.
+    local: Vec<LocalEdge>,
 }
 
 impl<'r> EdgeTaker<'r> {
     pub(crate) fn new(popped: &'r mut Vec<TryIRD>) -> Self {
-        Self { popped }
+        let start = popped.len();
+        Self {
+            popped,
+            start,
+            local: Vec::new(),
+        }
+    }
+
+    /// Returns the edges taken with [`EdgeTaker::take_local`].
+    pub(crate) fn into_local(self) -> Vec<LocalEdge> {
+        self.local
+    }
+
+    /// Checks that [`EdgeTaker::take_local`] is not called while destructing an object of CIRC.
+    ///
+    /// # Panics
+    ///
+    /// Panics if it is called. The taken edges are leaked, as the object may be reclaimed on
+    /// another thread than the one owning them.
+    pub(crate) fn assert_no_local(self) {
+        if !self.local.is_empty() {
+            forget(self.local);
+            panic!("`EdgeTaker::take_local` is called while destructing an object of CIRC");
+        }
+    }
+
+    /// Returns the number of edges taken by this `EdgeTaker` so far, including null ones.
+    ///
+    /// It is useful to check that [`RcObject::pop_edges`] takes the expected edges
+    /// (e.g., with `debug_assert_eq!` at the end of it).
+    #[inline]
+    pub fn taken(&self) -> usize {
+        self.popped.len() - self.start + self.local.len()
     }
 
     /// Takes an underlying [`Rc`] from `outgoing` edge, and stores it in a local buffer.
@@ -96,6 +246,101 @@ impl<'r> EdgeTaker<'r> {
             ird: try_ird_with_raw::<T>,
         });
     }
+
+    /// Takes underlying [`Rc`]s from all `outgoing` edges, and stores them in a local buffer.
+    ///
+    /// This is a shorthand of calling [`EdgeTaker::take`] for each edge of a node with a
+    /// variable fan-out (e.g., edges stored in a `Vec`). For heterogeneous edges like
+    /// `(AtomicRc<A>, AtomicRc<B>)`, call [`EdgeTaker::take`] for each field instead.
+    pub fn take_all<'e, T: RcObject, E: OwnRc<T> + 'e>(
+        &mut self,
+        outgoing: impl IntoIterator<Item = &'e mut E>,
+    ) {
+        for edge in outgoing {
+            self.take(edge);
+        }
+    }
+
+    /// Takes underlying [`Rc`]s from a fixed-size array of `outgoing` edges, and stores them in
+    /// a local buffer. It is useful for K-ary trees and fixed fan-out graphs.
+    pub fn take_array<T: RcObject, E: OwnRc<T>, const K: usize>(&mut self, outgoing: &mut [E; K]) {
+        self.take_all(outgoing);
+    }
+
+    /// Takes the edges of a `Box`ed sub-structure owned by the object, by calling its
+    /// [`RcObject::pop_edges`] with this `EdgeTaker`, and then drops the `Box`, leaving `None`.
+    ///
+    /// An owned `Box` is not an edge: if it is simply left to the `Drop` of the object, it is
+    /// dropped normally, and so are the `Rc`s inside it, which are then released one by one
+    /// instead of being destructed together with the other edges. This method drains those
+    /// `Rc`s into the same buffer, so that they are destructed by CIRC like the direct edges.
+    /// Everything else in the sub-structure is dropped with the `Box` right away.
+    ///
+    /// The `RcObject` implementation of `C` only describes its edges, so `C` does not need to
+    /// be managed by an [`Rc`] itself. If `C` owns another `Box`ed sub-structure, its
+    /// `pop_edges` may call this method in turn. As the `Box`es are owned, each of them is
+    /// visited once, and the recursion is as deep as the nesting of the `Box`es.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circ::{AtomicRc, EdgeTaker, Rc, RcObject};
+    ///
+    /// struct Leaf;
+    ///
+    /// unsafe impl RcObject for Leaf {
+    ///     fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+    /// }
+    ///
+    /// // A sub-structure owned by a `Node`, not shared by any other object.
+    /// struct Children {
+    ///     left: AtomicRc<Leaf>,
+    ///     right: AtomicRc<Leaf>,
+    /// }
+    ///
+    /// unsafe impl RcObject for Children {
+    ///     fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+    ///         out.take(&mut self.left);
+    ///         out.take(&mut self.right);
+    ///     }
+    /// }
+    ///
+    /// struct Node {
+    ///     children: Option<Box<Children>>,
+    /// }
+    ///
+    /// unsafe impl RcObject for Node {
+    ///     fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+    ///         out.take_boxed(&mut self.children);
+    ///     }
+    /// }
+    ///
+    /// drop(Rc::new(Node {
+    ///     children: Some(Box::new(Children {
+    ///         left: AtomicRc::new(Leaf),
+    ///         right: AtomicRc::new(Leaf),
+    ///     })),
+    /// }));
+    /// ```
+    pub fn take_boxed<C: RcObject>(&mut self, boxed: &mut Option<Box<C>>) {
+        if let Some(mut boxed) = boxed.take() {
+            boxed.pop_edges(self);
+        }
+    }
+
+    /// Takes a [`LocalRc`] edge, which is an owned [`LocalRc`] or one taken out of a
+    /// [`LocalAtomicRc`](crate::local::LocalAtomicRc), and stores it in a local buffer.
+    ///
+    /// The taken [`LocalRc`]s will be destructed iteratively, so that dropping a long chain of
+    /// them does not overflow the stack. See [`crate::local`].
+    ///
+    /// It must be called only for the objects managed by [`LocalRc`]. An object of CIRC may be
+    /// reclaimed on another thread than the one owning the [`LocalRc`]s, whose counts are not
+    /// atomic, so the destruction of an object of CIRC panics if it takes a [`LocalRc`] edge.
+    /// The edge is leaked then.
+    pub fn take_local<T: RcObject>(&mut self, outgoing: LocalRc<T>) {
+        self.local.push(LocalEdge::new(outgoing));
+    }
 }
 
 /// A trait for types owning a strong reference count.
@@ -104,8 +349,90 @@ pub trait OwnRc<T: RcObject> {
     fn take(&mut self) -> Rc<T>;
 }
 
+/// A trait for types releasing their reference counts under a given [`Guard`].
+///
+/// The `Drop` of a pointer such as [`Rc`] checks whether the current thread is pinned, and pins
+/// it if it is not. A container owning several pointers can instead pin once with
+/// [`drop_with_guard`] and release all of them under the guard, like [`Rc::finalize`].
+///
+/// After [`DropWithGuard::drop_with`], the pointers are left null, so their own `Drop`s do
+/// nothing.
+///
+/// # Examples
+///
+/// ```
+/// use circ::{drop_with_guard, AtomicRc, DropWithGuard, EdgeTaker, Guard, Rc, RcObject};
+///
+/// struct Leaf;
+///
+/// unsafe impl RcObject for Leaf {
+///     fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+/// }
+///
+/// struct Registry {
+///     first: Rc<Leaf>,
+///     second: AtomicRc<Leaf>,
+///     third: Option<Rc<Leaf>>,
+/// }
+///
+/// impl DropWithGuard for Registry {
+///     fn drop_with(&mut self, guard: &Guard) {
+///         self.first.drop_with(guard);
+///         self.second.drop_with(guard);
+///         self.third.drop_with(guard);
+///     }
+/// }
+///
+/// impl Drop for Registry {
+///     fn drop(&mut self) {
+///         drop_with_guard(self);
+///     }
+/// }
+///
+/// drop(Registry {
+///     first: Rc::new(Leaf),
+///     second: AtomicRc::new(Leaf),
+///     third: Some(Rc::new(Leaf)),
+/// });
+/// ```
+pub trait DropWithGuard {
+    /// Releases the reference counts owned by this object under `guard`, leaving it empty
+    /// (e.g., null pointers).
+    fn drop_with(&mut self, guard: &Guard);
+}
+
+/// Pins the current thread once, and releases the reference counts owned by `value` with
+/// [`DropWithGuard::drop_with`].
+///
+/// It is intended to be called in the `Drop` of a type implementing [`DropWithGuard`].
+#[inline]
+pub fn drop_with_guard<D: DropWithGuard + ?Sized>(value: &mut D) {
+    value.drop_with(&cs());
+}
+
+impl<T: RcObject> DropWithGuard for Rc<T> {
+    #[inline]
+    fn drop_with(&mut self, guard: &Guard) {
+        take(self).finalize(guard);
+    }
+}
+
+impl<T: RcObject> DropWithGuard for AtomicRc<T> {
+    #[inline]
+    fn drop_with(&mut self, guard: &Guard) {
+        OwnRc::take(self).finalize(guard);
+    }
+}
+
+impl<T: RcObject> DropWithGuard for Option<Rc<T>> {
+    #[inline]
+    fn drop_with(&mut self, guard: &Guard) {
+        OwnRc::take(self).finalize(guard);
+    }
+}
+
 impl<T> Tagged<RcInner<T>> {
-    fn with_timestamp(self) -> Self {
+    pub(crate) fn with_timestamp(self) -> Self {
         if self.is_null() {
             self
         } else {
@@ -114,23 +441,170 @@ impl<T> Tagged<RcInner<T>> {
     }
 }
 
+/// Checks that `success` and `failure` are a valid pair of orderings for a `compare_exchange`.
+///
+/// # Panics
+///
+/// Panics if `failure` is `Release` or `AcqRel`, or if it is stronger than `success`.
+#[inline]
+#[track_caller]
+pub(crate) fn validate_cas_orderings(success: Ordering, failure: Ordering) {
+    use Ordering::*;
+    assert!(
+        matches!(failure, SeqCst | Acquire | Relaxed),
+        "failure ordering must be SeqCst, Acquire, or Relaxed, but {failure:?} was given"
+    );
+    assert!(
+        match failure {
+            SeqCst => success == SeqCst,
+            Acquire => matches!(success, SeqCst | AcqRel | Acquire),
+            _ => true,
+        },
+        "failure ordering must be equivalent to or weaker than the success ordering, \
+        but {failure:?} is stronger than {success:?}"
+    );
+}
+
+/// Performs `cas` on `expected`, and retries it while it fails only because the epoch recorded
+/// on the pointer is different, which is meaningless for clients. Other threads are likely
+/// relinking the same pointer in that case, so it backs off a little before each retry.
+///
+/// `same_but_epoch(current, expected)` tells such a failure apart, and `retries` counts them.
+/// Returns the previous value on success, and the current value on any other failure.
+#[inline(always)]
+pub(crate) fn cas_retrying_epoch<W: Copy>(
+    mut expected: W,
+    retries: &mut u32,
+    mut cas: impl FnMut(W) -> Result<W, W>,
+    same_but_epoch: impl Fn(W, W) -> bool,
+) -> Result<W, W> {
+    let backoff = Backoff::new();
+    loop {
+        crate::debug::count_cas_attempt();
+        match cas(expected) {
+            Err(current) if same_but_epoch(current, expected) => {
+                crate::debug::count_cas_epoch_retry();
+                *retries = retries.saturating_add(1);
+                backoff.spin();
+                expected = current;
+            }
+            Err(current) => {
+                crate::debug::count_cas_failure();
+                return Err(current);
+            }
+            prev => return prev,
+        }
+    }
+}
+
+/// A pointer which can be passed as `expected` to [`AtomicRc::compare_exchange`]: a
+/// [`Snapshot`], a `&`[`Rc`] or a [`WeakSnapshot`].
+///
+/// The comparison takes the address and the tag into account, as with a [`Snapshot`].
+///
+/// This trait is sealed and cannot be implemented outside of this crate.
+pub trait AsExpected<T>: sealed::Sealed<T> {}
+
+mod sealed {
+    use crate::utils::Raw;
+
+    pub trait Sealed<T> {
+        fn raw(&self) -> Raw<T>;
+    }
+}
+
+impl<'g, T: RcObject> sealed::Sealed<T> for Snapshot<'g, T> {
+    #[inline]
+    fn raw(&self) -> Raw<T> {
+        self.ptr
+    }
+}
+
+impl<'g, T: RcObject> AsExpected<T> for Snapshot<'g, T> {}
+
+impl<T: RcObject> sealed::Sealed<T> for &Rc<T> {
+    #[inline]
+    fn raw(&self) -> Raw<T> {
+        self.ptr
+    }
+}
+
+impl<T: RcObject> AsExpected<T> for &Rc<T> {}
+
+impl<'g, T> sealed::Sealed<T> for WeakSnapshot<'g, T> {
+    #[inline]
+    fn raw(&self) -> Raw<T> {
+        self.ptr
+    }
+}
+
+impl<'g, T> AsExpected<T> for WeakSnapshot<'g, T> {}
+
 /// Result of a failed `compare_exchange` operation.
 ///
 /// It returns the ownership of the pointer which was given as a parameter `desired`.
-pub struct CompareExchangeError<P, S> {
+pub struct CompareExchangeError<P, S, E = S> {
     /// The desired value that was passed to `compare_exchange`.
     pub desired: P,
     /// The current pointer value inside the atomic pointer.
     pub current: S,
+    /// The expected value that was passed to `compare_exchange`.
+    ///
+    /// It is handy in retry loops, which may want to compare it with `current`.
+    pub expected: E,
+}
+
+/// Result of a failed `compare_exchange_once` operation.
+pub enum CompareExchangeOnceError<P, S> {
+    /// The current pointer is the same as `expected` with the same tag, and only the epoch
+    /// recorded on the pointer by CIRC is different.
+    ///
+    /// This is not a failure from the perspective of the user. Retrying with `current` as
+    /// `expected` will succeed unless the pointer is modified in the meantime.
+    Retry(CompareExchangeError<P, S>),
+    /// The current pointer or its tag is different from `expected`.
+    Mismatch(CompareExchangeError<P, S>),
+}
+
+impl<P, S> CompareExchangeOnceError<P, S> {
+    /// Returns `true` if it is a [`CompareExchangeOnceError::Retry`].
+    #[inline]
+    pub fn is_retry(&self) -> bool {
+        matches!(self, Self::Retry(_))
+    }
+
+    /// Returns the underlying [`CompareExchangeError`], regardless of the variant.
+    #[inline]
+    pub fn into_inner(self) -> CompareExchangeError<P, S> {
+        match self {
+            Self::Retry(e) | Self::Mismatch(e) => e,
+        }
+    }
 }
 
+/// The error of [`AtomicRc::try_load`], which indicates that the pointer could not be protected
+/// and the load should be retried.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Retry;
+
 /// A thread-safe (atomic) mutable memory location that contains an [`Rc<T>`].
 ///
 /// The pointer must be properly aligned. Since it is aligned, a tag can be stored into the unused
 /// least significant bits of the address. For example, the tag for a pointer to a sized type `T`
 /// should be less than `(1 << align_of::<T>().trailing_zeros())`.
+///
+/// # Small objects
+///
+/// The objects are always stored out of line in a reference-counted allocation, even if `T` is
+/// smaller than a pointer. Storing a small `Copy` value inline in the atomic word would not keep
+/// the API: a [`Snapshot`] dereferences to a `&'g T` which must stay valid while the word is
+/// overwritten, and the pointers carry an identity ([`Rc::ptr_eq`], [`Rc::id`]), counts and
+/// [`Weak`]s, none of which an inline value has. The blocks of small nodes can instead be
+/// recycled with an [`crate::pool::RcPool`]; `cargo bench --bench pool` compares it with the
+/// global allocator on a queue of small nodes (`pool/pooled` and `pool/unpooled`). A payload
+/// which needs no identity or reclamation at all can be kept in a plain atomic integer instead.
 pub struct AtomicRc<T: RcObject> {
-    link: Atomic<Raw<T>>,
+    link: AtomicTagged<RcInner<T>>,
     _marker: PhantomData<T>,
 }
 
@@ -139,16 +613,19 @@ unsafe impl<T: RcObject + Send + Sync> Sync for AtomicRc<T> {}
 
 // Ensure that TaggedPtr<T> is 8-byte long,
 // so that lock-free atomic operations are possible.
-const_assert!(Atomic::<Raw<u8>>::is_lock_free());
 const_assert!(size_of::<Raw<u8>>() == size_of::<usize>());
-const_assert!(size_of::<Atomic<Raw<u8>>>() == size_of::<AtomicUsize>());
+const_assert!(size_of::<AtomicTagged<RcInner<u8>>>() == size_of::<AtomicUsize>());
+// The counters force the alignment of `RcInner`, so that at least 3 tag bits are available
+// even for zero-sized or byte-aligned objects.
+const_assert!(align_of::<RcInner<()>>() >= 8);
+const_assert!(align_of::<RcInner<u8>>() >= 8);
 
 impl<T: RcObject> AtomicRc<T> {
     /// Constructs a new `AtomicRc` by allocating a new reference-couned object.
     #[inline(always)]
     pub fn new(obj: T) -> Self {
         Self {
-            link: Atomic::new(Rc::<T>::new(obj).into_raw()),
+            link: AtomicTagged::new(Rc::<T>::new(obj).into_raw()),
             _marker: PhantomData,
         }
     }
@@ -157,11 +634,39 @@ impl<T: RcObject> AtomicRc<T> {
     #[inline(always)]
     pub fn null() -> Self {
         Self {
-            link: Atomic::new(Tagged::null()),
+            link: AtomicTagged::new(Tagged::null()),
             _marker: PhantomData,
         }
     }
 
+    /// Takes the [`Rc`] stored in this `AtomicRc`, leaving a null pointer, like
+    /// [`std::mem::take`].
+    ///
+    /// The exclusive borrow rules out any concurrent access, so no atomic operation or guard is
+    /// needed. It is handy for single-threaded mutation and teardown, and the `AtomicRc` remains
+    /// usable afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circ::{AtomicRc, EdgeTaker, Rc, RcObject};
+    ///
+    /// struct Leaf(usize);
+    ///
+    /// unsafe impl RcObject for Leaf {
+    ///     fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+    /// }
+    ///
+    /// let mut slot = AtomicRc::new(Leaf(1));
+    /// let taken = slot.take();
+    /// assert_eq!(taken.as_ref().map(|leaf| leaf.0), Some(1));
+    /// assert!(slot.take().is_null());
+    /// ```
+    #[inline]
+    pub fn take(&mut self) -> Rc<T> {
+        Rc::from_raw(take(self.link.get_mut()))
+    }
+
     /// Loads a [`Snapshot`] pointer from this `AtomicRc`.
     ///
     /// This method takes an [`Ordering`] argument which describes the memory ordering of this
@@ -175,29 +680,141 @@ impl<T: RcObject> AtomicRc<T> {
         Snapshot::from_raw(self.link.load(order), guard)
     }
 
+    /// Loads a [`Snapshot`] pointer from this `AtomicRc`, or returns [`Retry`] if the loaded
+    /// pointer could not be protected.
+    ///
+    /// With a backend which protects each pointer individually (e.g., hazard pointers), a load
+    /// may fail to protect an object retired between publishing and validating the protection.
+    /// This method surfaces such a failure to the caller, instead of retrying internally.
+    /// The current EBR backend protects every object in the critical section of `guard`, so
+    /// it always succeeds, just like [`AtomicRc::load`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is `Release` or `AcqRel`.
+    #[inline]
+    pub fn try_load<'g>(
+        &self,
+        order: Ordering,
+        guard: &'g Guard,
+    ) -> Result<Snapshot<'g, T>, Retry> {
+        Ok(self.load(order, guard))
+    }
+
+    /// Loads an [`Rc`] pointer from this `AtomicRc`, by incrementing the strong count of the
+    /// referent while it is protected by `guard`.
+    ///
+    /// It is a shorthand of `self.load(order, guard).counted()`. Unlike a [`Snapshot`], the
+    /// returned `Rc` may outlive `guard`, so it suits a function returning a loaded object to
+    /// its caller. On the other hand, it costs an atomic read-modify-write on the count, so
+    /// prefer [`AtomicRc::load`] for temporary accesses inside the critical section (e.g.,
+    /// traversals).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is `Release` or `AcqRel`.
+    #[inline]
+    pub fn load_owned(&self, order: Ordering, guard: &Guard) -> Rc<T> {
+        self.load(order, guard).counted()
+    }
+
+    /// Loads a [`Snapshot`] pointer from this `AtomicRc` together with its tag.
+    ///
+    /// This is equivalent to calling [`Snapshot::tag`] on the result of [`AtomicRc::load`],
+    /// but reads the pointer bits only once. The returned tag is the one set by `with_tag`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is `Release` or `AcqRel`.
+    #[inline]
+    pub fn load_decomposed<'g>(
+        &self,
+        order: Ordering,
+        guard: &'g Guard,
+    ) -> (Snapshot<'g, T>, usize) {
+        let raw = self.link.load(order);
+        (Snapshot::from_raw(raw, guard), raw.tag())
+    }
+
+    /// Loads a [`Snapshot`] pointer from this `AtomicRc` only if it has changed since `since`.
+    ///
+    /// Returns `None` if the current pointer is equal to `since` (see [`Snapshot::ptr_eq`]),
+    /// and the new [`Snapshot`] otherwise. It is handy for re-validating a link read before,
+    /// e.g., checking whether a tail pointer has advanced.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is `Release` or `AcqRel`.
+    #[inline]
+    pub fn load_if_changed<'g>(
+        &self,
+        since: Snapshot<'g, T>,
+        order: Ordering,
+        guard: &'g Guard,
+    ) -> Option<Snapshot<'g, T>> {
+        let current = self.load(order, guard);
+        if current.ptr_eq(since) {
+            None
+        } else {
+            Some(current)
+        }
+    }
+
     /// Stores an [`Rc`] pointer into this `AtomicRc`.
     ///
     /// This method takes an [`Ordering`] argument which describes the memory ordering of
     /// this operation.
+    ///
+    /// It does not panic, and it does not run any destructor of `T` by itself: the strong
+    /// reference of `ptr` is moved into this `AtomicRc`, and the previous pointer is released
+    /// by deferring its destruction to EBR. A destructor panicking later, during the
+    /// reclamation, unwinds from the thread which executes it, and the objects it was
+    /// destructing are leaked rather than released twice.
     #[inline]
     pub fn store(&self, ptr: Rc<T>, order: Ordering, guard: &Guard) {
-        let new_ptr = ptr.ptr;
-        let old_ptr = self.link.swap(new_ptr.with_timestamp(), order);
-        // Skip decrementing a strong count of the inserted pointer.
-        forget(ptr);
+        // The strong count of `ptr` is moved out before anything else, so it is never released
+        // twice.
+        let new_ptr = ptr.into_raw().with_timestamp();
+        let old_ptr = self.link.swap(new_ptr, order);
         unsafe {
             // Did not use `Rc::drop`, to reuse the given `guard`.
-            if let Some(cnt) = old_ptr.as_raw().as_mut() {
+            let cnt = old_ptr.as_raw();
+            if !cnt.is_null() {
                 RcInner::decrement_strong(cnt, 1, Some(guard));
             }
         }
     }
 
+    /// Stores an [`Rc`] pointer into this `AtomicRc`, assuming that it holds a null pointer.
+    ///
+    /// Unlike [`AtomicRc::store`], it neither reads nor releases the previous pointer, so it
+    /// saves the work for initializing slots which are known to be null, e.g., the buckets of
+    /// a new table or the links of a freshly allocated node.
+    ///
+    /// If the previous pointer is not null, its strong reference is leaked, and the object is
+    /// never reclaimed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is `Acquire` or `AcqRel`, and in debug builds if the previous pointer
+    /// is not null.
+    #[inline]
+    pub fn init(&self, ptr: Rc<T>, order: Ordering) {
+        // A separate load, so that the store below is the same in both profiles.
+        debug_assert!(
+            self.link.load(Ordering::Relaxed).is_null(),
+            "`AtomicRc::init` is called on a non-null `AtomicRc`"
+        );
+        self.link.store(ptr.into_raw().with_timestamp(), order);
+    }
+
     /// Stores a [`Snapshot`] or [`Rc`] pointer into this `AtomicRc`,
     /// returning the previous [`Rc`].
     ///
     /// This method takes an [`Ordering`] argument which describes the memory ordering of
     /// this operation.
+    ///
+    /// It does not panic. The strong references are moved without touching the counts.
     #[inline(always)]
     pub fn swap(&self, new: Rc<T>, order: Ordering) -> Rc<T> {
         let new_ptr = new.into_raw();
@@ -205,13 +822,34 @@ impl<T: RcObject> AtomicRc<T> {
         Rc::from_raw(old_ptr)
     }
 
+    /// Moves the pointer in this `AtomicRc` into `dst`, leaving a null pointer in this
+    /// `AtomicRc`. Returns the previous pointer in `dst` as an [`Rc`].
+    ///
+    /// It is equivalent to `dst.swap(self.swap(Rc::null(), order), order)`, but the moved
+    /// pointer is never materialized as an owned [`Rc`], and its reference count is untouched.
+    ///
+    /// This is two swaps, not a single atomic move. Between them, the pointer is in neither of
+    /// the two `AtomicRc`s, so another thread loading both of them may find the object in
+    /// neither place. `order` applies to each of the two swaps.
+    ///
+    /// The pointer is moved without being dereferenced, so the current EBR backend does not use
+    /// `guard`. It is taken for the backends which protect each pointer individually.
+    #[inline]
+    pub fn move_into(&self, dst: &AtomicRc<T>, order: Ordering, _: &Guard) -> Rc<T> {
+        let moved = self.link.swap(Tagged::null(), order);
+        let old_ptr = dst.link.swap(moved.with_timestamp(), order);
+        Rc::from_raw(old_ptr)
+    }
+
     /// Stores the [`Rc`] pointer `desired` into the atomic pointer if the current value is the
-    /// same as `expected` [`Snapshot`] pointer. The tag is also taken into account,
+    /// same as `expected` pointer. The tag is also taken into account,
     /// so two pointers to the same object, but with different tags, will not be considered equal.
     ///
+    /// `expected` may be a [`Snapshot`], a `&`[`Rc`] or a [`WeakSnapshot`] (see [`AsExpected`]).
+    ///
     /// The return value is a result indicating whether the desired pointer was written.
     /// On success the pointer that was in this `AtomicRc` is returned.
-    /// On failure the actual current value and `desired` are returned.
+    /// On failure the actual current value, `desired` and `expected` are returned.
     ///
     /// This method takes two [`Ordering`] arguments to describe the memory
     /// ordering of this operation. `success` describes the required ordering for the
@@ -221,41 +859,385 @@ impl<T: RcObject> AtomicRc<T> {
     /// of this operation `Relaxed`, and using `Release` makes the successful load
     /// `Relaxed`. The failure ordering can only be `SeqCst`, `Acquire` or `Relaxed`
     /// and must be equivalent to or weaker than the success ordering.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure` is `Release` or `AcqRel`, or if it is stronger than `success`.
     #[inline(always)]
-    pub fn compare_exchange<'g>(
+    pub fn compare_exchange<'g, E: AsExpected<T>>(
         &self,
-        expected: Snapshot<'g, T>,
+        expected: E,
         desired: Rc<T>,
         success: Ordering,
         failure: Ordering,
         guard: &'g Guard,
-    ) -> Result<Rc<T>, CompareExchangeError<Rc<T>, Snapshot<'g, T>>> {
-        let mut expected_raw = expected.ptr;
+    ) -> Result<Rc<T>, CompareExchangeError<Rc<T>, Snapshot<'g, T>, E>> {
+        let mut retries = 0;
+        self.compare_exchange_counted(expected, desired, success, failure, &mut retries, guard)
+    }
+
+    /// Performs [`AtomicRc::compare_exchange`], adding the number of its internal retries to
+    /// `retries`.
+    ///
+    /// The pointers carry the epochs in which they were last stored, and a CAS whose `expected`
+    /// differs from the current pointer only in the epoch is retried internally, without being
+    /// reported to the caller. The count lets the caller observe such retries, e.g., to back off
+    /// more aggressively in its own retry loop after many of them. A high count usually
+    /// indicates that other threads keep relinking the same pointer across epochs (epoch
+    /// churn), rather than a real contention on the slot, which shows up as failures instead.
+    ///
+    /// Unlike the global counters of the `debug-counters` feature, the count is per call, so it
+    /// can drive the control flow of the caller. It is added to, not overwritten, so it can
+    /// accumulate over the iterations of an outer loop.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure` is `Release` or `AcqRel`, or if it is stronger than `success`.
+    #[inline(always)]
+    pub fn compare_exchange_counted<'g, E: AsExpected<T>>(
+        &self,
+        expected: E,
+        desired: Rc<T>,
+        success: Ordering,
+        failure: Ordering,
+        retries: &mut u32,
+        guard: &'g Guard,
+    ) -> Result<Rc<T>, CompareExchangeError<Rc<T>, Snapshot<'g, T>, E>> {
+        validate_cas_orderings(success, failure);
         let desired_raw = desired.ptr.with_timestamp();
-        loop {
-            match self
-                .link
-                .compare_exchange(expected_raw, desired_raw, success, failure)
-            {
-                Ok(_) => {
-                    // Skip decrementing a strong count of the inserted pointer.
-                    forget(desired);
-                    let rc = Rc::from_raw(expected_raw);
-                    return Ok(rc);
-                }
-                Err(current_raw) => {
-                    if current_raw.ptr_eq(expected_raw) {
-                        expected_raw = current_raw;
-                    } else {
-                        let current = Snapshot::from_raw(current_raw, guard);
-                        return Err(CompareExchangeError { desired, current });
-                    }
-                }
+        match cas_retrying_epoch(
+            expected.raw(),
+            retries,
+            |expected_raw| {
+                self.link
+                    .compare_exchange(expected_raw, desired_raw, success, failure)
+            },
+            Raw::ptr_eq,
+        ) {
+            Ok(prev_raw) => {
+                // Skip decrementing a strong count of the inserted pointer.
+                forget(desired);
+                Ok(Rc::from_raw(prev_raw))
             }
+            Err(current_raw) => Err(CompareExchangeError {
+                desired,
+                current: Snapshot::from_raw(current_raw, guard),
+                expected,
+            }),
         }
     }
 
-    /// Stores the [`Rc`] pointer `desired` into the atomic pointer if the current value is the
+    /// Performs [`AtomicRc::compare_exchange`], installing `desired` with the tag of `expected`
+    /// incremented by one, so that the tag serves as a version counter against the ABA problem.
+    ///
+    /// Without it, a slot which is changed from `A` to `B` and then back to `A` looks untouched
+    /// to a thread holding a stale [`Snapshot`] of `A`, so its CAS succeeds. If every update of
+    /// the slot goes through this method, the tag differs after each of them, and the stale CAS
+    /// fails. The tag of `desired` is overwritten.
+    ///
+    /// The counter takes all the [`crate::available_tag_bits`] of `T` and wraps around, so it
+    /// leaves no tag bits for user flags, and an ABA over a multiple of `1 << bits` updates
+    /// still goes unnoticed. With the 3 bits of a typical node, it detects up to 7 intermediate
+    /// updates. The objects themselves are never reclaimed while a [`Snapshot`] to them
+    /// exists, so the address is reused only if the object is relinked.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure` is `Release` or `AcqRel`, or if it is stronger than `success`.
+    #[inline]
+    pub fn compare_exchange_aba<'g>(
+        &self,
+        expected: Snapshot<'g, T>,
+        desired: Rc<T>,
+        success: Ordering,
+        failure: Ordering,
+        guard: &'g Guard,
+    ) -> Result<Rc<T>, CompareExchangeError<Rc<T>, Snapshot<'g, T>>> {
+        // `with_tag` truncates the tag, so the counter wraps around.
+        let desired = desired.with_tag(expected.tag().wrapping_add(1));
+        self.compare_exchange(expected, desired, success, failure, guard)
+    }
+
+    /// Publishes `ptr` to other threads, by storing it with `Release` ordering.
+    ///
+    /// It is [`AtomicRc::store`] with the ordering for the publishing side of a handoff: the
+    /// writes to the object before publishing it are visible to the threads which load it
+    /// with [`AtomicRc::consume`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circ::{cs, AtomicRc, EdgeTaker, Rc, RcObject};
+    ///
+    /// struct Message {
+    ///     text: String,
+    /// }
+    ///
+    /// unsafe impl RcObject for Message {
+    ///     fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+    /// }
+    ///
+    /// let slot = AtomicRc::<Message>::null();
+    ///
+    /// std::thread::scope(|s| {
+    ///     s.spawn(|| {
+    ///         let message = Rc::new(Message {
+    ///             text: "hello".to_string(),
+    ///         });
+    ///         slot.publish(message, &cs());
+    ///     });
+    ///     s.spawn(|| loop {
+    ///         let guard = cs();
+    ///         // The text written before publishing is visible once the message is consumed.
+    ///         if let Some(message) = slot.consume(&guard).as_ref() {
+    ///             assert_eq!(message.text, "hello");
+    ///             break;
+    ///         }
+    ///         std::thread::yield_now();
+    ///     });
+    /// });
+    /// ```
+    #[inline]
+    pub fn publish(&self, ptr: Rc<T>, guard: &Guard) {
+        self.store(ptr, Ordering::Release, guard)
+    }
+
+    /// Loads a [`Snapshot`] pointer published by another thread, with `Acquire` ordering.
+    ///
+    /// It is [`AtomicRc::load`] with the ordering for the consuming side of a handoff: the
+    /// writes to the object before it was published by [`AtomicRc::publish`] or
+    /// [`AtomicRc::publish_cas`] are visible through the returned [`Snapshot`].
+    #[inline]
+    pub fn consume<'g>(&self, guard: &'g Guard) -> Snapshot<'g, T> {
+        self.load(Ordering::Acquire, guard)
+    }
+
+    /// Publishes `desired` if the current pointer is the same as `expected`, as
+    /// [`AtomicRc::compare_exchange`] does.
+    ///
+    /// On success, the store is `Release`, so `desired` is published as by
+    /// [`AtomicRc::publish`], and the load is `Acquire`, so the replaced pointer may be
+    /// dereferenced as well (`AcqRel`). On failure, the current pointer is loaded with `Acquire`,
+    /// as by [`AtomicRc::consume`].
+    ///
+    /// # Examples
+    ///
+    /// A Treiber stack pushing nodes with the presets:
+    ///
+    /// ```
+    /// use circ::{cs, AtomicRc, EdgeTaker, Rc, RcObject};
+    ///
+    /// struct Node {
+    ///     value: usize,
+    ///     next: AtomicRc<Node>,
+    /// }
+    ///
+    /// unsafe impl RcObject for Node {
+    ///     fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+    ///         out.take(&mut self.next);
+    ///     }
+    /// }
+    ///
+    /// let top = AtomicRc::<Node>::null();
+    ///
+    /// std::thread::scope(|s| {
+    ///     for value in 0..4 {
+    ///         let top = &top;
+    ///         s.spawn(move || {
+    ///             let guard = &cs();
+    ///             let mut node = Rc::new(Node {
+    ///                 value,
+    ///                 next: AtomicRc::null(),
+    ///             });
+    ///             loop {
+    ///                 let curr = top.consume(guard);
+    ///                 let node_ref = unsafe { node.deref_mut() };
+    ///                 node_ref.next = AtomicRc::from(curr.counted());
+    ///                 match top.publish_cas(curr, node, guard) {
+    ///                     Ok(_) => break,
+    ///                     Err(e) => node = e.desired,
+    ///                 }
+    ///             }
+    ///         });
+    ///     }
+    /// });
+    ///
+    /// let guard = &cs();
+    /// let mut values = vec![];
+    /// let mut curr = top.consume(guard);
+    /// while let Some(node) = curr.as_ref() {
+    ///     values.push(node.value);
+    ///     curr = node.next.consume(guard);
+    /// }
+    /// values.sort();
+    /// assert_eq!(values, [0, 1, 2, 3]);
+    /// ```
+    #[inline]
+    pub fn publish_cas<'g, E: AsExpected<T>>(
+        &self,
+        expected: E,
+        desired: Rc<T>,
+        guard: &'g Guard,
+    ) -> Result<Rc<T>, CompareExchangeError<Rc<T>, Snapshot<'g, T>, E>> {
+        self.compare_exchange(
+            expected,
+            desired,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+            guard,
+        )
+    }
+
+    /// Performs [`AtomicRc::compare_exchange`], but on failure, returns only `desired` without
+    /// loading the current pointer.
+    ///
+    /// It suits the loops which restart from scratch on failure (e.g., re-traversing a list to
+    /// find a new position), where the current pointer would be discarded anyway. Thus, it does
+    /// not need a [`Guard`] either. On success, the pointer that was in this `AtomicRc` is
+    /// returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure` is `Release` or `AcqRel`, or if it is stronger than `success`.
+    #[inline]
+    pub fn compare_exchange_discard(
+        &self,
+        expected: Snapshot<'_, T>,
+        desired: Rc<T>,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<Rc<T>, Rc<T>> {
+        validate_cas_orderings(success, failure);
+        let desired_raw = desired.ptr.with_timestamp();
+        match cas_retrying_epoch(
+            expected.ptr,
+            &mut 0,
+            |expected_raw| {
+                self.link
+                    .compare_exchange(expected_raw, desired_raw, success, failure)
+            },
+            Raw::ptr_eq,
+        ) {
+            Ok(prev_raw) => {
+                // Skip decrementing a strong count of the inserted pointer.
+                forget(desired);
+                Ok(Rc::from_raw(prev_raw))
+            }
+            Err(_) => Err(desired),
+        }
+    }
+
+    /// Performs [`AtomicRc::compare_exchange`], and on success, returns a [`Snapshot`] of the
+    /// newly installed pointer instead of the previous one.
+    ///
+    /// This fuses the common "CAS, then load what I installed" sequence. The strong reference of
+    /// `desired` is moved into this `AtomicRc`, and the one of the previous pointer is released.
+    /// On failure, it is the same as [`AtomicRc::compare_exchange`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure` is `Release` or `AcqRel`, or if it is stronger than `success`.
+    #[inline]
+    pub fn compare_exchange_load<'g>(
+        &self,
+        expected: Snapshot<'g, T>,
+        desired: Rc<T>,
+        success: Ordering,
+        failure: Ordering,
+        guard: &'g Guard,
+    ) -> Result<Snapshot<'g, T>, CompareExchangeError<Rc<T>, Snapshot<'g, T>>> {
+        // The installed object is protected by the guard, even if it is unlinked concurrently.
+        let installed = desired.snapshot(guard);
+        let prev = self.compare_exchange(expected, desired, success, failure, guard)?;
+        prev.finalize(guard);
+        Ok(installed)
+    }
+
+    /// Stores the [`Rc`] pointer `desired` into the atomic pointer if the current value is the
+    /// same as `expected` [`Snapshot`] pointer, performing exactly one hardware CAS.
+    ///
+    /// CIRC records an epoch on every pointer stored in an `AtomicRc`, and
+    /// [`AtomicRc::compare_exchange`] transparently retries when the pointer and the tag are the
+    /// same as `expected` but the epoch is not. This method reports such a case as a
+    /// [`CompareExchangeOnceError::Retry`] instead, so that the caller can decide when (and
+    /// whether) to retry, e.g., with its own backoff. Other failures are reported as a
+    /// [`CompareExchangeOnceError::Mismatch`].
+    ///
+    /// The orderings are the same as [`AtomicRc::compare_exchange`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure` is `Release` or `AcqRel`, or if it is stronger than `success`.
+    #[inline]
+    pub fn compare_exchange_once<'g>(
+        &self,
+        expected: Snapshot<'g, T>,
+        desired: Rc<T>,
+        success: Ordering,
+        failure: Ordering,
+        guard: &'g Guard,
+    ) -> Result<Rc<T>, CompareExchangeOnceError<Rc<T>, Snapshot<'g, T>>> {
+        validate_cas_orderings(success, failure);
+        crate::debug::count_cas_attempt();
+        match self.link.compare_exchange(
+            expected.ptr,
+            desired.ptr.with_timestamp(),
+            success,
+            failure,
+        ) {
+            Ok(_) => {
+                // Skip decrementing a strong count of the inserted pointer.
+                forget(desired);
+                Ok(Rc::from_raw(expected.ptr))
+            }
+            Err(current_raw) => {
+                let err = CompareExchangeError {
+                    desired,
+                    current: Snapshot::from_raw(current_raw, guard),
+                    expected,
+                };
+                if current_raw.ptr_eq(expected.ptr) {
+                    crate::debug::count_cas_epoch_retry();
+                    Err(CompareExchangeOnceError::Retry(err))
+                } else {
+                    crate::debug::count_cas_failure();
+                    Err(CompareExchangeOnceError::Mismatch(err))
+                }
+            }
+        }
+    }
+
+    /// Performs [`AtomicRc::compare_exchange`], and on success, calls `on_success` with a
+    /// [`Snapshot`] of the newly installed pointer before returning.
+    ///
+    /// This fuses the common "CAS, then initialize the installed node" pattern (e.g., linking a
+    /// successor in a queue's enqueue) without loading the pointer again. The callback runs in
+    /// the same critical section, and it is not called if the CAS fails.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure` is `Release` or `AcqRel`, or if it is stronger than `success`.
+    #[inline]
+    pub fn compare_exchange_then<'g, F>(
+        &self,
+        expected: Snapshot<'g, T>,
+        desired: Rc<T>,
+        success: Ordering,
+        failure: Ordering,
+        guard: &'g Guard,
+        on_success: F,
+    ) -> Result<Rc<T>, CompareExchangeError<Rc<T>, Snapshot<'g, T>>>
+    where
+        F: FnOnce(Snapshot<'g, T>),
+    {
+        // The installed object is protected by the guard, even if it is unlinked concurrently.
+        let installed = desired.snapshot(guard);
+        let prev = self.compare_exchange(expected, desired, success, failure, guard)?;
+        on_success(installed);
+        Ok(prev)
+    }
+
+    /// Stores the [`Rc`] pointer `desired` into the atomic pointer if the current value is the
     /// same as `expected` [`Snapshot`] pointer. The tag is also taken into account,
     /// so two pointers to the same object, but with different tags, will not be considered equal.
     ///
@@ -263,7 +1245,7 @@ impl<T: RcObject> AtomicRc<T> {
     /// even when comparison succeeds, which can result in more efficient code on some platforms.
     /// The return value is a result indicating whether the desired pointer was written.
     /// On success the pointer that was in this `AtomicRc` is returned.
-    /// On failure the actual current value and `desired` are returned.
+    /// On failure the actual current value, `desired` and `expected` are returned.
     ///
     /// This method takes two [`Ordering`] arguments to describe the memory
     /// ordering of this operation. `success` describes the required ordering for the
@@ -273,6 +1255,10 @@ impl<T: RcObject> AtomicRc<T> {
     /// of this operation `Relaxed`, and using `Release` makes the successful load
     /// `Relaxed`. The failure ordering can only be `SeqCst`, `Acquire` or `Relaxed`
     /// and must be equivalent to or weaker than the success ordering.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure` is `Release` or `AcqRel`, or if it is stronger than `success`.
     #[inline(always)]
     pub fn compare_exchange_weak<'g>(
         &self,
@@ -282,27 +1268,61 @@ impl<T: RcObject> AtomicRc<T> {
         failure: Ordering,
         guard: &'g Guard,
     ) -> Result<Rc<T>, CompareExchangeError<Rc<T>, Snapshot<'g, T>>> {
-        let mut expected_raw = expected.ptr;
+        validate_cas_orderings(success, failure);
         let desired_raw = desired.ptr.with_timestamp();
-        loop {
-            match self
-                .link
-                .compare_exchange_weak(expected_raw, desired_raw, success, failure)
-            {
-                Ok(_) => {
-                    // Skip decrementing a strong count of the inserted pointer.
-                    forget(desired);
-                    let rc = Rc::from_raw(expected_raw);
-                    return Ok(rc);
-                }
-                Err(current_raw) => {
-                    if current_raw.ptr_eq(expected_raw) {
-                        expected_raw = current_raw;
-                    } else {
-                        let current = Snapshot::from_raw(current_raw, guard);
-                        return Err(CompareExchangeError { desired, current });
-                    }
-                }
+        match cas_retrying_epoch(
+            expected.ptr,
+            &mut 0,
+            |expected_raw| {
+                self.link
+                    .compare_exchange_weak(expected_raw, desired_raw, success, failure)
+            },
+            Raw::ptr_eq,
+        ) {
+            Ok(prev_raw) => {
+                // Skip decrementing a strong count of the inserted pointer.
+                forget(desired);
+                Ok(Rc::from_raw(prev_raw))
+            }
+            Err(current_raw) => Err(CompareExchangeError {
+                desired,
+                current: Snapshot::from_raw(current_raw, guard),
+                expected,
+            }),
+        }
+    }
+
+    /// Stores the [`Rc`] pointer `desired` into the atomic pointer if it currently holds a null
+    /// pointer without a tag.
+    ///
+    /// This is a specialized version of [`AtomicRc::compare_exchange`] with a null `expected`,
+    /// which is common when installing an object into an empty slot. On failure, `desired` is
+    /// returned to the caller unchanged.
+    ///
+    /// This method takes an [`Ordering`] argument which describes the memory ordering of the
+    /// read-modify-write operation. The ordering of the load operation on failure is derived
+    /// from it, in the same way as `AtomicPtr::compare_exchange` requires: `Release` becomes
+    /// `Relaxed`, and `AcqRel` becomes `Acquire`.
+    #[inline]
+    pub fn cas_from_null(&self, desired: Rc<T>, order: Ordering, _: &Guard) -> Result<(), Rc<T>> {
+        let failure = match order {
+            Ordering::Release => Ordering::Relaxed,
+            Ordering::AcqRel => Ordering::Acquire,
+            order => order,
+        };
+        crate::debug::count_cas_attempt();
+        match self
+            .link
+            .compare_exchange(Raw::null(), desired.ptr.with_timestamp(), order, failure)
+        {
+            Ok(_) => {
+                // Skip decrementing a strong count of the inserted pointer.
+                forget(desired);
+                Ok(())
+            }
+            Err(_) => {
+                crate::debug::count_cas_failure();
+                Err(desired)
             }
         }
     }
@@ -330,6 +1350,10 @@ impl<T: RcObject> AtomicRc<T> {
     ///
     /// [`AtomicRc::compare_exchange`] subsumes this method, but it is more efficient because it
     /// does not require [`Rc`] as `desired`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure` is `Release` or `AcqRel`, or if it is stronger than `success`.
     #[inline]
     pub fn compare_exchange_tag<'g>(
         &self,
@@ -339,26 +1363,185 @@ impl<T: RcObject> AtomicRc<T> {
         failure: Ordering,
         guard: &'g Guard,
     ) -> Result<Snapshot<'g, T>, CompareExchangeError<Snapshot<'g, T>, Snapshot<'g, T>>> {
-        let mut expected_raw = expected.ptr;
-        let desired_raw = expected_raw.with_tag(desired_tag).with_timestamp();
+        validate_cas_orderings(success, failure);
+        let desired_raw = expected.ptr.with_tag(desired_tag).with_timestamp();
+        match cas_retrying_epoch(
+            expected.ptr,
+            &mut 0,
+            |expected_raw| {
+                self.link
+                    .compare_exchange(expected_raw, desired_raw, success, failure)
+            },
+            Raw::ptr_eq,
+        ) {
+            Ok(prev_raw) => Ok(Snapshot::from_raw(prev_raw, guard)),
+            Err(current_raw) => Err(CompareExchangeError {
+                desired: Snapshot::from_raw(desired_raw, guard),
+                current: Snapshot::from_raw(current_raw, guard),
+                expected,
+            }),
+        }
+    }
+
+    /// Stores the pointer of `expected` with the `bit`-th tag bit set, if the current value is
+    /// the same as `expected`. The other tag bits are kept as they are in `expected`.
+    ///
+    /// This is a shorthand of [`AtomicRc::compare_exchange_tag`] for using each tag bit as an
+    /// independent flag, and it returns the same as that method.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure` is `Release` or `AcqRel`, or if it is stronger than `success`.
+    /// In debug builds, also panics if `bit` is not less than [`available_tag_bits`].
+    ///
+    /// [`available_tag_bits`]: crate::available_tag_bits
+    #[inline]
+    pub fn compare_exchange_set_bit<'g>(
+        &self,
+        expected: Snapshot<'g, T>,
+        bit: u32,
+        success: Ordering,
+        failure: Ordering,
+        guard: &'g Guard,
+    ) -> Result<Snapshot<'g, T>, CompareExchangeError<Snapshot<'g, T>, Snapshot<'g, T>>> {
+        debug_assert!(
+            bit < available_tag_bits::<T>(),
+            "the tag bit {bit} is out of the available tag bits"
+        );
+        let tag = expected.tag() | (1 << bit);
+        self.compare_exchange_tag(expected, tag, success, failure, guard)
+    }
+
+    /// Stores the pointer of `expected` with the `bit`-th tag bit cleared, if the current value is
+    /// the same as `expected`. The other tag bits are kept as they are in `expected`.
+    ///
+    /// This is a shorthand of [`AtomicRc::compare_exchange_tag`] for using each tag bit as an
+    /// independent flag, and it returns the same as that method.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure` is `Release` or `AcqRel`, or if it is stronger than `success`.
+    /// In debug builds, also panics if `bit` is not less than [`available_tag_bits`].
+    ///
+    /// [`available_tag_bits`]: crate::available_tag_bits
+    #[inline]
+    pub fn compare_exchange_clear_bit<'g>(
+        &self,
+        expected: Snapshot<'g, T>,
+        bit: u32,
+        success: Ordering,
+        failure: Ordering,
+        guard: &'g Guard,
+    ) -> Result<Snapshot<'g, T>, CompareExchangeError<Snapshot<'g, T>, Snapshot<'g, T>>> {
+        debug_assert!(
+            bit < available_tag_bits::<T>(),
+            "the tag bit {bit} is out of the available tag bits"
+        );
+        let tag = expected.tag() & !(1 << bit);
+        self.compare_exchange_tag(expected, tag, success, failure, guard)
+    }
+
+    /// Sets the tag of the atomic pointer to the maximum of the current tag and `tag`, returning
+    /// the previous tag. The pointer itself and its epoch bits are left untouched.
+    ///
+    /// If `tag` uses more bits than the unused least significant bits of the pointer to `T`,
+    /// it will be truncated to be fit.
+    ///
+    /// This is useful to encode a monotonic state (e.g., a version or a lifecycle stage) in the
+    /// tag. It is implemented with a CAS loop, so it retries whenever the pointer or the tag is
+    /// concurrently changed. Among concurrent calls, the tag never decreases, and it ends up at
+    /// the maximum of the requested tags. If the current tag is already at least `tag`, nothing
+    /// is written and `order` applies only to the load.
+    ///
+    /// `fetch_max_tag` takes an [`Ordering`] argument which describes the memory ordering of
+    /// this operation. All ordering modes are possible. Note that using `Acquire` makes the store
+    /// part of this operation `Relaxed`, and using `Release` makes the load part `Relaxed`.
+    #[inline]
+    pub fn fetch_max_tag(&self, tag: usize, order: Ordering, _: &Guard) -> usize {
+        let failure = match order {
+            Ordering::Release | Ordering::Relaxed => Ordering::Relaxed,
+            Ordering::AcqRel | Ordering::Acquire => Ordering::Acquire,
+            _ => Ordering::SeqCst,
+        };
+        let mut current = self.link.load(failure);
         loop {
+            let prev = current.tag();
+            let desired = current.with_tag(tag);
+            if desired.tag() <= prev {
+                return prev;
+            }
             match self
                 .link
-                .compare_exchange(expected_raw, desired_raw, success, failure)
+                .compare_exchange_weak(current, desired, order, failure)
             {
-                Ok(current_raw) => return Ok(Snapshot::from_raw(current_raw, guard)),
-                Err(current_raw) => {
-                    if current_raw.ptr_eq(expected_raw) {
-                        expected_raw = current_raw;
-                    } else {
-                        return Err(CompareExchangeError {
-                            desired: Snapshot::from_raw(desired_raw, guard),
-                            current: Snapshot::from_raw(current_raw, guard),
-                        });
-                    }
-                }
+                Ok(_) => return prev,
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    /// Fetches the tag of the atomic pointer, and applies `f` to it to get a new tag, which is
+    /// stored if `f` returns `Some`. The pointer itself and its epoch bits are left untouched.
+    ///
+    /// Like `fetch_update` of the standard atomic types, it retries with the up-to-date tag
+    /// whenever the pointer or the tag is concurrently changed, so `f` may be called multiple
+    /// times. Returns `Ok` with the previous tag if a new tag was stored, or `Err` with the
+    /// current tag if `f` returned `None`.
+    ///
+    /// If the new tag uses more bits than the unused least significant bits of the pointer to
+    /// `T`, it will be truncated to be fit.
+    ///
+    /// `set_order` describes the memory ordering of the successful CAS, and `fetch_order`
+    /// describes the ordering of the loads, as in [`AtomicRc::compare_exchange`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fetch_order` is `Release` or `AcqRel`, or if it is stronger than `set_order`.
+    #[inline]
+    pub fn fetch_update_tag<F>(
+        &self,
+        set_order: Ordering,
+        fetch_order: Ordering,
+        _: &Guard,
+        mut f: F,
+    ) -> Result<usize, usize>
+    where
+        F: FnMut(usize) -> Option<usize>,
+    {
+        validate_cas_orderings(set_order, fetch_order);
+        let mut current = self.link.load(fetch_order);
+        while let Some(tag) = f(current.tag()) {
+            match self.link.compare_exchange_weak(
+                current,
+                current.with_tag(tag),
+                set_order,
+                fetch_order,
+            ) {
+                Ok(_) => return Ok(current.tag()),
+                Err(actual) => current = actual,
             }
         }
+        Err(current.tag())
+    }
+
+    /// Consumes this `AtomicRc` and returns an iterator which walks the chain of objects starting
+    /// from it, detaching each object from its successor and yielding it as an owned [`Rc`].
+    ///
+    /// `next_fn` returns the link to the successor of the given object (e.g., `|node| &node.next`).
+    ///
+    /// This is intended for tearing down a linked structure while examining its objects.
+    /// It does not synchronize with other threads, so it should be used only when the chain is
+    /// exclusively owned (e.g., in `Drop` of the data structure). Otherwise, concurrent writers
+    /// may lose their updates on the detached links.
+    #[inline]
+    pub fn drain<F>(mut self, next_fn: F) -> DrainIter<T, F>
+    where
+        F: FnMut(&T) -> &AtomicRc<T>,
+    {
+        DrainIter {
+            next: self.take(),
+            next_fn,
+        }
     }
 
     // get_mut is unsound, because it allows writing ref without link epoch.
@@ -382,7 +1565,7 @@ impl<T: RcObject> AtomicRc<T> {
 impl<T: RcObject> OwnRc<T> for AtomicRc<T> {
     #[inline]
     fn take(&mut self) -> Rc<T> {
-        Rc::from_raw(take(self.link.get_mut()))
+        AtomicRc::take(self)
     }
 }
 
@@ -391,8 +1574,8 @@ impl<T: RcObject> Drop for AtomicRc<T> {
     fn drop(&mut self) {
         let ptr = (*self.link.get_mut()).as_raw();
         unsafe {
-            if let Some(cnt) = ptr.as_mut() {
-                RcInner::decrement_strong(cnt, 1, None);
+            if !ptr.is_null() {
+                RcInner::decrement_strong(ptr, 1, None);
             }
         }
     }
@@ -410,7 +1593,7 @@ impl<T: RcObject> From<Rc<T>> for AtomicRc<T> {
     fn from(value: Rc<T>) -> Self {
         let ptr = value.into_raw();
         Self {
-            link: Atomic::new(ptr),
+            link: AtomicTagged::new(ptr),
             _marker: PhantomData,
         }
     }
@@ -425,7 +1608,10 @@ impl<T: RcObject> From<&Rc<T>> for AtomicRc<T> {
 
 impl<T: RcObject> Debug for AtomicRc<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        Debug::fmt(&self.link.load(Ordering::Relaxed), f)
+        self.link
+            .load(Ordering::Relaxed)
+            .debug_struct(f, "AtomicRc")
+            .finish()
     }
 }
 
@@ -438,12 +1624,15 @@ impl<T: RcObject> Pointer for AtomicRc<T> {
 /// A reference-counted pointer to an object of type `T`.
 ///
 /// When `T` implements [`Send`] and [`Sync`], [`Rc<T>`] also implements these traits.
+/// An `Rc` is not tied to the thread that created it: it can be sent to another thread
+/// (e.g., through a channel), and dropping it there schedules the reclamation in the critical
+/// sections of the receiving thread.
 ///
 /// The pointer must be properly aligned. Since it is aligned, a tag can be stored into the unused
 /// least significant bits of the address. For example, the tag for a pointer to a sized type `T`
 /// should be less than `(1 << align_of::<T>().trailing_zeros())`.
 pub struct Rc<T: RcObject> {
-    ptr: Raw<T>,
+    pub(crate) ptr: Raw<T>,
     _marker: PhantomData<T>,
 }
 
@@ -458,7 +1647,8 @@ impl<T: RcObject> Clone for Rc<T> {
         };
         unsafe {
             if let Some(cnt) = rc.ptr.as_raw().as_ref() {
-                cnt.increment_strong();
+                let alive = cnt.increment_strong();
+                debug_assert!(alive, "cloning an object which is already reclaimed");
             }
         }
         rc
@@ -496,6 +1686,20 @@ impl<T: RcObject> Rc<T> {
         }
     }
 
+    /// Constructs a new `Rc` like [`Rc::new`], or returns `None` if the allocation would
+    /// exceed the cap set by [`crate::set_alloc_limit`].
+    ///
+    /// The cap is soft, as the objects which are no longer reachable are counted until EBR
+    /// actually reclaims them. `obj` is dropped if it fails.
+    ///
+    /// This is available only with the `alloc-limit` feature.
+    #[cfg(feature = "alloc-limit")]
+    #[inline]
+    pub fn try_new(obj: T) -> Option<Self> {
+        let ptr = RcInner::try_alloc(obj, 1)?;
+        Some(Self::from_raw(Raw::from(ptr)))
+    }
+
     /// Constructs multiple [`Rc`]s that point to the same object,
     /// which is allocated as a new reference-counted object.
     ///
@@ -536,7 +1740,33 @@ impl<T: RcObject> Rc<T> {
         if let Some(cnt) = unsafe { self.ptr.as_raw().as_ref() } {
             cnt.increment_weak(N as u32);
         }
-        array::from_fn(|_| Weak::null())
+        array::from_fn(|_| Weak::from_raw(self.ptr))
+    }
+
+    /// Constructs an iterator that produces the [`Weak`]s that point to the current object.
+    ///
+    /// This method is more efficient than calling [`Rc::downgrade`] multiple times
+    /// because it is sufficient to set the reference counter only once, avoiding expensive
+    /// read-modify-write operations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` is larger than the maximum weak count of an object, 2<sup>28</sup> - 1.
+    #[inline]
+    pub fn downgrade_many_iter(&self, count: usize) -> WeakIter<T> {
+        assert!(
+            count <= MAX_WEAK_COUNT,
+            "`count` exceeds the maximum weak count"
+        );
+        if count > 0 {
+            if let Some(cnt) = unsafe { self.ptr.as_raw().as_ref() } {
+                cnt.increment_weak(count as u32);
+            }
+        }
+        WeakIter {
+            remain: count,
+            ptr: self.ptr,
+        }
     }
 
     /// Returns the tag stored within the pointer.
@@ -561,66 +1791,210 @@ impl<T: RcObject> Rc<T> {
         new_ptr
     }
 
-    /// Consumes this pointer and release a strong reference count it was owning.
+    /// Consumes the `Rc`, returning a raw pointer to the object, or a null pointer if it was
+    /// null. The tag is discarded.
     ///
-    /// This method is more efficient than just `Drop`ing the pointer. The `Drop` method
-    /// checks whether the current thread is pinned and pin the thread if it is not.
-    /// However, this method skips that procedure as it already requires `Guard` as an argument.
+    /// The strong reference count owned by the `Rc` is not released. To avoid a memory leak,
+    /// the pointer must be converted back to an `Rc` with [`Rc::from_ptr`], or released with
+    /// [`Rc::decrement_strong_count`].
+    ///
+    /// This is intended for passing the ownership through foreign code (e.g., a C API), which
+    /// can retain and release it with [`Rc::increment_strong_count`] and
+    /// [`Rc::decrement_strong_count`].
     #[inline]
-    pub fn finalize(self, guard: &Guard) {
-        unsafe {
-            if let Some(cnt) = self.ptr.as_raw().as_mut() {
-                RcInner::decrement_strong(cnt, 1, Some(guard));
-            }
+    pub fn into_ptr(self) -> *const T {
+        let ptr = self.into_raw().as_raw();
+        if ptr.is_null() {
+            std::ptr::null()
+        } else {
+            unsafe { RcInner::data_ptr(ptr) }
         }
-        forget(self);
     }
 
-    /// Creates a [`Weak`] pointer by incrementing the weak reference counter.
+    /// Constructs an `Rc` from a raw pointer returned by [`Rc::into_ptr`], taking over one
+    /// strong reference count.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be null, or originate from [`Rc::into_ptr`] of `Rc<T>` (the same `T`) or from
+    /// [`crate::raw::alloc_inner`] (through [`crate::raw::RcInner::data_ptr`]), and the caller
+    /// must own a strong reference count of it, which is transferred to the returned `Rc`.
     #[inline]
-    pub fn downgrade(&self) -> Weak<T> {
-        unsafe {
-            if let Some(cnt) = self.ptr.as_raw().as_ref() {
-                cnt.increment_weak(1);
-                return Weak::from_raw(self.ptr);
-            }
-        }
-        Weak::from_raw(self.ptr)
+    pub unsafe fn from_ptr(ptr: *const T) -> Self {
+        // `RcInner<T>` places the object at offset zero.
+        Self::from_raw(Raw::from(ptr.cast::<RcInner<T>>().cast_mut()))
     }
 
-    /// Creates a [`Snapshot`] pointer to the same object.
+    /// Constructs an `Rc` like [`Rc::from_ptr`], but returns `None` if `ptr` is null or not
+    /// aligned for the allocations of `T` (see [`crate::rc_inner_align`]).
+    ///
+    /// This is a sanity check for the pointers coming back through foreign code, whose values
+    /// may have been corrupted. It cannot detect every invalid pointer, so the requirements of
+    /// [`Rc::from_ptr`] still apply to a non-null and aligned `ptr`.
+    ///
+    /// # Safety
+    ///
+    /// If `ptr` is not null and is aligned, the same requirements as [`Rc::from_ptr`] apply.
     #[inline]
-    pub fn snapshot<'g>(&self, guard: &'g Guard) -> Snapshot<'g, T> {
-        Snapshot::from_raw(self.ptr, guard)
+    pub unsafe fn try_from_ptr(ptr: *const T) -> Option<Self> {
+        if ptr.is_null() || !ptr.cast::<RcInner<T>>().is_aligned() {
+            return None;
+        }
+        Some(Self::from_ptr(ptr))
     }
 
-    /// Dereferences the pointer and returns an immutable reference.
+    /// Increments the strong reference count of the object that `ptr` points to.
     ///
-    /// It does not check whether the pointer is null.
+    /// # Safety
+    ///
+    /// `ptr` must originate from [`Rc::into_ptr`] of `Rc<T>` (the same `T`), and the caller
+    /// must own at least one strong reference count of it for the duration of this call.
+    #[inline]
+    pub unsafe fn increment_strong_count(ptr: *const T) {
+        let inner = &*ptr.cast::<RcInner<T>>();
+        let alive = inner.increment_strong();
+        debug_assert!(alive);
+    }
+
+    /// Decrements the strong reference count of the object that `ptr` points to. If it was
+    /// the last strong reference, the object is destructed after the grace period, as if the
+    /// last `Rc` was dropped.
     ///
     /// # Safety
     ///
-    /// The pointer must be a valid memory location to dereference.
+    /// `ptr` must originate from [`Rc::into_ptr`] of `Rc<T>` (the same `T`), and the caller
+    /// must own a strong reference count of it, which is released by this call.
     #[inline]
-    pub unsafe fn deref(&self) -> &T {
-        self.ptr.deref().data()
+    pub unsafe fn decrement_strong_count(ptr: *const T, guard: &Guard) {
+        RcInner::decrement_strong(ptr.cast::<RcInner<T>>().cast_mut(), 1, Some(guard));
     }
 
-    /// Dereferences the pointer and returns a mutable reference.
+    /// Reinterprets this pointer as a pointer to an object of another type `U`, keeping the
+    /// tag and the strong reference count.
     ///
-    /// It does not check whether the pointer is null.
+    /// This is for zero-copy interoperation between layout-compatible node types (e.g., a
+    /// generated type and a hand-written one). It is extremely unsafe, so prefer converting
+    /// the objects whenever possible.
     ///
     /// # Safety
     ///
-    /// The pointer must be a valid memory location to dereference and
-    /// other threads must not have references to the object.
+    /// * `U` must be layout-compatible with `T`: they have the same size and alignment, and
+    ///   every field of `U` has a compatible type at the same offset as the one of `T` (e.g.,
+    ///   both are `#[repr(C)]` with corresponding fields in the same order). Any value of `T`
+    ///   must be a valid value of `U`, and vice versa.
+    /// * The object may be destructed as either type, depending on which pointer releases the
+    ///   last strong reference. Thus, [`RcObject::pop_edges`] and the destructors of `T` and `U`
+    ///   must release the same resources, e.g., take the same edges and drop the same fields.
+    /// * The pointers of both types may coexist and access the object concurrently, so the
+    ///   fields must be synchronized in the same way through either type (e.g., an `AtomicRc`
+    ///   of one type must not correspond to a plain field of the other).
     #[inline]
-    pub unsafe fn deref_mut(&mut self) -> &mut T {
-        self.ptr.deref_mut().data_mut()
+    pub unsafe fn cast<U: RcObject>(self) -> Rc<U> {
+        Rc::from_raw(self.into_raw().cast())
     }
 
-    /// Dereferences the pointer and returns an immutable reference if it is not null.
-    #[inline]
+    /// Consumes this pointer and release a strong reference count it was owning.
+    ///
+    /// This method is more efficient than just `Drop`ing the pointer. The `Drop` method
+    /// checks whether the current thread is pinned and pin the thread if it is not.
+    /// However, this method skips that procedure as it already requires `Guard` as an argument.
+    #[inline]
+    pub fn finalize(self, guard: &Guard) {
+        let cnt = self.into_raw().as_raw();
+        if !cnt.is_null() {
+            unsafe { RcInner::decrement_strong(cnt, 1, Some(guard)) };
+        }
+    }
+
+    /// Consumes this pointer and releases its strong reference count. If it was the last
+    /// strong reference, the object is destructed immediately, instead of being deferred to
+    /// the backend EBR.
+    ///
+    /// This is intended for single-threaded benchmarks and teardown code, where the deferral
+    /// by EBR is an unnecessary overhead. The memory block is freed right away as well,
+    /// unless [`Weak`] pointers to the object remain. The successors of the object are
+    /// reclaimed as usual.
+    ///
+    /// # Safety
+    ///
+    /// No other thread may hold or concurrently obtain a [`Snapshot`] (or a reference derived
+    /// from it) to the object. Otherwise, it would be a use-after-free.
+    #[inline]
+    pub unsafe fn drop_now(self) {
+        let ptr = self.into_raw().as_raw();
+        if !ptr.is_null() {
+            RcInner::decrement_strong_now(ptr);
+        }
+    }
+
+    /// Returns the number of [`Weak`] pointers to the object, or zero if it is null.
+    ///
+    /// It includes the [`crate::AtomicWeak`]s pointing to the object as well.
+    #[inline]
+    pub fn weak_count(&self) -> u32 {
+        unsafe { self.ptr.as_raw().as_ref() }.map_or(0, |inner| {
+            // While the object is alive, its strong references collectively hold one weak count.
+            inner.weak() - 1
+        })
+    }
+
+    /// Creates a [`Weak`] pointer by incrementing the weak reference counter.
+    #[inline]
+    pub fn downgrade(&self) -> Weak<T> {
+        unsafe {
+            if let Some(cnt) = self.ptr.as_raw().as_ref() {
+                cnt.increment_weak(1);
+                return Weak::from_raw(self.ptr);
+            }
+        }
+        Weak::from_raw(self.ptr)
+    }
+
+    /// Creates a [`Snapshot`] pointer to the same object.
+    #[inline]
+    pub fn snapshot<'g>(&self, guard: &'g Guard) -> Snapshot<'g, T> {
+        Snapshot::from_raw(self.ptr, guard)
+    }
+
+    /// Creates a [`WeakSnapshot`] pointer to the same object, without incrementing the weak
+    /// reference counter.
+    ///
+    /// It is a shorthand of `self.snapshot(guard).downgrade()`. Unlike [`Rc::downgrade`], it is
+    /// a pure observation: the returned pointer owns no count, and it is valid only in the
+    /// critical section of `guard`. It suits passing a non-owning weak view to a helper which
+    /// only reads it.
+    #[inline]
+    pub fn weak_snapshot<'g>(&self, guard: &'g Guard) -> WeakSnapshot<'g, T> {
+        self.snapshot(guard).downgrade()
+    }
+
+    /// Dereferences the pointer and returns an immutable reference.
+    ///
+    /// It does not check whether the pointer is null.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be a valid memory location to dereference.
+    #[inline]
+    pub unsafe fn deref(&self) -> &T {
+        self.ptr.deref().data()
+    }
+
+    /// Dereferences the pointer and returns a mutable reference.
+    ///
+    /// It does not check whether the pointer is null.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be a valid memory location to dereference and
+    /// other threads must not have references to the object.
+    #[inline]
+    pub unsafe fn deref_mut(&mut self) -> &mut T {
+        &mut *RcInner::data_ptr(self.ptr.as_raw())
+    }
+
+    /// Dereferences the pointer and returns an immutable reference if it is not null.
+    #[inline]
     pub fn as_ref(&self) -> Option<&T> {
         if self.ptr.is_null() {
             None
@@ -629,6 +2003,21 @@ impl<T: RcObject> Rc<T> {
         }
     }
 
+    /// Returns the pointer to the object as a [`NonNull`], or `None` if it is null. The tag is
+    /// discarded.
+    ///
+    /// It is handy for registering the object to external data structures keyed on `NonNull`.
+    /// The pointer is valid as long as a strong reference to the object is alive.
+    #[inline]
+    pub fn as_non_null(&self) -> Option<NonNull<T>> {
+        let ptr = self.ptr.as_raw();
+        if ptr.is_null() {
+            None
+        } else {
+            NonNull::new(unsafe { RcInner::data_ptr(ptr) })
+        }
+    }
+
     /// Dereferences the pointer and returns a mutable reference if it is not null.
     ///
     /// # Safety
@@ -643,6 +2032,21 @@ impl<T: RcObject> Rc<T> {
         }
     }
 
+    /// Returns `true` if this is the only [`Rc`] (or [`AtomicRc`]) pointing to the object,
+    /// i.e., the strong reference count is exactly one. Returns `false` for a null pointer.
+    ///
+    /// This is useful in [`RcObject::pop_edges`] to decide whether an outgoing edge should be
+    /// taken for immediate recursive destruction. Note that the count of the object being
+    /// reclaimed itself has already reached zero at that point, so this method must be called
+    /// on its outgoing edges, not on a pointer to the object under reclamation.
+    ///
+    /// The result is only a snapshot of the count: other threads may concurrently create or
+    /// drop references unless they have no access to the object.
+    #[inline]
+    pub fn is_unique(&self) -> bool {
+        unsafe { self.ptr.as_raw().as_ref() }.is_some_and(|cnt| cnt.strong() == 1)
+    }
+
     /// Returns `true` if the two pointer values, including the tag values set by `with_tag`,
     /// are identical.
     #[inline]
@@ -653,6 +2057,46 @@ impl<T: RcObject> Rc<T> {
         // accessed epoch for the pointer.
         self.ptr.ptr_eq(other.ptr)
     }
+
+    /// Returns `true` if the two pointer values are identical, including the epoch tags in the
+    /// high bits, which [`Rc::ptr_eq`] ignores.
+    ///
+    /// It is only for validating the epoch stamping of CIRC itself, and thus available only in
+    /// the tests of this crate or with the `test-utils` feature.
+    #[cfg(any(test, feature = "test-utils"))]
+    #[doc(hidden)]
+    #[inline]
+    pub fn raw_bits_eq(&self, other: &Self) -> bool {
+        self.ptr.raw_bits_eq(other.ptr)
+    }
+
+    /// Compares the addresses of the objects, ignoring the tags.
+    ///
+    /// Unlike [`Ord`] for `Rc`, which compares the objects themselves, this gives a total order
+    /// on the object identities without dereferencing. See also [`ByPtr`].
+    #[inline]
+    pub fn cmp_ptr(&self, other: &Self) -> std::cmp::Ordering {
+        self.ptr.as_raw().cmp(&other.ptr.as_raw())
+    }
+
+    /// Compares the objects with `f`, e.g., by one of their fields, rather than by
+    /// [`PartialEq`] of `T`.
+    ///
+    /// Two null pointers are equal, and a null pointer is not equal to a non-null one. `f` is
+    /// called only if both are non-null. See also [`KeyedRc`].
+    #[inline]
+    pub fn eq_by<F: FnOnce(&T, &T) -> bool>(&self, other: &Self, f: F) -> bool {
+        match (self.as_ref(), other.as_ref()) {
+            (Some(a), Some(b)) => f(a, b),
+            (a, b) => a.is_none() && b.is_none(),
+        }
+    }
+
+    /// Returns the identity of the object, ignoring the tags. See [`PtrId`].
+    #[inline]
+    pub fn id(&self) -> PtrId {
+        PtrId::from_raw(self.ptr)
+    }
 }
 
 impl<T: RcObject> OwnRc<T> for Rc<T> {
@@ -662,6 +2106,15 @@ impl<T: RcObject> OwnRc<T> for Rc<T> {
     }
 }
 
+/// An optional edge (e.g., an optional child) can be taken as well, where `None` is treated
+/// as a null pointer.
+impl<T: RcObject> OwnRc<T> for Option<Rc<T>> {
+    #[inline]
+    fn take(&mut self) -> Rc<T> {
+        Option::take(self).unwrap_or_else(Rc::null)
+    }
+}
+
 impl<'g, T: RcObject> From<Snapshot<'g, T>> for Rc<T> {
     fn from(value: Snapshot<'g, T>) -> Self {
         value.counted()
@@ -670,11 +2123,11 @@ impl<'g, T: RcObject> From<Snapshot<'g, T>> for Rc<T> {
 
 impl<T: RcObject + Debug> Debug for Rc<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut debug = self.ptr.debug_struct(f, "Rc");
         if let Some(cnt) = self.as_ref() {
-            f.debug_tuple("RcObject").field(cnt).finish()
-        } else {
-            f.write_str("Null")
+            debug.field("data", cnt);
         }
+        debug.finish()
     }
 }
 
@@ -695,7 +2148,8 @@ impl<T: RcObject> Drop for Rc<T> {
     #[inline(always)]
     fn drop(&mut self) {
         unsafe {
-            if let Some(cnt) = self.ptr.as_raw().as_mut() {
+            let cnt = self.ptr.as_raw();
+            if !cnt.is_null() {
                 RcInner::decrement_strong(cnt, 1, None);
             }
         }
@@ -729,6 +2183,188 @@ impl<T: RcObject + Ord> Ord for Rc<T> {
     }
 }
 
+/// A wrapper of [`Rc`] or [`Snapshot`] which compares the pointers by the addresses of the
+/// objects (ignoring the tags), rather than by the objects themselves.
+///
+/// It is useful to key ordered collections (e.g., `BTreeSet`) on the object identities.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ByPtr<P>(pub P);
+
+impl<T: RcObject> PartialEq for ByPtr<Rc<T>> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.0.cmp_ptr(&other.0).is_eq()
+    }
+}
+
+impl<T: RcObject> Eq for ByPtr<Rc<T>> {}
+
+impl<T: RcObject> PartialOrd for ByPtr<Rc<T>> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: RcObject> Ord for ByPtr<Rc<T>> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.cmp_ptr(&other.0)
+    }
+}
+
+impl<T: RcObject> PartialEq for ByPtr<Snapshot<'_, T>> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.0.cmp_ptr(other.0).is_eq()
+    }
+}
+
+impl<T: RcObject> Eq for ByPtr<Snapshot<'_, T>> {}
+
+impl<T: RcObject> PartialOrd for ByPtr<Snapshot<'_, T>> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: RcObject> Ord for ByPtr<Snapshot<'_, T>> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.cmp_ptr(other.0)
+    }
+}
+
+/// A wrapper of [`Rc`] which compares, hashes and orders the objects by a key projected from
+/// them, rather than by the whole objects.
+///
+/// It lets a `HashSet` or a `BTreeSet` deduplicate the objects by a field (e.g., the key of a
+/// node), ignoring the others. A null pointer has no key: it is equal only to another null
+/// pointer, and less than any non-null pointer.
+///
+/// # Examples
+///
+/// ```
+/// use circ::{EdgeTaker, KeyedRc, Rc, RcObject};
+/// use std::collections::HashSet;
+///
+/// struct Node {
+///     key: u32,
+///     value: String,
+/// }
+///
+/// unsafe impl RcObject for Node {
+///     fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+/// }
+///
+/// let node = |key, value: &str| Rc::new(Node { key, value: value.to_string() });
+/// let set = [node(1, "a"), node(2, "b"), node(1, "c")]
+///     .into_iter()
+///     .map(|rc| KeyedRc::new(rc, |node: &Node| &node.key))
+///     .collect::<HashSet<_>>();
+/// assert_eq!(set.len(), 2);
+/// ```
+pub struct KeyedRc<T: RcObject, K: ?Sized> {
+    rc: Rc<T>,
+    key: fn(&T) -> &K,
+}
+
+impl<T: RcObject, K: ?Sized> KeyedRc<T, K> {
+    /// Wraps `rc`, keyed by `key`.
+    #[inline]
+    pub fn new(rc: Rc<T>, key: fn(&T) -> &K) -> Self {
+        Self { rc, key }
+    }
+
+    /// Returns the key of the object, or `None` if the pointer is null.
+    #[inline]
+    pub fn key(&self) -> Option<&K> {
+        self.rc.as_ref().map(self.key)
+    }
+
+    /// Returns the wrapped [`Rc`].
+    #[inline]
+    pub fn rc(&self) -> &Rc<T> {
+        &self.rc
+    }
+
+    /// Unwraps the [`Rc`].
+    #[inline]
+    pub fn into_rc(self) -> Rc<T> {
+        self.rc
+    }
+}
+
+impl<T: RcObject, K: ?Sized> Clone for KeyedRc<T, K> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            rc: self.rc.clone(),
+            key: self.key,
+        }
+    }
+}
+
+impl<T: RcObject, K: Debug + ?Sized> Debug for KeyedRc<T, K> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyedRc").field("key", &self.key()).finish()
+    }
+}
+
+impl<T: RcObject, K: PartialEq + ?Sized> PartialEq for KeyedRc<T, K> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl<T: RcObject, K: Eq + ?Sized> Eq for KeyedRc<T, K> {}
+
+impl<T: RcObject, K: Hash + ?Sized> Hash for KeyedRc<T, K> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+impl<T: RcObject, K: PartialOrd + ?Sized> PartialOrd for KeyedRc<T, K> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.key().partial_cmp(&other.key())
+    }
+}
+
+impl<T: RcObject, K: Ord + ?Sized> Ord for KeyedRc<T, K> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// The identity of a reference-counted object, i.e., its address without the tags.
+///
+/// Unlike the pointer values, which carry a user tag and an internal epoch tag, `PtrId` is the
+/// same for every [`Rc`], [`Snapshot`], [`Weak`] and [`WeakSnapshot`] pointing to the same
+/// object, so it can be used as a key of maps and sets. A null pointer has the id of zero.
+///
+/// An id is only meaningful while the object is alive (or at least its allocation is kept by a
+/// [`Weak`]), since the address may be reused for a new object after the reclamation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PtrId(pub usize);
+
+impl PtrId {
+    #[inline]
+    pub(crate) fn from_raw<T>(ptr: Raw<T>) -> Self {
+        Self(ptr.as_raw().addr())
+    }
+
+    /// Returns `true` if this is the id of a null pointer.
+    #[inline]
+    pub fn is_null(self) -> bool {
+        self.0 == 0
+    }
+}
+
 /// An iterator generating [`Rc`] pointers to the same and newly allocated object.
 ///
 /// See [`Rc::new_many_iter`] for the purpose of this iterator.
@@ -781,48 +2417,163 @@ impl<T: RcObject> Drop for NewRcIter<T> {
     }
 }
 
-/// A local pointer protected by the backend EBR.
+/// An iterator detaching and yielding the objects of a chain.
 ///
-/// Unlike [`Rc`] pointer, this pointer does not own a strong reference count by itself.
-/// This pointer is valid for use only during the lifetime of EBR guard `'g`.
-pub struct Snapshot<'g, T> {
-    pub(crate) ptr: Raw<T>,
-    pub(crate) _marker: PhantomData<&'g T>,
+/// See [`AtomicRc::drain`] for the purpose of this iterator.
+pub struct DrainIter<T: RcObject, F> {
+    next: Rc<T>,
+    next_fn: F,
 }
 
-impl<T> Clone for Snapshot<'_, T> {
-    fn clone(&self) -> Self {
-        *self
+impl<T: RcObject, F> Iterator for DrainIter<T, F>
+where
+    F: FnMut(&T) -> &AtomicRc<T>,
+{
+    type Item = Rc<T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let curr = take(&mut self.next);
+        let obj = curr.as_ref()?;
+        // Relaxed is enough, as the chain is exclusively owned.
+        self.next = (self.next_fn)(obj).swap(Rc::null(), Ordering::Relaxed);
+        Some(curr)
     }
 }
 
-impl<T> Copy for Snapshot<'_, T> {}
+/// An iterator generating [`Weak`] pointers to the same object.
+///
+/// See [`Rc::downgrade_many_iter`] for the purpose of this iterator.
+pub struct WeakIter<T> {
+    remain: usize,
+    ptr: Raw<T>,
+}
 
-impl<'g, T: RcObject> Snapshot<'g, T> {
-    /// Returns `true` if the pointer is null ignoring the tag.
-    #[inline(always)]
-    pub fn is_null(&self) -> bool {
-        self.ptr.is_null()
-    }
+impl<T> Iterator for WeakIter<T> {
+    type Item = Weak<T>;
 
-    /// Creates an [`Rc`] pointer by incrementing the strong reference counter.
     #[inline]
-    pub fn counted(self) -> Rc<T> {
-        let rc = Rc::from_raw(self.ptr);
-        unsafe {
-            if let Some(cnt) = rc.ptr.as_raw().as_ref() {
-                cnt.increment_strong();
-            }
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remain == 0 {
+            None
+        } else {
+            self.remain -= 1;
+            Some(Weak::from_raw(self.ptr))
         }
-        rc
     }
+}
 
-    /// Converts to `WeakSnapshot`. This does not touch the reference counter.
+impl<T> WeakIter<T> {
+    /// Aborts generating [`Weak`]s.
+    ///
+    /// It decreases the weak reference counter as the remaining number of [`Weak`]s that are not
+    /// generated yet.
     #[inline]
-    pub fn downgrade(self) -> WeakSnapshot<'g, T> {
-        WeakSnapshot {
-            ptr: self.ptr,
-            _marker: PhantomData,
+    pub fn abort(self, guard: &Guard) {
+        if self.remain > 0 && !self.ptr.is_null() {
+            unsafe {
+                RcInner::decrement_weak_many(self.ptr.as_raw(), self.remain as _, Some(guard));
+            };
+        }
+        forget(self);
+    }
+}
+
+impl<T> Drop for WeakIter<T> {
+    #[inline]
+    fn drop(&mut self) {
+        if self.remain > 0 && !self.ptr.is_null() {
+            unsafe {
+                RcInner::decrement_weak_many(self.ptr.as_raw(), self.remain as _, None);
+            };
+        }
+    }
+}
+
+/// A local pointer protected by the backend EBR.
+///
+/// Unlike [`Rc`] pointer, this pointer does not own a strong reference count by itself.
+/// This pointer is valid for use only during the lifetime of EBR guard `'g`.
+///
+/// A `Snapshot` must be dereferenced only on the thread which created it, as the guard `'g`
+/// protects the object only on that thread. In debug builds, dereferencing it on another thread
+/// panics.
+pub struct Snapshot<'g, T> {
+    pub(crate) ptr: Raw<T>,
+    pub(crate) thread: ThreadStamp,
+    pub(crate) _marker: PhantomData<&'g T>,
+}
+
+/// Identifies the thread which created a [`Snapshot`], to catch the dereferences on the other
+/// threads in debug builds. It is a zero-sized type with no-op methods in release builds.
+#[derive(Clone, Copy)]
+pub(crate) struct ThreadStamp {
+    // The address of a thread-local, which is cheaper to get than `std::thread::ThreadId`.
+    #[cfg(debug_assertions)]
+    addr: usize,
+}
+
+#[cfg(debug_assertions)]
+thread_local! {
+    static THREAD_STAMP: u8 = const { 0 };
+}
+
+impl ThreadStamp {
+    #[inline(always)]
+    pub(crate) fn current() -> Self {
+        Self {
+            #[cfg(debug_assertions)]
+            addr: THREAD_STAMP.with(|stamp| stamp as *const u8 as usize),
+        }
+    }
+
+    #[inline(always)]
+    fn check(self) {
+        #[cfg(debug_assertions)]
+        assert!(
+            self.addr == Self::current().addr,
+            "a `Snapshot` is dereferenced on a thread other than the one which created it"
+        );
+    }
+}
+
+impl<T> Clone for Snapshot<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Snapshot<'_, T> {}
+
+impl<'g, T: RcObject> Snapshot<'g, T> {
+    /// Creates an [`Rc`] pointer by incrementing the strong reference counter.
+    #[inline]
+    pub fn counted(self) -> Rc<T> {
+        let rc = Rc::from_raw(self.ptr);
+        unsafe {
+            if let Some(cnt) = rc.ptr.as_raw().as_ref() {
+                cnt.increment_strong();
+            }
+        }
+        rc
+    }
+
+    /// Creates a [`Weak`] pointer by incrementing the weak reference counter.
+    ///
+    /// Unlike [`Snapshot::downgrade`], the returned pointer is not bound to the guard, so it can
+    /// be kept after the critical section ends (e.g., as a long-lived back-pointer).
+    #[inline]
+    pub fn to_weak(self) -> Weak<T> {
+        // The guard keeps the object from being disposed, so the weak count is still positive.
+        self.downgrade().counted()
+    }
+
+    /// Converts to `WeakSnapshot`. This does not touch the reference counter.
+    #[inline]
+    pub fn downgrade(self) -> WeakSnapshot<'g, T> {
+        WeakSnapshot {
+            ptr: self.ptr,
+            _marker: PhantomData,
         }
     }
 
@@ -850,6 +2601,7 @@ impl<'g, T: RcObject> Snapshot<'g, T> {
     /// The pointer must be a valid memory location to dereference.
     #[inline]
     pub unsafe fn deref(self) -> &'g T {
+        self.thread.check();
         self.ptr.deref().data()
     }
 
@@ -862,8 +2614,9 @@ impl<'g, T: RcObject> Snapshot<'g, T> {
     /// The pointer must be a valid memory location to dereference and
     /// other threads must not have references to the object.
     #[inline]
-    pub unsafe fn deref_mut(mut self) -> &'g mut T {
-        self.ptr.deref_mut().data_mut()
+    pub unsafe fn deref_mut(self) -> &'g mut T {
+        self.thread.check();
+        &mut *RcInner::data_ptr(self.ptr.as_raw())
     }
 
     /// Dereferences the pointer and returns an immutable reference if it is not null.
@@ -876,6 +2629,58 @@ impl<'g, T: RcObject> Snapshot<'g, T> {
         }
     }
 
+    /// Returns a null pointer if this pointer is null, and otherwise calls `f` with the object
+    /// and returns the result.
+    ///
+    /// Like [`Option::and_then`], this chains the link following without checking each link
+    /// for null, e.g., `head.and_then(|n| n.next.load(order, guard))`.
+    #[inline]
+    pub fn and_then<U: RcObject, F: FnOnce(&'g T) -> Snapshot<'g, U>>(
+        self,
+        f: F,
+    ) -> Snapshot<'g, U> {
+        match self.as_ref() {
+            Some(obj) => f(obj),
+            None => Snapshot::null(),
+        }
+    }
+
+    /// Reinterprets this pointer as a pointer to an object of another type `U`, keeping the
+    /// tag.
+    ///
+    /// # Safety
+    ///
+    /// The same requirements as [`Rc::cast`] apply: `U` must be layout-compatible with `T`,
+    /// and their [`RcObject::pop_edges`], destructors and synchronization of the fields must
+    /// agree with each other.
+    #[inline]
+    pub unsafe fn cast<U: RcObject>(self) -> Snapshot<'g, U> {
+        Snapshot {
+            ptr: self.ptr.cast(),
+            thread: self.thread,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Dereferences the pointer and returns an immutable reference, without checking whether
+    /// the pointer is null.
+    ///
+    /// It is a faster alternative of [`Snapshot::as_ref`] for hot read paths where the pointer
+    /// is known to be non-null (e.g., a traversal over a chain whose end is checked separately).
+    /// With `debug_assertions`, it panics if the pointer is null.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must not be null.
+    #[inline]
+    pub unsafe fn as_ref_unchecked(self) -> &'g T {
+        debug_assert!(
+            !self.ptr.is_null(),
+            "`as_ref_unchecked` on a null `Snapshot`"
+        );
+        self.deref()
+    }
+
     /// Dereferences the pointer and returns a mutable reference if it is not null.
     ///
     /// # Safety
@@ -900,6 +2705,107 @@ impl<'g, T: RcObject> Snapshot<'g, T> {
         // accessed epoch for the pointer.
         self.ptr.ptr_eq(other.ptr)
     }
+
+    /// Compares the addresses of the objects, ignoring the tags.
+    ///
+    /// Unlike [`Ord`] for `Snapshot`, which compares the objects themselves, this gives a total
+    /// order on the object identities without dereferencing. See also [`ByPtr`].
+    #[inline]
+    pub fn cmp_ptr(self, other: Self) -> std::cmp::Ordering {
+        self.ptr.as_raw().cmp(&other.ptr.as_raw())
+    }
+
+    /// Compares the objects with `f`, like [`Rc::eq_by`].
+    #[inline]
+    pub fn eq_by<F: FnOnce(&T, &T) -> bool>(self, other: Self, f: F) -> bool {
+        match (self.as_ref(), other.as_ref()) {
+            (Some(a), Some(b)) => f(a, b),
+            (a, b) => a.is_none() && b.is_none(),
+        }
+    }
+
+    /// Returns the identity of the object, ignoring the tags. See [`PtrId`].
+    #[inline]
+    pub fn id(self) -> PtrId {
+        PtrId::from_raw(self.ptr)
+    }
+
+    /// Returns a raw pointer to the object, or a null pointer if it is null. The tag is
+    /// discarded.
+    ///
+    /// Unlike the `Snapshot`, the raw pointer is not bound to the critical section, so it can be
+    /// kept across a repin and protected again with [`Guard::protect`].
+    #[inline]
+    pub fn as_ptr(self) -> *const T {
+        let ptr = self.ptr.as_raw();
+        if ptr.is_null() {
+            std::ptr::null()
+        } else {
+            unsafe { RcInner::data_ptr(ptr) }
+        }
+    }
+}
+
+impl Guard {
+    /// Protects a raw pointer from [`Snapshot::as_ptr`] in the critical section of this guard,
+    /// returning it as a [`Snapshot`] without a tag.
+    ///
+    /// This lets a long scan keep its cursor across a repin (e.g., [`Guard::reactivate`]),
+    /// which ends the critical section the cursor was loaded in: store the cursor with
+    /// [`Snapshot::as_ptr`] before repinning, and protect it again afterwards. With the EBR
+    /// backend, it only re-wraps the pointer, as every object is protected in a critical
+    /// section. The object must not be reclaimed while the thread is unpinned, though.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be null, or originate from [`Snapshot::as_ptr`] of `Snapshot<T>` (the same
+    /// `T`), and the object must be still alive, i.e., it must not have been reclaimed since the
+    /// pointer was taken. For example, a strong reference held by the current thread or a data
+    /// structure which never unlinks its objects keeps it alive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use circ::{cs, AtomicRc, EdgeTaker, Rc, RcObject};
+    /// use std::sync::atomic::Ordering;
+    ///
+    /// struct Node {
+    ///     item: usize,
+    ///     next: AtomicRc<Node>,
+    /// }
+    ///
+    /// unsafe impl RcObject for Node {
+    ///     fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+    ///         out.take(&mut self.next);
+    ///     }
+    /// }
+    ///
+    /// // The list never unlinks its nodes while `head` is held.
+    /// let head = (0..100).rev().fold(Rc::null(), |next, item| {
+    ///     Rc::new(Node {
+    ///         item,
+    ///         next: AtomicRc::from(next),
+    ///     })
+    /// });
+    ///
+    /// let mut guard = cs();
+    /// let mut cursor = head.snapshot(&guard).as_ptr();
+    /// let mut sum = 0;
+    /// while !cursor.is_null() {
+    ///     let node = unsafe { guard.protect(cursor) };
+    ///     let node = node.as_ref().unwrap();
+    ///     sum += node.item;
+    ///     cursor = node.next.load(Ordering::Acquire, &guard).as_ptr();
+    ///     // Let the global epoch advance.
+    ///     guard.reactivate();
+    /// }
+    /// assert_eq!(sum, 4950);
+    /// ```
+    #[inline]
+    pub unsafe fn protect<T: RcObject>(&self, ptr: *const T) -> Snapshot<'_, T> {
+        // `RcInner<T>` places the object at offset zero.
+        Snapshot::from_raw(Raw::from(ptr.cast::<RcInner<T>>().cast_mut()), self)
+    }
 }
 
 impl<'g, T> Snapshot<'g, T> {
@@ -908,14 +2814,22 @@ impl<'g, T> Snapshot<'g, T> {
     pub fn null() -> Self {
         Self {
             ptr: Tagged::null(),
+            thread: ThreadStamp::current(),
             _marker: PhantomData,
         }
     }
 
+    /// Returns `true` if the pointer is null ignoring the tag.
+    #[inline(always)]
+    pub fn is_null(&self) -> bool {
+        self.ptr.is_null()
+    }
+
     #[inline]
     pub(crate) fn from_raw(acquired: Raw<T>, _: &'g Guard) -> Self {
         Self {
             ptr: acquired,
+            thread: ThreadStamp::current(),
             _marker: PhantomData,
         }
     }
@@ -957,11 +2871,11 @@ impl<T: RcObject + Ord> Ord for Snapshot<'_, T> {
 
 impl<T: RcObject + Debug> Debug for Snapshot<'_, T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut debug = self.ptr.debug_struct(f, "Snapshot");
         if let Some(cnt) = self.as_ref() {
-            f.debug_tuple("RcObject").field(cnt).finish()
-        } else {
-            f.write_str("Null")
+            debug.field("data", cnt);
         }
+        debug.finish()
     }
 }
 
@@ -970,3 +2884,1792 @@ impl<T: RcObject> Pointer for Snapshot<'_, T> {
         Pointer::fmt(&self.ptr, f)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeSet, HashSet};
+    use std::mem::ManuallyDrop;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    use crate::ebr_impl::global_epoch;
+    use crate::{
+        cs, drop_with_guard, is_pinned, quiescent_barrier, AtomicRc, DropWithGuard, EdgeTaker,
+        Guard, KeyedRc, Rc, RcObject, Snapshot,
+    };
+
+    struct Node {
+        next: AtomicRc<Self>,
+    }
+
+    unsafe impl RcObject for Node {
+        fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+            out.take(&mut self.next);
+        }
+    }
+
+    impl Node {
+        fn new() -> Self {
+            Self {
+                next: AtomicRc::null(),
+            }
+        }
+    }
+
+    /// A node counting its reclamations in `on_reclaim`. Each test gives its own counter, so
+    /// that the tests running in parallel do not interfere with each other.
+    struct Counted {
+        next: AtomicRc<Self>,
+        reclaims: &'static AtomicUsize,
+    }
+
+    unsafe impl RcObject for Counted {
+        fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+            out.take(&mut self.next);
+        }
+
+        fn on_reclaim(&mut self, _: &Guard) {
+            self.reclaims.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    impl Counted {
+        fn new(reclaims: &'static AtomicUsize) -> Self {
+            Self {
+                next: AtomicRc::null(),
+                reclaims,
+            }
+        }
+
+        /// Returns a chain of `len` nodes, or null if `len` is zero.
+        fn chain(len: usize, reclaims: &'static AtomicUsize) -> Rc<Self> {
+            (0..len).fold(Rc::null(), |next, _| {
+                Rc::new(Self {
+                    next: AtomicRc::from(next),
+                    reclaims,
+                })
+            })
+        }
+    }
+
+    #[test]
+    fn load_decomposed() {
+        let link = AtomicRc::from(Rc::new(Node::new()).with_tag(1));
+        let guard = &cs();
+
+        let (snapshot, tag) = link.load_decomposed(Ordering::SeqCst, guard);
+        assert_eq!(tag, 1);
+        assert_eq!(snapshot.tag(), 1);
+        assert!(snapshot.ptr_eq(link.load(Ordering::SeqCst, guard)));
+
+        // The epoch written in the high bits must not leak into the tag.
+        let new = Rc::new(Node::new()).with_tag(2);
+        link.store(new, Ordering::SeqCst, guard);
+        let (snapshot, tag) = link.load_decomposed(Ordering::SeqCst, guard);
+        assert_eq!(tag, 2);
+        assert!(!snapshot.is_null());
+    }
+
+    #[test]
+    fn on_reclaim_runs_once() {
+        const COUNT: usize = 1000;
+        static RECLAIMS: AtomicUsize = AtomicUsize::new(0);
+
+        drop(Counted::chain(COUNT, &RECLAIMS));
+        while RECLAIMS.load(Ordering::Relaxed) < COUNT {
+            cs().flush();
+        }
+        assert_eq!(RECLAIMS.load(Ordering::Relaxed), COUNT);
+    }
+
+    #[test]
+    #[should_panic(expected = "failure ordering must be SeqCst, Acquire, or Relaxed")]
+    fn compare_exchange_release_failure() {
+        let link = AtomicRc::<Node>::null();
+        let guard = &cs();
+        let _ = link.compare_exchange(
+            Snapshot::null(),
+            Rc::null(),
+            Ordering::SeqCst,
+            Ordering::Release,
+            guard,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "failure ordering must be SeqCst, Acquire, or Relaxed")]
+    fn compare_exchange_acqrel_failure() {
+        let link = AtomicRc::<Node>::null();
+        let guard = &cs();
+        let _ = link.compare_exchange(
+            Snapshot::null(),
+            Rc::null(),
+            Ordering::SeqCst,
+            Ordering::AcqRel,
+            guard,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "failure ordering must be equivalent to or weaker than")]
+    fn compare_exchange_stronger_failure() {
+        let link = AtomicRc::<Node>::null();
+        let guard = &cs();
+        let _ = link.compare_exchange(
+            Snapshot::null(),
+            Rc::null(),
+            Ordering::Acquire,
+            Ordering::SeqCst,
+            guard,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "failure ordering must be SeqCst, Acquire, or Relaxed")]
+    fn compare_exchange_weak_release_failure() {
+        let link = AtomicRc::<Node>::null();
+        let guard = &cs();
+        let _ = link.compare_exchange_weak(
+            Snapshot::null(),
+            Rc::null(),
+            Ordering::SeqCst,
+            Ordering::Release,
+            guard,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "failure ordering must be equivalent to or weaker than")]
+    fn compare_exchange_weak_stronger_failure() {
+        let link = AtomicRc::<Node>::null();
+        let guard = &cs();
+        let _ = link.compare_exchange_weak(
+            Snapshot::null(),
+            Rc::null(),
+            Ordering::Release,
+            Ordering::Acquire,
+            guard,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "failure ordering must be SeqCst, Acquire, or Relaxed")]
+    fn compare_exchange_tag_acqrel_failure() {
+        let link = AtomicRc::<Node>::null();
+        let guard = &cs();
+        let _ = link.compare_exchange_tag(
+            Snapshot::null(),
+            1,
+            Ordering::AcqRel,
+            Ordering::AcqRel,
+            guard,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "failure ordering must be equivalent to or weaker than")]
+    fn compare_exchange_tag_stronger_failure() {
+        let link = AtomicRc::<Node>::null();
+        let guard = &cs();
+        let _ = link.compare_exchange_tag(
+            Snapshot::null(),
+            1,
+            Ordering::Relaxed,
+            Ordering::Acquire,
+            guard,
+        );
+    }
+
+    #[test]
+    fn is_unique_stops_recursion_at_shared_subtrees() {
+        static RECLAIMS: AtomicUsize = AtomicUsize::new(0);
+        static SKIPPED: AtomicUsize = AtomicUsize::new(0);
+
+        struct Tree {
+            left: Rc<Self>,
+            right: Rc<Self>,
+        }
+
+        unsafe impl RcObject for Tree {
+            fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+                for child in [&mut self.left, &mut self.right] {
+                    if child.is_unique() {
+                        out.take(child);
+                    } else if !child.is_null() {
+                        SKIPPED.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            }
+
+            fn on_reclaim(&mut self, _: &Guard) {
+                RECLAIMS.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        fn leaf() -> Rc<Tree> {
+            Rc::new(Tree {
+                left: Rc::null(),
+                right: Rc::null(),
+            })
+        }
+
+        assert!(!Rc::<Tree>::null().is_unique());
+
+        let shared = leaf();
+        let root = Rc::new(Tree {
+            left: leaf(),
+            right: shared.clone(),
+        });
+        assert!(root.is_unique());
+        assert!(!shared.is_unique());
+
+        drop(root);
+        while RECLAIMS.load(Ordering::SeqCst) < 2 {
+            cs().flush();
+        }
+        assert_eq!(SKIPPED.load(Ordering::Relaxed), 1);
+        assert!(shared.is_unique());
+        assert!(shared.as_ref().unwrap().left.is_null());
+
+        drop(shared);
+        while RECLAIMS.load(Ordering::SeqCst) < 3 {
+            cs().flush();
+        }
+    }
+
+    #[test]
+    fn cas_from_null_race() {
+        const THREADS: usize = 16;
+        let slot = &AtomicRc::<Node>::null();
+        let succeeded = &AtomicUsize::new(0);
+
+        crossbeam_utils::thread::scope(|s| {
+            for _ in 0..THREADS {
+                s.spawn(move |_| {
+                    let desired = Rc::new(Node::new());
+                    let desired_ptr = desired.clone();
+                    let guard = &cs();
+                    match slot.cas_from_null(desired, Ordering::AcqRel, guard) {
+                        Ok(()) => {
+                            succeeded.fetch_add(1, Ordering::Relaxed);
+                            assert!(slot
+                                .load(Ordering::Acquire, guard)
+                                .ptr_eq(desired_ptr.snapshot(guard)));
+                        }
+                        Err(desired) => assert!(desired.ptr_eq(&desired_ptr)),
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        assert_eq!(succeeded.load(Ordering::Relaxed), 1);
+        assert!(!slot.load(Ordering::Relaxed, &cs()).is_null());
+    }
+
+    #[test]
+    fn downgrade_many_iter_abort() {
+        let rc = Rc::new(Node::new());
+        let weak_count = || unsafe { rc.ptr.deref() }.weak();
+        // Create a weak pointer first, so that the count starts from a stable state.
+        let first = rc.downgrade();
+        let initial = weak_count();
+
+        let mut iter = rc.downgrade_many_iter(10);
+        let weaks: Vec<_> = iter.by_ref().take(4).collect();
+        assert_eq!(weak_count(), initial + 10);
+        iter.abort(&cs());
+        assert_eq!(weak_count(), initial + 4);
+        assert!(weaks.iter().all(|weak| weak.ptr_eq(&first)));
+
+        drop(rc.downgrade_many_iter(3));
+        assert_eq!(weak_count(), initial + 4);
+
+        drop(weaks);
+        assert_eq!(weak_count(), initial);
+
+        let [a, b] = rc.weak_many::<2>();
+        assert!(a.ptr_eq(&first) && b.ptr_eq(&first));
+        assert_eq!(weak_count(), initial + 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "`count` exceeds the maximum weak count")]
+    fn downgrade_many_iter_too_many() {
+        let rc = Rc::new(Node::new());
+        // It would wrap around to 0 if truncated to `u32`.
+        drop(rc.downgrade_many_iter(1 << 32));
+    }
+
+    #[test]
+    fn drop_now_reclaims_immediately() {
+        static RECLAIMS: AtomicUsize = AtomicUsize::new(0);
+
+        let [a, b] = Rc::new_many(Counted::new(&RECLAIMS));
+        unsafe { a.drop_now() };
+        assert_eq!(RECLAIMS.load(Ordering::Relaxed), 0);
+        unsafe { b.drop_now() };
+        assert_eq!(RECLAIMS.load(Ordering::Relaxed), 1);
+        unsafe { Rc::<Counted>::null().drop_now() };
+    }
+
+    #[test]
+    fn compare_exchange_with_each_expected_type() {
+        let obj = AtomicRc::null();
+        let guard = cs();
+        let first = Rc::new(Node::new());
+        obj.store(first.clone(), Ordering::Release, &guard);
+
+        // `&Rc`
+        let second = Rc::new(Node::new());
+        let prev = obj
+            .compare_exchange(
+                &first,
+                second.clone(),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                &guard,
+            )
+            .ok()
+            .unwrap();
+        assert!(prev.ptr_eq(&first));
+        let err = obj
+            .compare_exchange(
+                &first,
+                Rc::null(),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                &guard,
+            )
+            .err()
+            .unwrap();
+        assert!(err.expected.ptr_eq(&first));
+        assert!(err.current.ptr_eq(second.snapshot(&guard)));
+
+        // `WeakSnapshot`
+        let expected = second.snapshot(&guard).downgrade();
+        let prev = obj
+            .compare_exchange(
+                expected,
+                first.clone(),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                &guard,
+            )
+            .ok()
+            .unwrap();
+        assert!(prev.ptr_eq(&second));
+        // The tag is taken into account.
+        assert!(obj
+            .compare_exchange(
+                first.snapshot(&guard).downgrade().with_tag(1),
+                Rc::null(),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                &guard,
+            )
+            .is_err());
+
+        // `Snapshot`
+        let expected = obj.load(Ordering::Acquire, &guard);
+        let prev = obj
+            .compare_exchange(
+                expected,
+                Rc::null(),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                &guard,
+            )
+            .ok()
+            .unwrap();
+        assert!(prev.ptr_eq(&first));
+        assert!(obj.load(Ordering::Acquire, &guard).is_null());
+    }
+
+    #[test]
+    fn load_owned_outlives_guard() {
+        fn first(list: &AtomicRc<Node>) -> Rc<Node> {
+            list.load_owned(Ordering::Acquire, &cs())
+        }
+
+        let list = AtomicRc::new(Node::new());
+        let loaded = first(&list);
+        assert!(!is_pinned());
+        // Unlink it, and let the unlinked object go through the reclamation.
+        let unlinked = list.swap(Rc::null(), Ordering::AcqRel);
+        drop(unlinked);
+        quiescent_barrier();
+
+        assert!(loaded.is_unique());
+        assert!(loaded
+            .as_ref()
+            .unwrap()
+            .next
+            .load(Ordering::Acquire, &cs())
+            .is_null());
+    }
+
+    #[test]
+    fn try_load_always_succeeds_with_ebr() {
+        let obj = AtomicRc::new(Node::new());
+        let guard = cs();
+        let loaded = obj.try_load(Ordering::Acquire, &guard).unwrap();
+        assert!(loaded.ptr_eq(obj.load(Ordering::Acquire, &guard)));
+        assert!(AtomicRc::<Node>::null()
+            .try_load(Ordering::Acquire, &guard)
+            .unwrap()
+            .is_null());
+    }
+
+    #[test]
+    fn and_then_chases_links() {
+        let guard = cs();
+        let next = |node: &'_ Node| node.next.load(Ordering::Acquire, &guard);
+
+        // head -> second -> null
+        let second = Rc::new(Node::new());
+        let head = AtomicRc::new(Node {
+            next: AtomicRc::from(second.clone()),
+        });
+        let head = head.load(Ordering::Acquire, &guard);
+
+        assert!(head.and_then(next).ptr_eq(second.snapshot(&guard)));
+        assert!(head.and_then(next).and_then(next).is_null());
+        assert!(head.and_then(next).and_then(next).and_then(next).is_null());
+    }
+
+    #[test]
+    fn fetch_update_tag_state_machine() {
+        // IDLE -> BUSY -> DONE, where only one thread may take each transition.
+        const IDLE: usize = 0;
+        const BUSY: usize = 1;
+        const DONE: usize = 2;
+        #[cfg(miri)]
+        const THREADS: usize = 4;
+        #[cfg(not(miri))]
+        const THREADS: usize = 16;
+
+        let obj = &AtomicRc::new(Node::new());
+        let transition = |from, to| {
+            obj.fetch_update_tag(Ordering::AcqRel, Ordering::Acquire, &cs(), |tag| {
+                (tag == from).then_some(to)
+            })
+        };
+
+        let (started, finished) = std::thread::scope(|s| {
+            let handles = (0..THREADS)
+                .map(|_| {
+                    s.spawn(move || {
+                        let started = transition(IDLE, BUSY).is_ok();
+                        let finished = started && transition(BUSY, DONE).is_ok();
+                        (started, finished)
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|h| h.join().unwrap())
+                .fold((0, 0), |(s, f), (started, finished)| {
+                    (s + started as usize, f + finished as usize)
+                })
+        });
+        assert_eq!((started, finished), (1, 1));
+
+        let guard = cs();
+        assert_eq!(transition(IDLE, BUSY), Err(DONE));
+        let curr = obj.load(Ordering::Acquire, &guard);
+        assert_eq!(curr.tag(), DONE);
+        assert!(!curr.is_null());
+    }
+
+    #[test]
+    fn compare_exchange_bits_as_flags() {
+        const MARKED: u32 = 0;
+        const FROZEN: u32 = 1;
+
+        let obj = AtomicRc::new(Node::new());
+        let guard = cs();
+
+        let curr = obj.load(Ordering::Acquire, &guard);
+        obj.compare_exchange_set_bit(curr, MARKED, Ordering::AcqRel, Ordering::Acquire, &guard)
+            .ok()
+            .unwrap();
+        let curr = obj.load(Ordering::Acquire, &guard);
+        let prev = obj
+            .compare_exchange_set_bit(curr, FROZEN, Ordering::AcqRel, Ordering::Acquire, &guard)
+            .ok()
+            .unwrap();
+        assert_eq!(prev.tag(), 0b01);
+        let curr = obj.load(Ordering::Acquire, &guard);
+        assert_eq!(curr.tag(), 0b11);
+
+        // Clearing one flag keeps the other.
+        obj.compare_exchange_clear_bit(curr, MARKED, Ordering::AcqRel, Ordering::Acquire, &guard)
+            .ok()
+            .unwrap();
+        let curr = obj.load(Ordering::Acquire, &guard);
+        assert_eq!(curr.tag(), 0b10);
+
+        // A stale `expected` fails without touching the flags.
+        let stale = curr.with_tag(0);
+        let err = obj
+            .compare_exchange_set_bit(stale, MARKED, Ordering::AcqRel, Ordering::Acquire, &guard)
+            .err()
+            .unwrap();
+        assert_eq!(err.current.tag(), 0b10);
+        assert_eq!(obj.load(Ordering::Acquire, &guard).tag(), 0b10);
+    }
+
+    #[test]
+    fn fetch_max_tag_concurrent() {
+        const THREADS: usize = 8;
+
+        #[repr(align(16))]
+        struct Aligned;
+
+        unsafe impl RcObject for Aligned {
+            fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+        }
+
+        let rc = Rc::new(Aligned);
+        let obj = AtomicRc::from(&rc);
+        std::thread::scope(|s| {
+            for t in 0..THREADS {
+                let obj = &obj;
+                s.spawn(move || {
+                    let guard = cs();
+                    for tag in (0..16).filter(|tag| tag % THREADS == t) {
+                        let prev = obj.fetch_max_tag(tag, Ordering::AcqRel, &guard);
+                        assert!(prev < 16);
+                    }
+                });
+            }
+        });
+
+        let guard = cs();
+        let curr = obj.load(Ordering::Acquire, &guard);
+        assert_eq!(curr.tag(), 15);
+        assert!(curr.ptr_eq(rc.snapshot(&guard).with_tag(15)));
+        assert_eq!(obj.fetch_max_tag(3, Ordering::AcqRel, &guard), 15);
+        assert_eq!(obj.load(Ordering::Acquire, &guard).tag(), 15);
+    }
+
+    #[test]
+    fn snapshot_to_weak_outlives_guard() {
+        let obj = AtomicRc::new(Node::new());
+        let weak = {
+            let guard = cs();
+            obj.load(Ordering::Acquire, &guard).to_weak()
+        };
+
+        let rc = weak.upgrade().unwrap();
+        assert!(rc.ptr_eq(&obj.load(Ordering::Acquire, &cs()).counted()));
+    }
+
+    #[test]
+    fn take_array_destructs_wide_nodes() {
+        const FAN_OUT: usize = 16;
+        static RECLAIMS: AtomicUsize = AtomicUsize::new(0);
+
+        struct Wide {
+            children: [AtomicRc<Counted>; FAN_OUT],
+        }
+
+        unsafe impl RcObject for Wide {
+            fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+                out.take_array(&mut self.children);
+            }
+
+            fn on_reclaim(&mut self, _: &Guard) {
+                RECLAIMS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        // The root and its children count into the same counter.
+        let root = Rc::new(Wide {
+            children: std::array::from_fn(|_| AtomicRc::new(Counted::new(&RECLAIMS))),
+        });
+        drop(root);
+
+        while RECLAIMS.load(Ordering::Relaxed) < FAN_OUT + 1 {
+            cs().flush();
+        }
+        assert_eq!(RECLAIMS.load(Ordering::Relaxed), FAN_OUT + 1);
+    }
+
+    #[test]
+    fn compare_exchange_then_runs_on_success_only() {
+        let guard = cs();
+        let tail = AtomicRc::new(Node::new());
+        let expected = tail.load(Ordering::Acquire, &guard);
+
+        let mut called = false;
+        let result = tail.compare_exchange_then(
+            expected,
+            Rc::new(Node::new()),
+            Ordering::AcqRel,
+            Ordering::Acquire,
+            &guard,
+            |installed| {
+                called = true;
+                let successor = installed.as_ref().unwrap();
+                successor
+                    .next
+                    .store(Rc::new(Node::new()), Ordering::Release, &guard);
+            },
+        );
+        assert!(result.is_ok());
+        assert!(called);
+        let installed = tail.load(Ordering::Acquire, &guard);
+        assert!(!installed
+            .as_ref()
+            .unwrap()
+            .next
+            .load(Ordering::Acquire, &guard)
+            .is_null());
+
+        let result = tail.compare_exchange_then(
+            expected,
+            Rc::new(Node::new()),
+            Ordering::AcqRel,
+            Ordering::Acquire,
+            &guard,
+            |_| panic!("must not be called on failure"),
+        );
+        assert!(matches!(result, Err(e) if e.current.ptr_eq(installed)));
+    }
+
+    #[test]
+    fn zero_sized_objects() {
+        struct ZstNode;
+
+        unsafe impl RcObject for ZstNode {
+            fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+        }
+
+        let token = Rc::new(());
+        let cloned = token.clone();
+        assert!(token.ptr_eq(&cloned));
+        drop(token);
+        drop(cloned);
+
+        let guard = cs();
+        let obj = AtomicRc::new(ZstNode);
+        let snapshot = obj.load(Ordering::Acquire, &guard);
+        obj.compare_exchange_tag(snapshot, 0b111, Ordering::AcqRel, Ordering::Acquire, &guard)
+            .ok()
+            .unwrap();
+        let tagged = obj.load(Ordering::Acquire, &guard);
+        assert_eq!(tagged.tag(), 0b111);
+        assert!(tagged.with_tag(0).ptr_eq(snapshot));
+        assert!(tagged.as_ref().is_some());
+    }
+
+    #[test]
+    fn quiescent_barrier_reclaims_chain() {
+        #[cfg(miri)]
+        const LEN: usize = 100;
+        #[cfg(not(miri))]
+        const LEN: usize = 10_000;
+        static RECLAIMS: AtomicUsize = AtomicUsize::new(0);
+
+        drop(Counted::chain(LEN, &RECLAIMS));
+
+        crate::quiescent_barrier();
+        assert_eq!(RECLAIMS.load(Ordering::Relaxed), LEN);
+    }
+
+    #[test]
+    #[should_panic(expected = "a barrier must not be called in a critical section")]
+    fn quiescent_barrier_in_critical_section() {
+        let _guard = cs();
+        crate::quiescent_barrier();
+    }
+
+    #[test]
+    fn debug_shows_tag_and_null() {
+        #[derive(Debug)]
+        #[allow(dead_code)]
+        struct Data(u32);
+
+        unsafe impl RcObject for Data {
+            fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+        }
+
+        let guard = cs();
+        let obj = AtomicRc::from(Rc::new(Data(7)).with_tag(1));
+        let snapshot = obj.load(Ordering::Acquire, &guard);
+        let addr = format!("{:p}", snapshot);
+        assert_eq!(
+            format!("{:?}", obj),
+            format!("AtomicRc {{ addr: {addr}, tag: 1, null: false }}")
+        );
+        assert_eq!(
+            format!("{:?}", snapshot),
+            format!("Snapshot {{ addr: {addr}, tag: 1, null: false, data: Data(7) }}")
+        );
+        assert_eq!(
+            format!("{:?}", snapshot.counted()),
+            format!("Rc {{ addr: {addr}, tag: 1, null: false, data: Data(7) }}")
+        );
+        assert_eq!(
+            format!("{:?}", Rc::<Data>::null().with_tag(2)),
+            "Rc { addr: 0x0, tag: 2, null: true }"
+        );
+    }
+
+    #[test]
+    fn send_across_channel() {
+        #[cfg(miri)]
+        const COUNT: usize = 100;
+        #[cfg(not(miri))]
+        const COUNT: usize = 10_000;
+        static RECLAIMS: AtomicUsize = AtomicUsize::new(0);
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        std::thread::scope(|s| {
+            s.spawn(move || {
+                for _ in 0..COUNT {
+                    sender.send(Rc::new(Counted::new(&RECLAIMS))).unwrap();
+                }
+            });
+            s.spawn(move || {
+                for rc in receiver {
+                    drop(rc);
+                }
+                // The producer may have exited, so the reclamation must be driven by the
+                // consumer alone.
+                crate::quiescent_barrier();
+                assert_eq!(RECLAIMS.load(Ordering::Relaxed), COUNT);
+            });
+        });
+    }
+
+    #[test]
+    fn drain_chain() {
+        const LEN: u64 = 1000;
+
+        struct Item {
+            value: u64,
+            next: AtomicRc<Item>,
+        }
+
+        unsafe impl RcObject for Item {
+            fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+                out.take(&mut self.next);
+            }
+        }
+
+        let mut head = Rc::null();
+        for value in 1..=LEN {
+            head = Rc::new(Item {
+                value,
+                next: AtomicRc::from(head),
+            });
+        }
+
+        let mut sum = 0;
+        for item in AtomicRc::from(head).drain(|item| &item.next) {
+            let item = item.as_ref().unwrap();
+            assert!(item.next.load(Ordering::Relaxed, &cs()).is_null());
+            sum += item.value;
+        }
+        assert_eq!(sum, LEN * (LEN + 1) / 2);
+    }
+
+    #[test]
+    fn by_ptr_orders_by_identity() {
+        use std::collections::BTreeSet;
+
+        use crate::ByPtr;
+
+        let nodes = [(); 8].map(|_| Rc::new(Node::new()));
+        let mut set = BTreeSet::new();
+        for node in &nodes {
+            assert!(set.insert(ByPtr(node.clone())));
+        }
+        // The same object is a duplicate regardless of its tag.
+        assert!(!set.insert(ByPtr(nodes[3].clone().with_tag(1))));
+        assert_eq!(set.len(), nodes.len());
+
+        let sorted = set.iter().map(|node| &node.0).collect::<Vec<_>>();
+        assert!(sorted.windows(2).all(|w| w[0].cmp_ptr(w[1]).is_lt()));
+        for node in &nodes {
+            assert!(set.contains(&ByPtr(node.clone())));
+        }
+    }
+
+    #[test]
+    fn move_into_another_link() {
+        static RECLAIMS: AtomicUsize = AtomicUsize::new(0);
+
+        let node = Rc::new(Counted::new(&RECLAIMS)).with_tag(1);
+        let src = AtomicRc::from(&node);
+        let dst = AtomicRc::new(Counted::new(&RECLAIMS));
+        let old = {
+            let guard = &cs();
+            let dst_old = dst.load(Ordering::Acquire, guard);
+
+            let old = src.move_into(&dst, Ordering::AcqRel, guard);
+            assert!(old.snapshot(guard).ptr_eq(dst_old));
+            assert!(src.load(Ordering::Acquire, guard).is_null());
+            let moved = dst.load(Ordering::Acquire, guard);
+            assert!(moved.ptr_eq(node.snapshot(guard)));
+            assert_eq!(moved.tag(), 1);
+            // The reference of `src` is moved to `dst`, rather than copied or dropped.
+            unsafe { assert_eq!(node.ptr.deref().strong(), 2) };
+
+            // Moving a null pointer empties `dst`.
+            let rest = src.move_into(&dst, Ordering::AcqRel, guard);
+            assert!(rest.snapshot(guard).ptr_eq(node.snapshot(guard)));
+            assert!(dst.load(Ordering::Acquire, guard).is_null());
+            dst.store(rest, Ordering::Release, guard);
+            old
+        };
+
+        // Only the previous object of `dst` is released by dropping the returned `Rc`.
+        drop(old);
+        drop(node);
+        crate::quiescent_barrier();
+        assert_eq!(RECLAIMS.load(Ordering::Relaxed), 1);
+
+        drop(src);
+        drop(dst);
+        crate::quiescent_barrier();
+        assert_eq!(RECLAIMS.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn edge_taker_counts_taken_edges() {
+        static NODES: AtomicUsize = AtomicUsize::new(0);
+        static TAKEN: AtomicUsize = AtomicUsize::new(0);
+
+        struct TreeNode {
+            left: AtomicRc<TreeNode>,
+            right: AtomicRc<TreeNode>,
+        }
+
+        unsafe impl RcObject for TreeNode {
+            fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+                out.take(&mut self.left);
+                out.take(&mut self.right);
+                NODES.fetch_add(1, Ordering::Relaxed);
+                TAKEN.fetch_add(out.taken(), Ordering::Relaxed);
+            }
+        }
+
+        impl TreeNode {
+            fn leaf() -> Self {
+                Self {
+                    left: AtomicRc::null(),
+                    right: AtomicRc::null(),
+                }
+            }
+        }
+
+        let root = Rc::new(TreeNode {
+            left: AtomicRc::new(TreeNode::leaf()),
+            right: AtomicRc::new(TreeNode::leaf()),
+        });
+        drop(root);
+        crate::quiescent_barrier();
+        // Each node takes exactly 2 edges, including the null edges of the leaves.
+        assert_eq!(NODES.load(Ordering::Relaxed), 3);
+        assert_eq!(TAKEN.load(Ordering::Relaxed), 6);
+    }
+
+    #[test]
+    fn compare_exchange_error_keeps_expected() {
+        let guard = cs();
+        let obj = AtomicRc::new(Node::new());
+        let stale = obj.load(Ordering::Acquire, &guard);
+        obj.store(Rc::new(Node::new()), Ordering::Release, &guard);
+
+        let err = obj
+            .compare_exchange(
+                stale,
+                Rc::null(),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                &guard,
+            )
+            .err()
+            .unwrap();
+        assert!(err.expected.ptr_eq(stale));
+        assert!(!err.current.ptr_eq(stale));
+        assert!(err.desired.is_null());
+    }
+
+    #[test]
+    fn raw_strong_count_round_trip() {
+        static RECLAIMS: AtomicUsize = AtomicUsize::new(0);
+
+        let ptr = Rc::new(Counted::new(&RECLAIMS)).into_ptr();
+        assert!(std::ptr::eq(unsafe { (*ptr).reclaims }, &RECLAIMS));
+        for _ in 0..3 {
+            unsafe { Rc::increment_strong_count(ptr) };
+        }
+        for _ in 0..3 {
+            unsafe { Rc::decrement_strong_count(ptr, &cs()) };
+        }
+        crate::quiescent_barrier();
+        assert_eq!(RECLAIMS.load(Ordering::Relaxed), 0);
+
+        let rc = unsafe { Rc::from_ptr(ptr) };
+        assert!(std::ptr::eq(rc.as_ref().unwrap().reclaims, &RECLAIMS));
+        unsafe { Rc::increment_strong_count(ptr) };
+        drop(rc);
+        unsafe { Rc::decrement_strong_count(ptr, &cs()) };
+        crate::quiescent_barrier();
+        assert_eq!(RECLAIMS.load(Ordering::Relaxed), 1);
+
+        assert!(Rc::<Counted>::null().into_ptr().is_null());
+        assert!(unsafe { Rc::<Counted>::from_ptr(std::ptr::null()) }.is_null());
+    }
+
+    #[test]
+    fn compare_exchange_once_reports_retry() {
+        let rc = Rc::new(Node::new());
+        let obj = AtomicRc::from(&rc);
+        let guard = cs();
+        // A snapshot loaded before the same object was relinked in another epoch.
+        let current = obj.load(Ordering::Acquire, &guard);
+        let stale = Snapshot::from_raw(
+            current.ptr.with_high_tag(current.ptr.high_tag() + 1),
+            &guard,
+        );
+
+        let err = obj
+            .compare_exchange_once(
+                stale,
+                Rc::null(),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                &guard,
+            )
+            .err()
+            .unwrap();
+        assert!(err.is_retry());
+        let err = err.into_inner();
+        assert!(err.current.ptr_eq(stale));
+
+        // The current value is up to date, so the retry succeeds.
+        let prev = obj
+            .compare_exchange_once(
+                err.current,
+                err.desired,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                &guard,
+            )
+            .ok()
+            .unwrap();
+        assert!(prev.ptr_eq(&rc));
+
+        let err = obj
+            .compare_exchange_once(
+                stale,
+                Rc::null(),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                &guard,
+            )
+            .err()
+            .unwrap();
+        assert!(!err.is_retry());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "null `Snapshot`")]
+    fn as_ref_unchecked_on_null() {
+        let obj = AtomicRc::<Node>::null();
+        let guard = cs();
+        let node = unsafe { obj.load(Ordering::Acquire, &guard).as_ref_unchecked() };
+        node.next.load(Ordering::Acquire, &guard);
+    }
+
+    #[test]
+    fn as_non_null_matches_pointer_format() {
+        let rc = Rc::new(Node::new()).with_tag(1);
+        let ptr = rc.as_non_null().unwrap();
+        assert_eq!(format!("{ptr:p}"), format!("{rc:p}"));
+        assert!(std::ptr::eq(ptr.as_ptr(), rc.as_ref().unwrap()));
+        assert!(Rc::<Node>::null().as_non_null().is_none());
+    }
+
+    #[test]
+    fn compare_exchange_load_returns_installed() {
+        let guard = cs();
+        let head = AtomicRc::<Node>::null();
+        let installed = head
+            .compare_exchange_load(
+                Snapshot::null(),
+                Rc::new(Node::new()),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                &guard,
+            )
+            .ok()
+            .unwrap();
+        assert!(installed.ptr_eq(head.load(Ordering::Acquire, &guard)));
+
+        // Continue operating on the installed node right away.
+        let node = installed.as_ref().unwrap();
+        node.next
+            .store(Rc::new(Node::new()), Ordering::Release, &guard);
+        assert!(!head
+            .load(Ordering::Acquire, &guard)
+            .as_ref()
+            .unwrap()
+            .next
+            .load(Ordering::Acquire, &guard)
+            .is_null());
+
+        let err = head
+            .compare_exchange_load(
+                Snapshot::null(),
+                Rc::new(Node::new()),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                &guard,
+            )
+            .err()
+            .unwrap();
+        assert!(err.current.ptr_eq(installed));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "already reclaimed")]
+    fn double_free_is_detected() {
+        let rc = Rc::new(Node::new());
+        // Keeps the memory block alive after the object is reclaimed.
+        let _weak = rc.downgrade();
+        let dup = unsafe { std::ptr::read(&rc) };
+        drop(rc);
+        crate::quiescent_barrier();
+        dup.finalize(&cs());
+    }
+
+    #[test]
+    fn ptr_id_ignores_tags() {
+        use std::collections::HashSet;
+
+        let guard = cs();
+        let rc = Rc::new(Node::new()).with_tag(1);
+        let snapshot = rc.snapshot(&guard);
+        // A snapshot carrying a different epoch tag still identifies the same object.
+        let stale = Snapshot::from_raw(
+            snapshot.ptr.with_high_tag(snapshot.ptr.high_tag() + 1),
+            &guard,
+        );
+        let weak = rc.downgrade().with_tag(0);
+
+        assert_eq!(rc.id(), snapshot.id());
+        assert_eq!(rc.id(), stale.id());
+        assert_eq!(rc.id(), weak.id());
+        assert_eq!(rc.id(), snapshot.downgrade().id());
+        assert_eq!(rc.id().0, rc.as_ref().unwrap() as *const _ as usize);
+
+        let other = Rc::new(Node::new());
+        assert_ne!(rc.id(), other.id());
+        assert!(Rc::<Node>::null().id().is_null());
+
+        let ids: HashSet<_> = [rc.id(), snapshot.id(), weak.id(), other.id()].into();
+        assert_eq!(ids.len(), 2);
+    }
+
+    #[test]
+    fn load_if_changed_detects_swap() {
+        let head = AtomicRc::new(Node::new());
+        let guard = cs();
+        let since = head.load(Ordering::Acquire, &guard);
+        assert!(head
+            .load_if_changed(since, Ordering::Acquire, &guard)
+            .is_none());
+        // A different epoch tag does not count as a change.
+        let stale = Snapshot::from_raw(since.ptr.with_high_tag(since.ptr.high_tag() + 1), &guard);
+        assert!(head
+            .load_if_changed(stale, Ordering::Acquire, &guard)
+            .is_none());
+
+        std::thread::scope(|s| {
+            s.spawn(|| drop(head.swap(Rc::new(Node::new()), Ordering::AcqRel)));
+        });
+        let changed = head
+            .load_if_changed(since, Ordering::Acquire, &guard)
+            .unwrap();
+        assert!(!changed.ptr_eq(since));
+        assert!(changed.ptr_eq(head.load(Ordering::Acquire, &guard)));
+    }
+
+    #[test]
+    fn init_null_slots() {
+        let guard = &cs();
+        let slots = (0..4).map(|_| AtomicRc::null()).collect::<Vec<_>>();
+        for slot in &slots {
+            slot.init(Rc::new(Node::new()), Ordering::Relaxed);
+        }
+        assert!(slots
+            .iter()
+            .all(|slot| !slot.load(Ordering::Relaxed, guard).is_null()));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "`AtomicRc::init` is called on a non-null `AtomicRc`")]
+    fn init_non_null_slot() {
+        let slot = AtomicRc::new(Node::new());
+        slot.init(Rc::new(Node::new()), Ordering::Relaxed);
+    }
+
+    #[test]
+    #[should_panic(expected = "there is no such thing as an acquire")]
+    fn init_rejects_acquire() {
+        AtomicRc::null().init(Rc::new(Node::new()), Ordering::AcqRel);
+    }
+
+    #[test]
+    fn cast_between_layout_compatible_types() {
+        #[repr(C)]
+        struct Generated {
+            next: AtomicRc<Generated>,
+            value: u64,
+        }
+
+        #[repr(C)]
+        struct Written {
+            next: AtomicRc<Written>,
+            value: u64,
+        }
+
+        unsafe impl RcObject for Generated {
+            fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+                out.take(&mut self.next);
+            }
+        }
+
+        unsafe impl RcObject for Written {
+            fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+                out.take(&mut self.next);
+            }
+        }
+
+        let tail = Rc::new(Generated {
+            next: AtomicRc::null(),
+            value: 2,
+        });
+        let head = Rc::new(Generated {
+            next: AtomicRc::from(tail),
+            value: 1,
+        })
+        .with_tag(1);
+
+        let guard = &cs();
+        let snapshot = unsafe { head.snapshot(guard).cast::<Written>() };
+        assert_eq!(snapshot.tag(), 1);
+        let next = snapshot
+            .as_ref()
+            .unwrap()
+            .next
+            .load(Ordering::Acquire, guard);
+        assert_eq!(next.as_ref().unwrap().value, 2);
+
+        // The object is destructed as `Written`.
+        let head = unsafe { head.cast::<Written>() };
+        assert_eq!(head.tag(), 1);
+        assert_eq!(head.as_ref().unwrap().value, 1);
+        assert!(head.ptr_eq(&Rc::from(snapshot)));
+    }
+
+    #[test]
+    fn enum_nodes_are_destructed_recursively() {
+        #[cfg(not(miri))]
+        const DEPTH: usize = 16;
+        #[cfg(miri)]
+        const DEPTH: usize = 6;
+        static LEAF_DROPS: AtomicUsize = AtomicUsize::new(0);
+
+        enum Tree {
+            Leaf(usize),
+            Branch(AtomicRc<Tree>, AtomicRc<Tree>),
+            Unary { child: AtomicRc<Tree> },
+        }
+
+        unsafe impl RcObject for Tree {
+            fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+                match self {
+                    Tree::Leaf(_) => {}
+                    Tree::Branch(left, right) => {
+                        out.take(left);
+                        out.take(right);
+                    }
+                    Tree::Unary { child } => out.take(child),
+                }
+            }
+        }
+
+        impl Drop for Tree {
+            fn drop(&mut self) {
+                if let Tree::Leaf(weight) = self {
+                    LEAF_DROPS.fetch_add(*weight, Ordering::Relaxed);
+                }
+            }
+        }
+
+        fn build(depth: usize) -> Rc<Tree> {
+            if depth == 0 {
+                return Rc::new(Tree::Leaf(1));
+            }
+            let branch = Rc::new(Tree::Branch(
+                AtomicRc::from(build(depth - 1)),
+                AtomicRc::from(build(depth - 1)),
+            ));
+            Rc::new(Tree::Unary {
+                child: AtomicRc::from(branch),
+            })
+        }
+
+        drop(build(DEPTH));
+        crate::quiescent_barrier();
+        assert_eq!(LEAF_DROPS.load(Ordering::Relaxed), 1 << DEPTH);
+    }
+
+    #[test]
+    fn try_from_ptr_validates_pointer() {
+        let guard = cs();
+        unsafe {
+            assert!(Rc::<Node>::try_from_ptr(std::ptr::null()).is_none());
+
+            let ptr = Rc::new(Node::new()).into_ptr();
+            let misaligned = ptr.cast::<u8>().wrapping_add(1).cast::<Node>();
+            assert!(Rc::try_from_ptr(misaligned).is_none());
+
+            let rc = Rc::try_from_ptr(ptr).unwrap();
+            assert_eq!(rc.snapshot(&guard).as_ref().unwrap() as *const Node, ptr);
+            assert!(rc.is_unique());
+        }
+    }
+
+    #[test]
+    fn drop_with_guard_releases_all_fields() {
+        static RECLAIMS: AtomicUsize = AtomicUsize::new(0);
+
+        struct Triple {
+            first: Rc<Counted>,
+            second: Rc<Counted>,
+            third: Rc<Counted>,
+        }
+
+        impl DropWithGuard for Triple {
+            fn drop_with(&mut self, guard: &Guard) {
+                self.first.drop_with(guard);
+                self.second.drop_with(guard);
+                self.third.drop_with(guard);
+            }
+        }
+
+        impl Drop for Triple {
+            fn drop(&mut self) {
+                drop_with_guard(self);
+                assert!(self.first.is_null() && self.second.is_null() && self.third.is_null());
+            }
+        }
+
+        let leaf = || Rc::new(Counted::new(&RECLAIMS));
+        drop(Triple {
+            first: leaf(),
+            second: leaf(),
+            third: leaf(),
+        });
+        crate::quiescent_barrier();
+        assert_eq!(RECLAIMS.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn protect_cursor_across_repin() {
+        const LEN: usize = 100;
+
+        let nodes = (0..LEN).map(|_| Rc::new(Node::new())).collect::<Vec<_>>();
+        for (node, next) in nodes.iter().zip(&nodes[1..]) {
+            node.as_ref()
+                .unwrap()
+                .next
+                .swap(next.clone(), Ordering::Relaxed);
+        }
+
+        let mut guard = cs();
+        let mut cursor = nodes[0].snapshot(&guard).as_ptr();
+        let mut visited = 0;
+        while !cursor.is_null() {
+            // `nodes` keeps every node alive while the thread is unpinned.
+            let node = unsafe { guard.protect(cursor) };
+            assert_eq!(node.id(), nodes[visited].id());
+            visited += 1;
+            cursor = node
+                .as_ref()
+                .unwrap()
+                .next
+                .load(Ordering::Acquire, &guard)
+                .as_ptr();
+            if visited % 10 == 0 {
+                guard.reactivate();
+            }
+        }
+        assert_eq!(visited, LEN);
+        assert!(unsafe { guard.protect(std::ptr::null::<Node>()) }.is_null());
+    }
+
+    #[test]
+    fn take_boxed_edges() {
+        static RECLAIMS: AtomicUsize = AtomicUsize::new(0);
+
+        // A `Box`ed sub-structure with `Rc` edges, which owns another one in turn.
+        struct Sub {
+            leaves: Vec<AtomicRc<Counted>>,
+            inner: Option<Box<Sub>>,
+        }
+
+        unsafe impl RcObject for Sub {
+            fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+                out.take_all(self.leaves.iter_mut());
+                out.take_boxed(&mut self.inner);
+            }
+        }
+
+        struct Parent {
+            sub: Option<Box<Sub>>,
+        }
+
+        unsafe impl RcObject for Parent {
+            fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+                out.take_boxed(&mut self.sub);
+                assert_eq!(out.taken(), 3);
+            }
+        }
+
+        let leaves = |count| {
+            (0..count)
+                .map(|_| AtomicRc::new(Counted::new(&RECLAIMS)))
+                .collect()
+        };
+        let parent = Rc::new(Parent {
+            sub: Some(Box::new(Sub {
+                leaves: leaves(2),
+                inner: Some(Box::new(Sub {
+                    leaves: leaves(1),
+                    inner: None,
+                })),
+            })),
+        });
+
+        drop(parent);
+        quiescent_barrier();
+        assert_eq!(RECLAIMS.load(Ordering::Relaxed), 3);
+
+        // Without `Rc` edges, `take_boxed` takes nothing.
+        let mut empty = None::<Box<Sub>>;
+        let mut popped = Vec::new();
+        let mut taker = EdgeTaker::new(&mut popped);
+        taker.take_boxed(&mut empty);
+        assert_eq!(taker.taken(), 0);
+    }
+
+    #[test]
+    fn compare_exchange_aba_catches_aba() {
+        let a = Rc::new(Node::new());
+        let b = Rc::new(Node::new());
+        let guard = &cs();
+
+        // Changes the slot from `a` to `b`, and then back to `a`.
+        let a_to_b_to_a = |slot: &AtomicRc<Node>, aba: bool| {
+            for next in [&b, &a] {
+                let curr = slot.load(Ordering::Acquire, guard);
+                let res = if aba {
+                    slot.compare_exchange_aba(
+                        curr,
+                        next.clone(),
+                        Ordering::SeqCst,
+                        Ordering::SeqCst,
+                        guard,
+                    )
+                } else {
+                    slot.compare_exchange(
+                        curr,
+                        next.clone(),
+                        Ordering::SeqCst,
+                        Ordering::SeqCst,
+                        guard,
+                    )
+                };
+                assert!(res.is_ok());
+            }
+        };
+
+        // A plain CAS with a stale snapshot succeeds.
+        let slot = AtomicRc::from(a.clone());
+        let stale = slot.load(Ordering::Acquire, guard);
+        a_to_b_to_a(&slot, false);
+        assert!(slot
+            .compare_exchange(stale, b.clone(), Ordering::SeqCst, Ordering::SeqCst, guard)
+            .is_ok());
+
+        // It fails if the slot is versioned.
+        let slot = AtomicRc::from(a.clone());
+        let stale = slot.load(Ordering::Acquire, guard);
+        a_to_b_to_a(&slot, true);
+        let err = slot
+            .compare_exchange_aba(stale, b.clone(), Ordering::SeqCst, Ordering::SeqCst, guard)
+            .err()
+            .unwrap();
+        assert_eq!(err.current.id(), a.id());
+        assert_eq!(err.current.tag(), 2);
+
+        // The counter wraps around within the available bits.
+        let max = (1 << crate::available_tag_bits::<Node>()) - 1;
+        let slot = AtomicRc::from(a.clone().with_tag(max));
+        let curr = slot.load(Ordering::Acquire, guard);
+        assert!(slot
+            .compare_exchange_aba(curr, b.clone(), Ordering::SeqCst, Ordering::SeqCst, guard)
+            .is_ok());
+        assert_eq!(slot.load(Ordering::Acquire, guard).tag(), 0);
+    }
+
+    #[test]
+    fn keyed_rc_dedupes_by_key() {
+        struct KeyValue {
+            key: u32,
+            value: u32,
+        }
+
+        unsafe impl RcObject for KeyValue {
+            fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+        }
+
+        let node = |key, value| Rc::new(KeyValue { key, value });
+        let (a, b) = (node(1, 10), node(1, 20));
+        assert!(a.eq_by(&b, |a, b| a.key == b.key));
+        assert!(!a.eq_by(&b, |a, b| a.value == b.value));
+        assert!(!a.eq_by(&Rc::null(), |_, _| true));
+        assert!(Rc::<KeyValue>::null().eq_by(&Rc::null(), |_, _| false));
+        let guard = &cs();
+        assert!(a
+            .snapshot(guard)
+            .eq_by(b.snapshot(guard), |a, b| a.key == b.key));
+
+        let nodes = [a, b, node(2, 10), node(3, 30), node(2, 40), Rc::null()];
+        let keyed = |rc: &Rc<KeyValue>| KeyedRc::new(rc.clone(), |node: &KeyValue| &node.key);
+        let set = nodes.iter().map(keyed).collect::<HashSet<_>>();
+        let mut keys = set.iter().map(|k| k.key().copied()).collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!(keys, [None, Some(1), Some(2), Some(3)]);
+
+        // A `HashSet` keeps the first one of each key.
+        assert!(set
+            .iter()
+            .filter_map(|k| k.rc().as_ref())
+            .all(|node| node.value != 20 && node.value != 40));
+
+        let set = nodes.iter().map(keyed).collect::<BTreeSet<_>>();
+        let keys = set.iter().map(|k| k.key().copied()).collect::<Vec<_>>();
+        assert_eq!(keys, [None, Some(1), Some(2), Some(3)]);
+    }
+
+    #[test]
+    fn on_reclaim_allocates_and_releases() {
+        const COUNT: usize = 100;
+        static RECLAIMS: [AtomicUsize; 2] = [AtomicUsize::new(0), AtomicUsize::new(0)];
+        static BIN: Mutex<Vec<Rc<Recycled>>> = Mutex::new(Vec::new());
+
+        struct Recycled {
+            generation: usize,
+        }
+
+        unsafe impl RcObject for Recycled {
+            fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+
+            fn on_reclaim(&mut self, guard: &Guard) {
+                RECLAIMS[self.generation].fetch_add(1, Ordering::Relaxed);
+                if self.generation == 0 {
+                    // Recycle a cleaned copy, and release two more new objects, which schedules
+                    // new deferred tasks in the middle of the collection.
+                    let new = || Rc::new(Recycled { generation: 1 });
+                    BIN.lock().unwrap().push(new());
+                    drop(new());
+                    new().finalize(guard);
+                }
+            }
+        }
+
+        let objs = (0..COUNT)
+            .map(|_| Rc::new(Recycled { generation: 0 }))
+            .collect::<Vec<_>>();
+        drop(objs);
+        quiescent_barrier();
+        assert_eq!(RECLAIMS[0].load(Ordering::Relaxed), COUNT);
+        // The new objects released by the callbacks are reclaimed as well.
+        assert_eq!(RECLAIMS[1].load(Ordering::Relaxed), 2 * COUNT);
+
+        let recycled = std::mem::take(&mut *BIN.lock().unwrap());
+        assert_eq!(recycled.len(), COUNT);
+        assert!(recycled
+            .iter()
+            .all(|rc| rc.as_ref().unwrap().generation == 1));
+        drop(recycled);
+        quiescent_barrier();
+        assert_eq!(RECLAIMS[1].load(Ordering::Relaxed), 3 * COUNT);
+    }
+
+    #[test]
+    fn take_leaves_null() {
+        let guard = &cs();
+        let mut slot = AtomicRc::new(Node::new());
+        let taken = slot.take();
+        assert!(!taken.is_null());
+        assert!(slot.load(Ordering::Relaxed, guard).is_null());
+
+        // The slot is still usable.
+        slot.store(taken.clone(), Ordering::Relaxed, guard);
+        assert!(slot
+            .load(Ordering::Relaxed, guard)
+            .ptr_eq(taken.snapshot(guard)));
+        assert_eq!(slot.take().id(), taken.id());
+        assert!(slot.take().is_null());
+    }
+
+    #[test]
+    fn store_stamps_current_epoch() {
+        let guard = &cs();
+        let mut slot = AtomicRc::null();
+        let rc = Rc::new(Node::new());
+        // The quiescent barriers of the other tests may advance the epoch in the middle.
+        let (stored, epoch) = loop {
+            let epoch = global_epoch();
+            slot.store(rc.clone(), Ordering::Relaxed, guard);
+            let stored = slot.take();
+            if global_epoch() == epoch {
+                break (stored, epoch);
+            }
+        };
+
+        // Stamp the same pointer independently, without touching the count.
+        let stamped = |epoch| ManuallyDrop::new(Rc::from_raw(rc.ptr.with_high_tag(epoch)));
+        assert!(stored.raw_bits_eq(&stamped(epoch)));
+        assert!(!stored.raw_bits_eq(&stamped(epoch + 1)));
+        assert!(stored.ptr_eq(&stamped(epoch + 1)));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(
+        expected = "a `Snapshot` is dereferenced on a thread other than the one which created it"
+    )]
+    fn deref_snapshot_on_another_thread() {
+        struct Smuggled<'g>(Snapshot<'g, Node>);
+        unsafe impl Send for Smuggled<'_> {}
+
+        let obj = Rc::new(Node::new());
+        let guard = &cs();
+        let smuggled = Smuggled(obj.snapshot(guard));
+        std::thread::scope(|s| {
+            let handle = s.spawn(move || {
+                let smuggled = smuggled;
+                smuggled.0.as_ref().is_some()
+            });
+            // Propagate the panic message of the thread.
+            if let Err(payload) = handle.join() {
+                std::panic::resume_unwind(payload);
+            }
+        });
+    }
+
+    #[test]
+    fn compare_exchange_counts_epoch_retries() {
+        let guard = &cs();
+        let slot = AtomicRc::new(Node::new());
+        let mut retries = 0;
+        // The same pointer as `snapshot`, but stamped with another epoch.
+        fn stale(snapshot: Snapshot<'_, Node>) -> Snapshot<'_, Node> {
+            Snapshot {
+                ptr: snapshot.ptr.with_high_tag(snapshot.ptr.high_tag() + 1),
+                ..snapshot
+            }
+        }
+
+        let current = slot.load(Ordering::Relaxed, guard);
+        assert!(slot
+            .compare_exchange_counted(
+                stale(current),
+                Rc::new(Node::new()),
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+                &mut retries,
+                guard,
+            )
+            .is_ok());
+        assert_eq!(retries, 1);
+
+        // A real mismatch is reported without a retry.
+        assert!(slot
+            .compare_exchange_counted(
+                Snapshot::null(),
+                Rc::new(Node::new()),
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+                &mut retries,
+                guard,
+            )
+            .is_err());
+        assert_eq!(retries, 1);
+
+        // The count accumulates over the calls.
+        let current = slot.load(Ordering::Relaxed, guard);
+        assert!(slot
+            .compare_exchange_counted(
+                stale(current),
+                Rc::null(),
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+                &mut retries,
+                guard,
+            )
+            .is_ok());
+        assert_eq!(retries, 2);
+    }
+
+    #[test]
+    fn weak_snapshot_keeps_weak_count() {
+        let guard = &cs();
+        let obj = Rc::new(Node::new());
+        let weak = obj.downgrade();
+        assert_eq!(obj.weak_count(), 1);
+
+        let weak_snapshot = obj.weak_snapshot(guard);
+        assert_eq!(obj.weak_count(), 1);
+        assert!(weak_snapshot
+            .upgrade()
+            .is_some_and(|snapshot| snapshot.ptr_eq(obj.snapshot(guard))));
+        assert!(Rc::<Node>::null().weak_snapshot(guard).is_null());
+        drop(weak);
+        assert_eq!(obj.weak_count(), 0);
+    }
+
+    #[test]
+    fn take_optional_edges() {
+        static RECLAIMS: AtomicUsize = AtomicUsize::new(0);
+
+        // The leaves are reachable only through the nested optional edges.
+        struct Tree {
+            left: Option<Rc<Tree>>,
+            right: Option<Rc<Counted>>,
+        }
+
+        unsafe impl RcObject for Tree {
+            fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+                out.take(&mut self.left);
+                out.take(&mut self.right);
+                debug_assert_eq!(out.taken(), 2);
+                assert!(self.left.is_none() && self.right.is_none());
+            }
+        }
+
+        let root = Rc::new(Tree {
+            left: Some(Rc::new(Tree {
+                left: None,
+                right: Some(Rc::new(Counted::new(&RECLAIMS))),
+            })),
+            right: Some(Counted::chain(2, &RECLAIMS)),
+        });
+        drop(root);
+        crate::quiescent_barrier();
+        assert_eq!(RECLAIMS.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn transparent_newtype_delegates_edges() {
+        #[cfg(not(miri))]
+        const LENGTH: usize = 10_000;
+        #[cfg(miri)]
+        const LENGTH: usize = 100;
+        static RECLAIMS: AtomicUsize = AtomicUsize::new(0);
+
+        #[repr(transparent)]
+        struct Head(Counted);
+
+        unsafe impl RcObject for Head {
+            fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+                self.0.pop_edges(out);
+            }
+
+            fn on_reclaim(&mut self, guard: &Guard) {
+                self.0.on_reclaim(guard);
+            }
+        }
+
+        let head = Rc::new(Head(Counted {
+            next: AtomicRc::from(Counted::chain(LENGTH - 1, &RECLAIMS)),
+            reclaims: &RECLAIMS,
+        }));
+        drop(head);
+        crate::quiescent_barrier();
+        assert_eq!(RECLAIMS.load(Ordering::Relaxed), LENGTH);
+    }
+
+    #[test]
+    fn compare_exchange_discard_keeps_desired() {
+        let guard = &cs();
+        let head = AtomicRc::new(Node::new());
+        let stale = head.load(Ordering::Acquire, guard);
+        head.store(Rc::new(Node::new()), Ordering::Release, guard);
+
+        let mut desired = Rc::new(Node::new());
+        let target = desired.snapshot(guard);
+        for _ in 0..3 {
+            desired = head
+                .compare_exchange_discard(stale, desired, Ordering::AcqRel, Ordering::Acquire)
+                .err()
+                .unwrap();
+            // The failures neither consume nor duplicate the reference of `desired`.
+            assert!(desired.is_unique());
+            assert!(desired.snapshot(guard).ptr_eq(target));
+        }
+
+        let current = head.load(Ordering::Acquire, guard);
+        let old = head
+            .compare_exchange_discard(current, desired, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .unwrap();
+        assert!(old.snapshot(guard).ptr_eq(current));
+        assert!(head.load(Ordering::Acquire, guard).ptr_eq(target));
+    }
+
+    #[test]
+    fn bundled_bounds_in_generic_container() {
+        use crate::ThreadSafeNode;
+
+        /// A generic slot shared across threads, which only needs the bundled bound.
+        struct Slot<T: ThreadSafeNode> {
+            head: AtomicRc<T>,
+        }
+
+        impl<T: ThreadSafeNode> Slot<T> {
+            fn replace(&self, node: T) -> Rc<T> {
+                self.head.swap(Rc::new(node), Ordering::AcqRel)
+            }
+        }
+
+        fn assert_send_sync<S: Send + Sync>(_: &S) {}
+
+        let slot = Slot {
+            head: AtomicRc::<Node>::null(),
+        };
+        assert_send_sync(&slot);
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| drop(slot.replace(Node::new())));
+            }
+        });
+        assert!(!slot.head.load(Ordering::Acquire, &cs()).is_null());
+    }
+}
@@ -1,9 +1,16 @@
+use std::alloc::Layout;
 use std::cell::Cell;
-use std::mem::transmute;
-use std::sync::atomic::Ordering;
+use std::collections::VecDeque;
+use std::mem::{align_of, needs_drop, size_of, transmute};
+use std::ptr::{self, addr_of_mut};
+use std::sync::atomic::{AtomicU8, Ordering};
 use std::{mem::ManuallyDrop, sync::atomic::AtomicU64};
 
-use crate::ebr_impl::{cs, global_epoch, Guard, Tagged, HIGH_TAG_WIDTH};
+use crate::ebr_impl::{
+    barrier, cs, global_epoch, reclaimer_available, run_on_reclaimer, set_reclaim_thread, Guard,
+    Tagged, HIGH_TAG_WIDTH,
+};
+use crate::strong::TryIRD;
 use crate::{EdgeTaker, Rc, RcObject};
 
 /// Raw pointer to a reference counted object. Allows tagging.
@@ -21,7 +28,11 @@ impl Deferable for Guard {
         F: FnOnce(*mut RcInner<T>),
     {
         debug_assert!(!ptr.is_null());
-        self.defer_unchecked(move || f(ptr));
+        self.defer_unchecked(move || {
+            // Counted here rather than on deferring, which is on the hot path of dropping.
+            EXECUTE_COUNTER.with(|counter| counter.set(counter.get().wrapping_add(1)));
+            f(ptr)
+        });
     }
 }
 
@@ -43,16 +54,121 @@ const EPOCH_MASK_HEIGHT: u32 = u64::BITS - EPOCH_WIDTH;
 const EPOCH: u64 = ((1 << EPOCH_WIDTH) - 1) << EPOCH_MASK_HEIGHT;
 const DESTRUCTED: u64 = 1 << (EPOCH_MASK_HEIGHT - 1);
 const WEAKED: u64 = 1 << (EPOCH_MASK_HEIGHT - 2);
-const TOTAL_COUNT_WIDTH: u32 = u64::BITS - EPOCH_WIDTH - 2;
+/// Set if the memory block is allocated by [`crate::pool::RcPool`], and thus returned to the
+/// pool of the reclaiming thread instead of the global allocator.
+const POOLED: u64 = 1 << (EPOCH_MASK_HEIGHT - 3);
+const TOTAL_COUNT_WIDTH: u32 = u64::BITS - EPOCH_WIDTH - 3;
 const WEAK_WIDTH: u32 = TOTAL_COUNT_WIDTH / 2;
 const STRONG_WIDTH: u32 = TOTAL_COUNT_WIDTH - WEAK_WIDTH;
 const STRONG: u64 = (1 << STRONG_WIDTH) - 1;
 const WEAK: u64 = ((1 << WEAK_WIDTH) - 1) << STRONG_WIDTH;
+/// The largest weak count which an object can have, as it must fit in `WEAK_WIDTH` bits.
+pub(crate) const MAX_WEAK_COUNT: usize = (1 << WEAK_WIDTH) - 1;
 const COUNT: u64 = 1;
 const WEAK_COUNT: u64 = 1 << STRONG_WIDTH;
+/// In debug builds, the strong count of a destructed object is set to this value, so that a
+/// later access to the object (e.g., a double free) can be detected while its memory block is
+/// kept alive by weak references. A destructed object may still be incremented by a failed
+/// upgrade, so any count not less than it is regarded as poisoned if the object is destructed.
+/// A live object may have more strong references than this.
+#[cfg(debug_assertions)]
+const POISON: u32 = 1 << (STRONG_WIDTH - 1);
 
 thread_local! {
     static DISPOSE_COUNTER: Cell<usize> = const { Cell::new(0) };
+    /// The number of reclamation tasks executed by the current thread.
+    static EXECUTE_COUNTER: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Blocks until the reclamation tasks deferred so far by the current thread, and the ones
+/// already handed over to the global queue, have been executed. This includes the ones that are
+/// deferred in turn on the current thread while executing them (e.g., the successors of a long
+/// chain).
+///
+/// This is mainly for deterministic tests (e.g., checking that every object is reclaimed after
+/// dropping the last [`Rc`]) and controlled shutdown.
+///
+/// Note that the tasks are deferred in a thread-local buffer first. Tasks left in the buffers
+/// of other threads are not waited for, until those threads flush them (e.g., with
+/// [`Guard::flush`]) or exit. Also, it may block indefinitely if other threads never leave
+/// their critical sections, as it must wait for the global epoch to advance.
+///
+/// # Panics
+///
+/// Panics if the current thread is in a critical section.
+pub fn quiescent_barrier() {
+    loop {
+        let executed = EXECUTE_COUNTER.with(Cell::get);
+        barrier();
+        // The tasks executed on this thread may have deferred more tasks.
+        if EXECUTE_COUNTER.with(Cell::get) == executed {
+            return;
+        }
+    }
+}
+
+/// Runs the pending destructors before the program exits, so that the objects holding external
+/// resources (e.g., files and sockets) are cleaned up.
+///
+/// Without it, the deferred reclamation tasks left in the EBR buffers at exit are never
+/// executed. It shuts down the reclaimer thread first (see [`crate::set_reclaim_thread`]), so
+/// that the tasks are executed on the calling thread, and then waits for the tasks like
+/// [`quiescent_barrier`]: the ones deferred by the calling thread, and the ones of the threads
+/// which have already exited, as an exiting thread hands its buffer over to the global queue.
+///
+/// Call it from the main thread after joining the other threads. The tasks left in the buffers
+/// of the threads which are still running are not waited for, and it blocks as long as any of
+/// them stays in a critical section. Objects which become unreachable after this call are
+/// reclaimed as usual, so it may be called again.
+///
+/// # Panics
+///
+/// Panics if the current thread is in a critical section, instead of blocking forever.
+pub fn shutdown() {
+    set_reclaim_thread(false);
+    quiescent_barrier();
+}
+
+/// The order in which the immediate recursive destruction (IRD) visits the successors of
+/// destructed objects.
+///
+/// The edges popped by [`RcObject::pop_edges`] wait in a buffer until they are visited, and the
+/// order determines how large the buffer grows:
+///
+/// * [`Order::Dfs`] visits the successors of the most recently destructed object first. The
+///   buffer is bounded by the depth of the structure times its fan-out, so it suits chains and
+///   deep structures with a small fan-out.
+/// * [`Order::Bfs`] visits the successors in the order they are popped. The buffer is bounded by
+///   the width of the widest level, so it suits shallow structures, and deep ones whose nodes
+///   hang many side branches off a long spine.
+///
+/// For a plain chain, both orders keep only a single edge in the buffer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum Order {
+    /// Depth-first order. This is the default.
+    #[default]
+    Dfs,
+    /// Breadth-first order.
+    Bfs,
+}
+
+static IRD_ORDER: AtomicU8 = AtomicU8::new(Order::Dfs as u8);
+
+/// Sets the order in which the immediate recursive destruction visits the successors of
+/// destructed objects. See [`Order`] for the tradeoff.
+///
+/// The order is global, and it takes effect from the next destruction.
+pub fn set_ird_order(order: Order) {
+    IRD_ORDER.store(order as u8, Ordering::Relaxed);
+}
+
+fn ird_order() -> Order {
+    if IRD_ORDER.load(Ordering::Relaxed) == Order::Bfs as u8 {
+        Order::Bfs
+    } else {
+        Order::Dfs
+    }
 }
 
 /// Effectively wraps the presence of epoch and destruction bits.
@@ -78,6 +194,11 @@ impl State {
         ((self.inner & WEAK) / WEAK_COUNT) as u32
     }
 
+    #[cfg(debug_assertions)]
+    fn poisoned(self) -> bool {
+        self.destructed() && self.strong() >= POISON
+    }
+
     fn destructed(self) -> bool {
         (self.inner & DESTRUCTED) != 0
     }
@@ -86,6 +207,10 @@ impl State {
         (self.inner & WEAKED) != 0
     }
 
+    fn pooled(self) -> bool {
+        (self.inner & POOLED) != 0
+    }
+
     fn with_epoch(self, epoch: usize) -> Self {
         Self::from_raw((self.inner & !EPOCH) | (((epoch as u64) << EPOCH_MASK_HEIGHT) & EPOCH))
     }
@@ -99,6 +224,11 @@ impl State {
         Self::from_raw(self.inner - (val as u64) * COUNT)
     }
 
+    #[cfg(debug_assertions)]
+    fn with_poison(self) -> Self {
+        Self::from_raw((self.inner & !STRONG) | ((POISON as u64) * COUNT)).with_destructed(true)
+    }
+
     fn add_weak(self, val: u32) -> Self {
         Self::from_raw(self.inner + (val as u64) * WEAK_COUNT)
     }
@@ -150,41 +280,245 @@ impl<const WIDTH: u32> Modular<WIDTH> {
 }
 
 /// A reference-counted object of type `T` with an atomic reference counts.
-pub(crate) struct RcInner<T> {
+///
+/// The object is placed at the beginning, so that a pointer to the object can be cast back to
+/// the pointer to its `RcInner` (see [`Rc::into_ptr`]).
+///
+/// It is exposed only as an opaque allocation for [`crate::raw`].
+#[repr(C)]
+pub struct RcInner<T> {
     storage: ManuallyDrop<T>,
     state: AtomicU64,
 }
 
+/// Returns the size of the heap allocation for an object of `T` managed by [`Rc`], which
+/// includes the header of the reference counts.
+///
+/// It is useful for accounting the memory footprint of a data structure. The header is a
+/// single 64-bit word placed after the object, so the allocation is the size of `T` rounded
+/// up to a multiple of 8, plus 8 bytes, and then rounded up to [`rc_inner_align`].
+pub const fn rc_inner_size<T>() -> usize {
+    size_of::<RcInner<T>>()
+}
+
+/// Returns the alignment of the heap allocation for an object of `T` managed by [`Rc`], which
+/// is the larger of the alignments of `T` and of the header of the reference counts.
+pub const fn rc_inner_align<T>() -> usize {
+    align_of::<RcInner<T>>()
+}
+
+/// Asserts that `T` has drop glue, and thus cannot be [`Copy`].
+///
+/// A node type whose edges are counted pointers ([`Rc`], [`crate::AtomicRc`], [`crate::Weak`],
+/// ...) always has drop glue. If a node type loses it, e.g., because its edges were replaced
+/// with plain indices or raw pointers while the definition evolved, it may derive [`Copy`], and
+/// a copy of a node would bypass the reference counts: [`EdgeTaker::take`] nulls out the edges
+/// of only one of the copies. Wrapping a trivially copyable value in [`Rc`] is rarely intended
+/// in the first place.
+///
+/// This is not enforced on [`RcObject`] itself, as the leaf types without any edges are
+/// legitimate. Call it for the types which are supposed to have edges, preferably in a const
+/// item, so that it fails at compile time:
+///
+/// ```
+/// use circ::{assert_not_trivially_copyable, AtomicRc, EdgeTaker, RcObject};
+///
+/// struct Node {
+///     next: AtomicRc<Node>,
+/// }
+///
+/// unsafe impl RcObject for Node {
+///     fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+///         out.take(&mut self.next);
+///     }
+/// }
+///
+/// const _: () = assert_not_trivially_copyable::<Node>();
+/// ```
+///
+/// ```compile_fail
+/// use circ::{assert_not_trivially_copyable, EdgeTaker, RcObject};
+///
+/// #[derive(Clone, Copy)]
+/// struct Node {
+///     next: usize,
+/// }
+///
+/// unsafe impl RcObject for Node {
+///     fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+/// }
+///
+/// const _: () = assert_not_trivially_copyable::<Node>();
+/// ```
+///
+/// # Panics
+///
+/// Panics if `T` has no drop glue (see [`std::mem::needs_drop`]).
+pub const fn assert_not_trivially_copyable<T: RcObject>() {
+    assert!(
+        needs_drop::<T>(),
+        "the object type has no drop glue, so it may be `Copy`"
+    );
+}
+
+/// Allocates an object of `T` managed by the same allocation as [`Rc`], with `count` strong
+/// references to it.
+///
+/// See [`crate::raw`] for the usage.
+///
+/// # Safety
+///
+/// The returned allocation must be released in exactly one way: either freed with
+/// [`free_inner`] while no other reference to it exists, or handed over to `count` [`Rc`]s
+/// with [`Rc::from_ptr`] (of [`RcInner::data_ptr`]), which release it through the backend EBR.
+/// Handing over fewer or more counts than `count` leaks the object or reclaims it too early.
+#[inline]
+pub unsafe fn alloc_inner<T>(obj: T, count: u32) -> *mut RcInner<T> {
+    RcInner::alloc(obj, count)
+}
+
+/// Destructs the object of an allocation from [`alloc_inner`] and frees the allocation
+/// immediately, without deferring it to the backend EBR.
+///
+/// The object is destructed like a reclaimed [`Rc`]: its edges are taken with
+/// [`RcObject::pop_edges`], [`RcObject::on_reclaim`] is called, and then it is dropped. The
+/// successors which become unreachable are reclaimed as usual.
+///
+/// # Safety
+///
+/// `ptr` must originate from [`alloc_inner`] of the same `T`, and must not be freed already.
+/// No [`Rc`], [`crate::Weak`] or [`crate::Snapshot`] may have been made from it, and no other
+/// thread may access it anymore.
+#[inline]
+pub unsafe fn free_inner<T: RcObject>(ptr: *mut RcInner<T>) {
+    // Release all the strong references at once and mark it as destructed, like `try_destruct`.
+    let state = State::from_raw((*ptr).state.load(Ordering::Relaxed));
+    (*ptr).state.store(
+        state
+            .sub_strong(state.strong())
+            .with_destructed(true)
+            .as_raw(),
+        Ordering::Relaxed,
+    );
+    dispose(ptr);
+}
+
+/// Returns the number of the low tag bits available in the pointers to `T` managed by [`Rc`].
+///
+/// Tags given to the pointers (e.g., [`Rc::with_tag`]) are truncated to this many bits.
+/// As the header of the reference counts is a 64-bit word, there are at least 3 bits.
+pub const fn available_tag_bits<T>() -> u32 {
+    rc_inner_align::<T>().trailing_zeros()
+}
+
 impl<T> RcInner<T> {
     #[inline(always)]
     pub(crate) fn alloc(obj: T, init_strong: u32) -> *mut Self {
-        let obj = Self {
+        #[cfg(feature = "alloc-limit")]
+        crate::alloc_limit::reserve(size_of::<Self>());
+        Self::alloc_reserved(obj, init_strong)
+    }
+
+    /// Allocates an object like [`RcInner::alloc`], or returns `None` if it would exceed the
+    /// cap set by [`crate::set_alloc_limit`].
+    #[cfg(feature = "alloc-limit")]
+    #[inline(always)]
+    pub(crate) fn try_alloc(obj: T, init_strong: u32) -> Option<*mut Self> {
+        if !crate::alloc_limit::try_reserve(size_of::<Self>()) {
+            return None;
+        }
+        Some(Self::alloc_reserved(obj, init_strong))
+    }
+
+    #[inline(always)]
+    fn alloc_reserved(obj: T, init_strong: u32) -> *mut Self {
+        #[cfg(feature = "stats")]
+        crate::stats::ALLOCATED.fetch_add(1, Ordering::Relaxed);
+        Box::into_raw(Box::new(Self::init(obj, init_strong, false)))
+    }
+
+    /// Allocates an object like [`RcInner::alloc`], reusing a memory block in the pool of the
+    /// current thread if any. The block is returned to a pool when it is reclaimed.
+    #[inline(always)]
+    pub(crate) fn alloc_pooled(obj: T, init_strong: u32) -> *mut Self {
+        #[cfg(feature = "alloc-limit")]
+        crate::alloc_limit::reserve(size_of::<Self>());
+        #[cfg(feature = "stats")]
+        crate::stats::ALLOCATED.fetch_add(1, Ordering::Relaxed);
+        let ptr = crate::pool::take_block(Layout::new::<Self>()).cast::<Self>();
+        unsafe { ptr.write(Self::init(obj, init_strong, true)) };
+        ptr
+    }
+
+    #[inline(always)]
+    fn init(obj: T, init_strong: u32, pooled: bool) -> Self {
+        let pooled = if pooled { POOLED } else { 0 };
+        Self {
             storage: ManuallyDrop::new(obj),
-            state: AtomicU64::new((init_strong as u64) * COUNT + WEAK_COUNT),
-        };
-        Box::into_raw(Box::new(obj))
+            state: AtomicU64::new((init_strong as u64) * COUNT + WEAK_COUNT + pooled),
+        }
     }
 
     /// # Safety
     ///
     /// The given `ptr` must not be shared across more than one thread.
     pub(crate) unsafe fn dealloc(ptr: *mut Self) {
-        drop(Box::from_raw(ptr));
+        if State::from_raw((*ptr).state.load(Ordering::Relaxed)).pooled() {
+            // The object is already dropped, and the header needs no drop.
+            crate::pool::recycle_block(ptr.cast(), Layout::new::<Self>());
+        } else {
+            drop(Box::from_raw(ptr));
+        }
+        #[cfg(feature = "alloc-limit")]
+        crate::alloc_limit::release(size_of::<Self>());
     }
 
     /// Returns an immutable reference to the object.
     pub fn data(&self) -> &T {
+        #[cfg(debug_assertions)]
+        assert!(
+            !State::from_raw(self.state.load(Ordering::Relaxed)).poisoned(),
+            "dereferencing an object which is already reclaimed"
+        );
         &self.storage
     }
 
-    /// Returns a mutable reference to the object.
-    pub fn data_mut(&mut self) -> &mut T {
-        &mut self.storage
+    /// Returns a raw pointer to the object.
+    ///
+    /// Unlike taking `&mut RcInner<T>`, it does not create a reference covering the counters,
+    /// which may be accessed by other threads at the same time.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be a valid pointer to `RcInner<T>`.
+    #[inline]
+    pub unsafe fn data_ptr(ptr: *mut Self) -> *mut T {
+        // `ManuallyDrop<T>` has the same layout with `T`.
+        addr_of_mut!((*ptr).storage).cast::<T>()
+    }
+
+    /// Returns the current strong reference count of the object.
+    #[inline]
+    pub(crate) fn strong(&self) -> u32 {
+        State::from_raw(self.state.load(Ordering::SeqCst)).strong()
+    }
+
+    /// Returns the current weak reference count of the object.
+    #[inline]
+    pub(crate) fn weak(&self) -> u32 {
+        State::from_raw(self.state.load(Ordering::SeqCst)).weak()
     }
 
     #[inline]
     pub(crate) fn increment_strong(&self) -> bool {
-        let val = State::from_raw(self.state.fetch_add(COUNT, Ordering::SeqCst));
+        self.increment_strong_many(1)
+    }
+
+    /// Increments the strong count by `count` at once, like [`RcInner::increment_strong`].
+    #[inline]
+    pub(crate) fn increment_strong_many(&self, count: u32) -> bool {
+        debug_assert!(count > 0);
+        let val = State::from_raw(self.state.fetch_add(count as u64 * COUNT, Ordering::SeqCst));
         if val.destructed() {
             return false;
         }
@@ -235,12 +569,34 @@ impl<T> RcInner<T> {
 
     #[inline]
     pub(crate) unsafe fn decrement_weak(ptr: *mut Self, guard: Option<&Guard>) {
-        debug_assert!(State::from_raw((*ptr).state.load(Ordering::SeqCst)).weak() >= 1);
-        if State::from_raw((*ptr).state.fetch_sub(WEAK_COUNT, Ordering::SeqCst)).weak() == 1 {
+        Self::decrement_weak_many(ptr, 1, guard);
+    }
+
+    #[inline]
+    pub(crate) unsafe fn decrement_weak_many(ptr: *mut Self, count: u32, guard: Option<&Guard>) {
+        debug_assert!(State::from_raw((*ptr).state.load(Ordering::SeqCst)).weak() >= count);
+        if State::from_raw(
+            (*ptr)
+                .state
+                .fetch_sub(count as u64 * WEAK_COUNT, Ordering::SeqCst),
+        )
+        .weak()
+            == count
+        {
             guard.defer_with_inner(ptr, |inner| Self::try_dealloc(inner));
         }
     }
 
+    /// Marks a destructed object as poisoned. See [`POISON`].
+    #[cfg(debug_assertions)]
+    unsafe fn poison(ptr: *mut Self) {
+        let _ = (*ptr)
+            .state
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |state| {
+                Some(State::from_raw(state).with_poison().as_raw())
+            });
+    }
+
     #[inline]
     pub(crate) fn is_not_destructed(&self) -> bool {
         let mut old = State::from_raw(self.state.load(Ordering::SeqCst));
@@ -262,10 +618,40 @@ impl<T> RcInner<T> {
 impl<T: RcObject> RcInner<T> {
     #[inline]
     pub(crate) unsafe fn decrement_strong(ptr: *mut Self, count: u32, guard: Option<&Guard>) {
+        let hit_zero = Self::sub_strong_with_epoch(ptr, count);
+
+        let trigger_recl = |guard: &Guard| {
+            if hit_zero {
+                guard.defer_with_inner(ptr, |inner| Self::try_destruct_routed(inner));
+            }
+            // Periodically triggers a collection.
+            guard.incr_manual_collection();
+        };
+
+        if let Some(guard) = guard {
+            trigger_recl(guard)
+        } else if hit_zero {
+            trigger_recl(&cs())
+        }
+        // Otherwise, nothing needs to be deferred. Pinning the thread only to count the event
+        // for the periodic collection is not worth it, e.g., for an `Rc` dropped by a container
+        // which knows nothing about the guards.
+    }
+
+    /// Decrements the strong count, marking the current epoch on it.
+    /// Returns `true` if the count has reached zero.
+    #[inline]
+    unsafe fn sub_strong_with_epoch(ptr: *mut Self, count: u32) -> bool {
         let epoch = global_epoch();
         // Should mark the current epoch on the strong count with CAS.
-        let hit_zero = loop {
+        loop {
             let curr = State::from_raw((*ptr).state.load(Ordering::SeqCst));
+            #[cfg(debug_assertions)]
+            assert!(
+                !curr.poisoned(),
+                "releasing a strong reference to an object which is already reclaimed \
+                (e.g., a double free)"
+            );
             debug_assert!(curr.strong() >= count);
             if (*ptr)
                 .state
@@ -277,33 +663,56 @@ impl<T: RcObject> RcInner<T> {
                 )
                 .is_ok()
             {
-                break curr.strong() == count;
+                return curr.strong() == count;
             }
-        };
+        }
+    }
 
-        let trigger_recl = |guard: &Guard| {
-            if hit_zero {
-                guard.defer_with_inner(ptr, |inner| Self::try_destruct(inner));
-            }
-            // Periodically triggers a collection.
-            guard.incr_manual_collection();
-        };
+    /// Decrements the strong count, and destructs the object right away if the count has reached
+    /// zero, instead of deferring it with EBR.
+    ///
+    /// # Safety
+    ///
+    /// No other thread may hold a [`crate::Snapshot`] (or any other uncounted reference) to
+    /// the object.
+    #[inline]
+    pub(crate) unsafe fn decrement_strong_now(ptr: *mut Self) {
+        if Self::sub_strong_with_epoch(ptr, 1) {
+            Self::try_destruct_routed(ptr);
+        }
+    }
 
-        if let Some(guard) = guard {
-            trigger_recl(guard)
+    /// Destructs the object like `try_destruct`, but on the reclaimer thread if `T` prefers it.
+    /// See [`RcObject::DEFER_TO_BACKGROUND`].
+    #[inline]
+    unsafe fn try_destruct_routed(ptr: *mut Self) {
+        if T::DEFER_TO_BACKGROUND {
+            run_on_reclaimer(move || Self::try_destruct(ptr));
         } else {
-            trigger_recl(&cs())
+            Self::try_destruct(ptr);
         }
     }
 
     #[inline]
     unsafe fn try_destruct(ptr: *mut Self) {
+        if Self::try_mark_destructed(ptr, None) {
+            // Note that `decrement_weak` will be called in `dispose`.
+            dispose(ptr);
+        }
+    }
+
+    /// Marks the object as destructed and returns `true` if its strong count is still zero.
+    ///
+    /// Otherwise, the object has been revived by an upgrade, which also gave the destructing
+    /// side a count to release. Releases it and returns `false` in that case.
+    #[inline]
+    unsafe fn try_mark_destructed(ptr: *mut Self, guard: Option<&Guard>) -> bool {
         let mut old = State::from_raw((*ptr).state.load(Ordering::SeqCst));
         debug_assert!(!old.destructed());
         loop {
             if old.strong() > 0 {
-                Self::decrement_strong(ptr, 1, None);
-                return;
+                Self::decrement_strong(ptr, 1, guard);
+                return false;
             }
             match (*ptr).state.compare_exchange(
                 old.as_raw(),
@@ -311,8 +720,7 @@ impl<T: RcObject> RcInner<T> {
                 Ordering::SeqCst,
                 Ordering::SeqCst,
             ) {
-                // Note that `decrement_weak` will be called in `dispose`.
-                Ok(_) => return dispose(ptr),
+                Ok(_) => return true,
                 Err(curr) => old = State::from_raw(curr),
             }
         }
@@ -321,9 +729,37 @@ impl<T: RcObject> RcInner<T> {
 
 #[inline]
 unsafe fn dispose<T: RcObject>(inner: *mut RcInner<T>) {
+    dispose_in_order(inner, ird_order());
+}
+
+/// Destructs the object and its successors which are immediately reclaimable, visiting them in
+/// the given order. Returns the peak number of edges waiting in the buffer.
+unsafe fn dispose_in_order<T: RcObject>(inner: *mut RcInner<T>, order: Order) -> usize {
     DISPOSE_COUNTER.with(|counter| {
         let guard = &cs();
-        dispose_general_node(inner, DisposeContext::new(0, counter, guard));
+        let mut queue = IrdQueue::new(order);
+        dispose_general_node(inner, DisposeContext::new(0, counter, guard), &mut queue);
+        while let Some((next, ctx, succ_epoch)) = queue.pop() {
+            next.try_ird(ctx, &mut queue, succ_epoch);
+        }
+        queue.peak
+    })
+}
+
+/// Releases the edges taken from an object destructed outside of the EBR (i.e., by
+/// [`crate::local`]), destructing the successors which become unreachable like IRD.
+pub(crate) unsafe fn release_edges(edges: Vec<TryIRD>, guard: &Guard) {
+    if edges.is_empty() {
+        return;
+    }
+    // The object was never shared, so its successors are stamped with the current epoch.
+    let succ_epoch = State::from_raw(0).with_epoch(global_epoch()).epoch();
+    DISPOSE_COUNTER.with(|counter| {
+        let mut queue = IrdQueue::new(ird_order());
+        queue.extend(edges, DisposeContext::new(0, counter, guard), succ_epoch);
+        while let Some((next, ctx, succ_epoch)) = queue.pop() {
+            next.try_ird(ctx, &mut queue, succ_epoch);
+        }
     });
 }
 
@@ -352,12 +788,69 @@ impl<'d> DisposeContext<'d> {
     }
 }
 
+/// Edges popped from destructed objects, waiting for the immediate recursive destruction.
+pub(crate) struct IrdQueue<'d> {
+    order: Order,
+    edges: VecDeque<(TryIRD, DisposeContext<'d>, u32)>,
+    peak: usize,
+}
+
+impl<'d> IrdQueue<'d> {
+    fn new(order: Order) -> Self {
+        Self {
+            order,
+            edges: VecDeque::new(),
+            peak: 0,
+        }
+    }
+
+    /// Pushes the non-null edges popped from an object whose epoch is `succ_epoch`.
+    fn extend(&mut self, edges: Vec<TryIRD>, ctx: DisposeContext<'d>, succ_epoch: u32) {
+        let edges = edges.into_iter().filter(|next| !next.is_null());
+        match self.order {
+            // Pushes them in reverse, so that the first edge is popped first.
+            Order::Dfs => edges
+                .rev()
+                .for_each(|next| self.edges.push_back((next, ctx.clone(), succ_epoch))),
+            Order::Bfs => {
+                edges.for_each(|next| self.edges.push_back((next, ctx.clone(), succ_epoch)))
+            }
+        }
+        self.peak = self.peak.max(self.edges.len());
+    }
+
+    fn pop(&mut self) -> Option<(TryIRD, DisposeContext<'d>, u32)> {
+        match self.order {
+            Order::Dfs => self.edges.pop_back(),
+            Order::Bfs => self.edges.pop_front(),
+        }
+    }
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Makes IRD regard every successor as immediately reclaimable, so that the traversal can be
+    /// tested deterministically.
+    static IGNORE_EPOCHS: Cell<bool> = const { Cell::new(false) };
+}
+
 #[inline]
-unsafe fn dispose_general_node<T: RcObject>(ptr: *mut RcInner<T>, ctx: DisposeContext<'_>) {
-    let rc = match ptr.as_mut() {
-        Some(rc) => rc,
-        None => return,
-    };
+fn ignore_epochs() -> bool {
+    #[cfg(test)]
+    return IGNORE_EPOCHS.with(Cell::get);
+    #[cfg(not(test))]
+    false
+}
+
+#[inline]
+unsafe fn dispose_general_node<'d, T: RcObject>(
+    ptr: *mut RcInner<T>,
+    ctx: DisposeContext<'d>,
+    queue: &mut IrdQueue<'d>,
+) {
+    if ptr.is_null() {
+        return;
+    }
 
     let count = ctx.counter.get();
     ctx.counter.set(count + 1);
@@ -368,15 +861,16 @@ unsafe fn dispose_general_node<T: RcObject>(ptr: *mut RcInner<T>, ctx: DisposeCo
     }
 
     if ctx.depth >= 1024 {
-        // Prevent a potential stack overflow.
+        // Bound the work of a single disposal.
         ctx.guard
-            .defer_with_inner(rc, |rc| RcInner::try_destruct(rc));
+            .defer_with_inner(ptr, |rc| RcInner::try_destruct_routed(rc));
         return;
     }
 
-    let state = State::from_raw(rc.state.load(Ordering::SeqCst));
+    let state = State::from_raw((*ptr).state.load(Ordering::SeqCst));
     let node_epoch = state.epoch();
-    debug_assert_eq!(state.strong(), 0);
+    // A root is marked as destructed by `try_destruct`.
+    debug_assert!(ctx.depth > 0 || state.destructed());
 
     let curr_epoch = global_epoch();
     let modu: Modular<EPOCH_WIDTH> = Modular::new(curr_epoch as isize + 1);
@@ -384,30 +878,50 @@ unsafe fn dispose_general_node<T: RcObject>(ptr: *mut RcInner<T>, ctx: DisposeCo
 
     // Note that checking whether it is a root is necessary, because if `node_epoch` is
     // old enough, `modu.le` may return false.
-    if ctx.depth == 0 || modu.le(node_epoch as _, curr_epoch as isize - 3) {
-        // The current node is immediately reclaimable.
+    if ctx.depth == 0 || modu.le(node_epoch as _, curr_epoch as isize - 3) || ignore_epochs() {
+        if ctx.depth > 0 && T::DEFER_TO_BACKGROUND && reclaimer_available() {
+            // Let the reclaimer destruct it as a root.
+            run_on_reclaimer(move || RcInner::try_destruct(ptr));
+            return;
+        }
+        // The current node is immediately reclaimable, unless it has been revived by
+        // `Weak::upgrade` in the meantime.
+        if ctx.depth > 0 && !RcInner::try_mark_destructed(ptr, Some(ctx.guard)) {
+            return;
+        }
         // Before freeing this allocation, let's collect outgoing edges.
-        rc.data_mut().pop_edges(&mut EdgeTaker::new(&mut outgoings));
+        let data = &mut *RcInner::data_ptr(ptr);
+        let mut out = EdgeTaker::new(&mut outgoings);
+        data.pop_edges(&mut out);
+        out.assert_no_local();
+        data.on_reclaim(ctx.guard);
 
-        ManuallyDrop::drop(&mut rc.storage);
-        if State::from_raw(rc.state.load(Ordering::SeqCst)).weaked() {
-            RcInner::decrement_weak(rc, Some(ctx.guard));
+        ptr::drop_in_place(data);
+        #[cfg(debug_assertions)]
+        RcInner::poison(ptr);
+        #[cfg(feature = "stats")]
+        crate::stats::DISPOSED.fetch_add(1, Ordering::Relaxed);
+        if State::from_raw((*ptr).state.load(Ordering::SeqCst)).weaked() {
+            RcInner::decrement_weak(ptr, Some(ctx.guard));
         } else {
-            RcInner::dealloc(rc);
+            RcInner::dealloc(ptr);
         }
 
-        for next in outgoings.drain(..) {
-            next.try_ird(ctx.clone(), node_epoch);
-        }
+        queue.extend(outgoings, ctx, node_epoch);
     } else {
         // It is likely to be unsafe to reclaim right now.
         ctx.guard
-            .defer_with_inner(rc, |rc| RcInner::try_destruct(rc));
+            .defer_with_inner(ptr, |rc| RcInner::try_destruct_routed(rc));
     }
 }
 
 #[inline]
-unsafe fn try_imm_recur_destr<T: RcObject>(next: Rc<T>, ctx: DisposeContext<'_>, succ_epoch: u32) {
+unsafe fn try_imm_recur_destr<'d, T: RcObject>(
+    next: Rc<T>,
+    ctx: DisposeContext<'d>,
+    queue: &mut IrdQueue<'d>,
+    succ_epoch: u32,
+) {
     if next.is_null() {
         return;
     }
@@ -439,18 +953,193 @@ unsafe fn try_imm_recur_destr<T: RcObject>(next: Rc<T>, ctx: DisposeContext<'_>,
 
     // If the reference count hit zero, try dispose it recursively.
     if next_cnt.strong() == 0 {
-        dispose_general_node(next_ptr.as_raw(), ctx.deepen());
+        #[cfg(feature = "stats")]
+        crate::stats::IRD_RECURSIONS.fetch_add(1, Ordering::Relaxed);
+        dispose_general_node(next_ptr.as_raw(), ctx.deepen(), queue);
     }
 }
 
-pub(crate) unsafe fn try_ird_with_raw<T: RcObject>(
+pub(crate) unsafe fn try_ird_with_raw<'d, T: RcObject>(
     next: Raw<()>,
-    ctx: DisposeContext<'_>,
+    ctx: DisposeContext<'d>,
+    queue: &mut IrdQueue<'d>,
     succ_epoch: u32,
 ) {
     try_imm_recur_destr(
         Rc::from_raw(transmute::<Raw<()>, Raw<T>>(next)),
         ctx,
+        queue,
         succ_epoch,
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use std::mem::size_of;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::{
+        alloc_inner, available_tag_bits, dispose_in_order, free_inner, rc_inner_align,
+        rc_inner_size, Order, State, IGNORE_EPOCHS,
+    };
+    use crate::{cs, AtomicRc, EdgeTaker, Guard, Rc, RcObject};
+
+    struct Node {
+        children: Vec<AtomicRc<Node>>,
+    }
+
+    unsafe impl RcObject for Node {
+        fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+            out.take_all(&mut self.children);
+        }
+    }
+
+    impl Node {
+        fn new(children: Vec<Rc<Node>>) -> Rc<Self> {
+            Rc::new(Self {
+                children: children.into_iter().map(AtomicRc::from).collect(),
+            })
+        }
+    }
+
+    /// Destructs the structure rooted at `root` at once, and returns the peak buffer size.
+    fn peak_buffer(root: Rc<Node>, order: Order) -> usize {
+        let ptr = root.into_raw().as_raw();
+        unsafe {
+            // Release the last reference and mark it as destructed, like `try_destruct`.
+            let state = State::from_raw((*ptr).state.load(Ordering::SeqCst));
+            (*ptr).state.store(
+                state.sub_strong(1).with_destructed(true).as_raw(),
+                Ordering::SeqCst,
+            );
+            IGNORE_EPOCHS.with(|ignore| ignore.set(true));
+            let peak = dispose_in_order(ptr, order);
+            IGNORE_EPOCHS.with(|ignore| ignore.set(false));
+            peak
+        }
+    }
+
+    /// A complete 16-ary tree of height 2.
+    fn wide() -> Rc<Node> {
+        Node::new(
+            (0..16)
+                .map(|_| Node::new((0..16).map(|_| Node::new(vec![])).collect()))
+                .collect(),
+        )
+    }
+
+    /// A spine of 200 nodes, each of which has a leaf as a side branch.
+    fn deep() -> Rc<Node> {
+        (0..200).fold(Node::new(vec![]), |spine, _| {
+            Node::new(vec![spine, Node::new(vec![])])
+        })
+    }
+
+    #[test]
+    fn free_inner_reclaims_like_rc() {
+        static RECLAIMS: AtomicUsize = AtomicUsize::new(0);
+        static LEAF_DROPS: AtomicUsize = AtomicUsize::new(0);
+
+        struct Leaf;
+
+        unsafe impl RcObject for Leaf {
+            fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+        }
+
+        impl Drop for Leaf {
+            fn drop(&mut self) {
+                LEAF_DROPS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        struct Parent {
+            child: AtomicRc<Leaf>,
+        }
+
+        unsafe impl RcObject for Parent {
+            fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+                out.take(&mut self.child);
+            }
+
+            fn on_reclaim(&mut self, _: &Guard) {
+                // The edges are taken before.
+                assert!(self.child.load(Ordering::Relaxed, &cs()).is_null());
+                RECLAIMS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let parent = Parent {
+            child: AtomicRc::new(Leaf),
+        };
+        unsafe { free_inner(alloc_inner(parent, 2)) };
+        assert_eq!(RECLAIMS.load(Ordering::Relaxed), 1);
+
+        crate::quiescent_barrier();
+        assert_eq!(LEAF_DROPS.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn ird_order_peak_buffer() {
+        // DFS keeps the unvisited siblings along the current path.
+        assert_eq!(peak_buffer(wide(), Order::Dfs), 15 + 16);
+        assert_eq!(peak_buffer(deep(), Order::Dfs), 201);
+        // BFS keeps a whole level.
+        assert_eq!(peak_buffer(wide(), Order::Bfs), 16 * 16);
+        assert_eq!(peak_buffer(deep(), Order::Bfs), 3);
+    }
+
+    #[test]
+    fn rc_inner_layout() {
+        #[repr(align(32))]
+        struct Aligned {
+            _bytes: [u8; 40],
+        }
+
+        // The object, padded to the header, followed by the 8-byte header.
+        assert_eq!((rc_inner_size::<()>(), rc_inner_align::<()>()), (8, 8));
+        assert_eq!((rc_inner_size::<u8>(), rc_inner_align::<u8>()), (16, 8));
+        assert_eq!((rc_inner_size::<u64>(), rc_inner_align::<u64>()), (16, 8));
+        assert_eq!(
+            (rc_inner_size::<[u8; 13]>(), rc_inner_align::<[u8; 13]>()),
+            (24, 8)
+        );
+        assert_eq!(
+            (rc_inner_size::<Aligned>(), rc_inner_align::<Aligned>()),
+            (96, 32)
+        );
+        assert_eq!(available_tag_bits::<u8>(), 3);
+        assert_eq!(available_tag_bits::<Aligned>(), 5);
+
+        // A list node only adds the header to the link.
+        struct Node {
+            next: AtomicRc<Node>,
+        }
+
+        unsafe impl RcObject for Node {
+            fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+                out.take(&mut self.next);
+            }
+        }
+
+        assert_eq!(
+            rc_inner_size::<Node>(),
+            size_of::<Node>().next_multiple_of(8) + 8
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn many_strong_references_are_not_poisoned() {
+        use super::{RcInner, POISON};
+
+        let rc = Node::new(Vec::new());
+        let ptr = rc.ptr.as_raw();
+        unsafe {
+            // A live object may have as many references as the count of a poisoned one.
+            assert!((*ptr).increment_strong_many(POISON));
+            assert!(rc.as_ref().unwrap().children.is_empty());
+            RcInner::decrement_strong(ptr, POISON, Some(&cs()));
+        }
+        assert!(rc.is_unique());
+    }
+}
@@ -0,0 +1,251 @@
+//! An atomic [`Rc`] pointer paired with a version counter.
+
+use std::{
+    marker::PhantomData,
+    mem::{forget, size_of},
+    sync::atomic::Ordering,
+};
+
+use portable_atomic::AtomicU128;
+use static_assertions::const_assert;
+
+use crate::ebr_impl::{Guard, Tagged};
+use crate::strong::{cas_retrying_epoch, validate_cas_orderings};
+use crate::utils::{Raw, RcInner};
+use crate::{CompareExchangeError, Rc, RcObject, Snapshot};
+
+// A pointer must fit in the lower half of the double-width word.
+const_assert!(size_of::<usize>() <= size_of::<u64>());
+
+/// A thread-safe (atomic) mutable memory location that contains an [`Rc<T>`] and a version.
+///
+/// Unlike [`crate::AtomicRc`], whose tag is limited to the unused least significant bits of the
+/// pointer, the version is a full 64-bit counter which is incremented on every successful
+/// [`AtomicRcVersioned::compare_exchange`]. Thus, a pointer which is replaced and then installed
+/// again is not confused with the one loaded before (the ABA problem), no matter how many times
+/// it happens.
+///
+/// The pointer and the version are stored in a 128-bit atomic word. It uses the double-width
+/// CAS instruction (e.g., `cmpxchg16b` on x86-64) if the target supports it, and falls back to
+/// a global lock otherwise. Use [`AtomicRcVersioned::is_lock_free`] to check which one is used.
+pub struct AtomicRcVersioned<T: RcObject> {
+    link: AtomicU128,
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: RcObject + Send + Sync> Send for AtomicRcVersioned<T> {}
+unsafe impl<T: RcObject + Send + Sync> Sync for AtomicRcVersioned<T> {}
+
+#[inline]
+fn pack<T>(ptr: Raw<T>, version: u64) -> u128 {
+    ((version as u128) << 64) | ptr.expose_addr() as u128
+}
+
+#[inline]
+fn unpack<T>(word: u128) -> (Raw<T>, u64) {
+    (
+        Tagged::with_exposed_addr(word as u64 as usize),
+        (word >> 64) as u64,
+    )
+}
+
+impl<T: RcObject> AtomicRcVersioned<T> {
+    /// Constructs a new `AtomicRcVersioned` by allocating a new reference-counted object.
+    /// The initial version is zero.
+    #[inline]
+    pub fn new(obj: T) -> Self {
+        Self::from(Rc::new(obj))
+    }
+
+    /// Constructs a new `AtomicRcVersioned` containing a null pointer.
+    /// The initial version is zero.
+    #[inline]
+    pub fn null() -> Self {
+        Self::from(Rc::null())
+    }
+
+    /// Returns `true` if the double-width atomic operations are lock-free on this target.
+    #[inline]
+    pub fn is_lock_free() -> bool {
+        AtomicU128::is_lock_free()
+    }
+
+    /// Loads a [`Snapshot`] pointer and the current version from this `AtomicRcVersioned`.
+    ///
+    /// This method takes an [`Ordering`] argument which describes the memory ordering of
+    /// this operation. Possible values are [`Ordering::SeqCst`], [`Ordering::Acquire`] and
+    /// [`Ordering::Relaxed`].
+    #[inline]
+    pub fn load<'g>(&self, order: Ordering, guard: &'g Guard) -> (Snapshot<'g, T>, u64) {
+        let (ptr, version) = unpack(self.link.load(order));
+        (Snapshot::from_raw(ptr, guard), version)
+    }
+
+    /// Stores the [`Rc`] pointer `desired` into the atomic pointer if the current pointer and
+    /// version are the same as `expected` and `expected_version`. The tag of the pointer is also
+    /// taken into account. On success, the version is incremented by one.
+    ///
+    /// The return value is a result indicating whether the desired pointer was written.
+    /// On success the pointer that was in this `AtomicRcVersioned` is returned.
+    /// On failure the actual current pointer and version, `desired` and the expected pointer and
+    /// version are returned.
+    ///
+    /// This method takes two [`Ordering`] arguments to describe the memory
+    /// ordering of this operation, just like [`crate::AtomicRc::compare_exchange`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure` is `Release` or `AcqRel`, or if it is stronger than `success`.
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    pub fn compare_exchange<'g>(
+        &self,
+        expected: Snapshot<'g, T>,
+        expected_version: u64,
+        desired: Rc<T>,
+        success: Ordering,
+        failure: Ordering,
+        guard: &'g Guard,
+    ) -> Result<Rc<T>, CompareExchangeError<Rc<T>, (Snapshot<'g, T>, u64)>> {
+        validate_cas_orderings(success, failure);
+        let desired_word = pack(
+            desired.ptr.with_timestamp(),
+            expected_version.wrapping_add(1),
+        );
+        match cas_retrying_epoch(
+            pack(expected.ptr, expected_version),
+            &mut 0,
+            |expected_word| {
+                self.link
+                    .compare_exchange(expected_word, desired_word, success, failure)
+            },
+            |current_word, expected_word| {
+                let (current_raw, current_version) = unpack::<T>(current_word);
+                let (expected_raw, expected_version) = unpack::<T>(expected_word);
+                current_raw.ptr_eq(expected_raw) && current_version == expected_version
+            },
+        ) {
+            Ok(prev_word) => {
+                // Skip decrementing a strong count of the inserted pointer.
+                forget(desired);
+                Ok(Rc::from_raw(unpack::<T>(prev_word).0))
+            }
+            Err(current_word) => {
+                let (current_raw, current_version) = unpack::<T>(current_word);
+                Err(CompareExchangeError {
+                    desired,
+                    current: (Snapshot::from_raw(current_raw, guard), current_version),
+                    expected: (expected, expected_version),
+                })
+            }
+        }
+    }
+}
+
+impl<T: RcObject> From<Rc<T>> for AtomicRcVersioned<T> {
+    #[inline]
+    fn from(value: Rc<T>) -> Self {
+        Self {
+            link: AtomicU128::new(pack(value.into_raw(), 0)),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: RcObject> Default for AtomicRcVersioned<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::null()
+    }
+}
+
+impl<T: RcObject> Drop for AtomicRcVersioned<T> {
+    #[inline]
+    fn drop(&mut self) {
+        let (ptr, _) = unpack::<T>(*self.link.get_mut());
+        let ptr = ptr.as_raw();
+        unsafe {
+            if !ptr.is_null() {
+                RcInner::decrement_strong(ptr, 1, None);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use crate::{cs, AtomicRc, AtomicRcVersioned, EdgeTaker, Rc, RcObject};
+
+    struct Node;
+
+    unsafe impl RcObject for Node {
+        fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+    }
+
+    #[test]
+    fn version_prevents_aba() {
+        let guard = cs();
+        let a = Rc::new(Node);
+        let b = Rc::new(Node);
+
+        let plain = AtomicRc::from(&a);
+        let versioned = AtomicRcVersioned::from(a.clone());
+        let plain_loaded = plain.load(Ordering::Acquire, &guard);
+        let (loaded, version) = versioned.load(Ordering::Acquire, &guard);
+        assert_eq!(version, 0);
+
+        // A -> B -> A
+        for (expected, desired) in [(&a, &b), (&b, &a)] {
+            let snapshot = expected.snapshot(&guard);
+            assert!(plain
+                .compare_exchange(
+                    snapshot,
+                    desired.clone(),
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                    &guard
+                )
+                .is_ok());
+            let (_, version) = versioned.load(Ordering::Acquire, &guard);
+            assert!(versioned
+                .compare_exchange(
+                    snapshot,
+                    version,
+                    desired.clone(),
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                    &guard,
+                )
+                .is_ok());
+        }
+
+        // The plain tag cannot tell that the pointer has been replaced in the meantime.
+        assert!(plain
+            .compare_exchange(
+                plain_loaded,
+                Rc::null(),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                &guard
+            )
+            .is_ok());
+
+        // The version can.
+        let err = versioned
+            .compare_exchange(
+                loaded,
+                version,
+                Rc::null(),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                &guard,
+            )
+            .err()
+            .unwrap();
+        let (current, current_version) = err.current;
+        assert!(current.ptr_eq(loaded));
+        assert_eq!(current_version, 2);
+    }
+}
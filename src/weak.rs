@@ -5,12 +5,13 @@ use std::{
     sync::atomic::{AtomicUsize, Ordering},
 };
 
-use atomic::Atomic;
+use rustc_hash::FxHashMap;
 use static_assertions::const_assert;
 
-use crate::ebr_impl::{Guard, Tagged};
+use crate::ebr_impl::{AtomicTagged, Guard, Tagged};
+use crate::strong::ThreadStamp;
 use crate::utils::{Raw, RcInner};
-use crate::{CompareExchangeError, Rc, RcObject, Snapshot};
+use crate::{CompareExchangeError, DropWithGuard, PtrId, Rc, RcObject, Snapshot};
 
 /// A thread-safe (atomic) mutable memory location that contains a [`Weak<T>`].
 ///
@@ -18,7 +19,7 @@ use crate::{CompareExchangeError, Rc, RcObject, Snapshot};
 /// least significant bits of the address. For example, the tag for a pointer to a sized type `T`
 /// should be less than `(1 << align_of::<T>().trailing_zeros())`.
 pub struct AtomicWeak<T> {
-    pub(crate) link: Atomic<Raw<T>>,
+    pub(crate) link: AtomicTagged<RcInner<T>>,
 }
 
 unsafe impl<T: Send + Sync> Send for AtomicWeak<T> {}
@@ -26,16 +27,15 @@ unsafe impl<T: Send + Sync> Sync for AtomicWeak<T> {}
 
 // Ensure that TaggedPtr<T> is 8-byte long,
 // so that lock-free atomic operations are possible.
-const_assert!(Atomic::<Raw<u8>>::is_lock_free());
 const_assert!(size_of::<Raw<u8>>() == size_of::<usize>());
-const_assert!(size_of::<Atomic<Raw<u8>>>() == size_of::<AtomicUsize>());
+const_assert!(size_of::<AtomicTagged<RcInner<u8>>>() == size_of::<AtomicUsize>());
 
 impl<T> AtomicWeak<T> {
     /// Constructs a new `AtomicWeak` containing a null pointer.
     #[inline(always)]
     pub fn null() -> Self {
         Self {
-            link: Atomic::new(Tagged::null()),
+            link: AtomicTagged::new(Tagged::null()),
         }
     }
 
@@ -62,7 +62,8 @@ impl<T> AtomicWeak<T> {
         forget(ptr);
         let old_ptr = self.link.swap(new_ptr, order);
         unsafe {
-            if let Some(cnt) = old_ptr.as_raw().as_mut() {
+            let cnt = old_ptr.as_raw();
+            if !cnt.is_null() {
                 RcInner::decrement_weak(cnt, Some(guard));
             }
         }
@@ -86,7 +87,7 @@ impl<T> AtomicWeak<T> {
     ///
     /// The return value is a result indicating whether the desired pointer was written.
     /// On success the pointer that was in this `AtomicWeak` is returned.
-    /// On failure the actual current value and `desired` are returned.
+    /// On failure the actual current value, `desired` and `expected` are returned.
     ///
     /// This method takes two [`Ordering`] arguments to describe the memory
     /// ordering of this operation. `success` describes the required ordering for the
@@ -117,7 +118,11 @@ impl<T> AtomicWeak<T> {
             }
             Err(current) => {
                 let current = WeakSnapshot::from_raw(current, guard);
-                Err(CompareExchangeError { desired, current })
+                Err(CompareExchangeError {
+                    desired,
+                    current,
+                    expected,
+                })
             }
         }
     }
@@ -131,7 +136,7 @@ impl<T> AtomicWeak<T> {
     /// even when comparison succeeds, which can result in more efficient code on some platforms.
     /// The return value is a result indicating whether the desired pointer was written.
     /// On success the pointer that was in this `AtomicWeak` is returned.
-    /// On failure the actual current value and `desired` are returned.
+    /// On failure the actual current value, `desired` and `expected` are returned.
     ///
     /// This method takes two [`Ordering`] arguments to describe the memory
     /// ordering of this operation. `success` describes the required ordering for the
@@ -162,7 +167,11 @@ impl<T> AtomicWeak<T> {
             }
             Err(current) => {
                 let current = WeakSnapshot::from_raw(current, guard);
-                Err(CompareExchangeError { desired, current })
+                Err(CompareExchangeError {
+                    desired,
+                    current,
+                    expected,
+                })
             }
         }
     }
@@ -177,7 +186,7 @@ impl<T> AtomicWeak<T> {
     ///
     /// The return value is a result indicating whether the desired pointer was written.
     /// On success the pointer that was in this `AtomicWeak` is returned.
-    /// On failure the actual current value and `desired` are returned.
+    /// On failure the actual current value, `desired` and `expected` are returned.
     /// For both cases, the ownership of `expected` is returned by a dedicated field.
     ///
     /// This method takes two [`Ordering`] arguments to describe the memory
@@ -210,6 +219,7 @@ impl<T> AtomicWeak<T> {
             Err(current) => Err(CompareExchangeError {
                 desired: WeakSnapshot::from_raw(desired_raw, guard),
                 current: WeakSnapshot::from_raw(current, guard),
+                expected,
             }),
         }
     }
@@ -228,7 +238,7 @@ impl<T> From<Weak<T>> for AtomicWeak<T> {
     fn from(value: Weak<T>) -> Self {
         let init_ptr = value.into_raw();
         Self {
-            link: Atomic::new(init_ptr),
+            link: AtomicTagged::new(init_ptr),
         }
     }
 }
@@ -264,8 +274,8 @@ impl<T> Drop for AtomicWeak<T> {
     fn drop(&mut self) {
         let ptr = (*self.link.get_mut()).as_raw();
         unsafe {
-            if let Some(cnt) = ptr.as_mut() {
-                RcInner::decrement_weak(cnt, None);
+            if !ptr.is_null() {
+                RcInner::decrement_weak(ptr, None);
             }
         }
     }
@@ -289,9 +299,14 @@ impl<T> Default for AtomicWeak<T> {
 ///
 /// When `T` implements [`Send`] and [`Sync`], [`Weak<T>`] also implements these traits.
 ///
+/// The weak count of an object fits in 28 bits, and the strong references collectively hold one
+/// of it. Thus an object can have at most 2<sup>28</sup> - 2 weak pointers (including the ones in
+/// [`AtomicWeak`]s) at a time, and exceeding it corrupts the reference counts.
+///
 /// The pointer must be properly aligned. Since it is aligned, a tag can be stored into the unused
 /// least significant bits of the address. For example, the tag for a pointer to a sized type `T`
 /// should be less than `(1 << align_of::<T>().trailing_zeros())`.
+#[repr(transparent)]
 pub struct Weak<T> {
     ptr: Raw<T>,
 }
@@ -374,6 +389,12 @@ impl<T> Weak<T> {
         // accessed epoch for the pointer.
         self.ptr.ptr_eq(other.ptr)
     }
+
+    /// Returns the identity of the object, ignoring the tags. See [`PtrId`].
+    #[inline]
+    pub fn id(&self) -> PtrId {
+        PtrId::from_raw(self.ptr)
+    }
 }
 
 impl<T: RcObject> Weak<T> {
@@ -391,11 +412,70 @@ impl<T: RcObject> Weak<T> {
     }
 }
 
+/// Upgrades all the given [`Weak`] pointers whose referents are still alive, and returns the
+/// obtained [`Rc`] pointers in the same order. Null and dead pointers are skipped.
+///
+/// Each pointer is upgraded one by one, so the result is not an atomic snapshot: an object may
+/// be destructed right after its preceding pointers are upgraded, and a returned object may be
+/// unlinked from its data structure by the time this function returns.
+///
+/// The current EBR backend does not use `guard`, as [`Weak::upgrade`] needs no protection. It is
+/// taken for the backends which protect each pointer individually.
+pub fn upgrade_all<T: RcObject>(weaks: &[Weak<T>], _: &Guard) -> Vec<Rc<T>> {
+    weaks
+        .iter()
+        .filter(|weak| !weak.is_null())
+        .filter_map(Weak::upgrade)
+        .collect()
+}
+
+/// Upgrades each of the given [`Weak`] pointers like [`Weak::upgrade`], and returns the results
+/// in the same order.
+///
+/// Unlike [`upgrade_all`], the pointers to the same object are grouped together, and the
+/// strong count of each distinct object is incremented by a single atomic operation. This is
+/// cheaper when many of the pointers share their referents (e.g., the observers fanning out to
+/// a few subjects). As the objects are upgraded one by one, the result is not an atomic
+/// snapshot either. Like [`upgrade_all`], it takes `guard` only for the backends which protect
+/// each pointer individually.
+pub fn upgrade_all_dedup<T: RcObject>(weaks: &[Weak<T>], _: &Guard) -> Vec<Option<Rc<T>>> {
+    let mut counts = FxHashMap::<*mut RcInner<T>, u32>::default();
+    for weak in weaks.iter().filter(|weak| !weak.is_null()) {
+        *counts.entry(weak.ptr.as_raw()).or_default() += 1;
+    }
+    let alive = counts
+        .into_iter()
+        .map(|(ptr, count)| (ptr, unsafe { &*ptr }.increment_strong_many(count)))
+        .collect::<FxHashMap<_, _>>();
+
+    weaks
+        .iter()
+        .map(|weak| {
+            if weak.is_null() || alive[&weak.ptr.as_raw()] {
+                Some(Rc::from_raw(weak.ptr))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+impl<T> DropWithGuard for Weak<T> {
+    #[inline]
+    fn drop_with(&mut self, guard: &Guard) {
+        let cnt = std::mem::replace(self, Weak::null()).into_raw().as_raw();
+        if !cnt.is_null() {
+            unsafe { RcInner::decrement_weak(cnt, Some(guard)) };
+        }
+    }
+}
+
 impl<T> Drop for Weak<T> {
     #[inline(always)]
     fn drop(&mut self) {
         unsafe {
-            if let Some(cnt) = self.ptr.as_raw().as_mut() {
+            let cnt = self.ptr.as_raw();
+            if !cnt.is_null() {
                 RcInner::decrement_weak(cnt, None);
             }
         }
@@ -469,6 +549,7 @@ impl<'g, T> WeakSnapshot<'g, T> {
         }
         Some(Snapshot {
             ptr,
+            thread: ThreadStamp::current(),
             _marker: PhantomData,
         })
     }
@@ -517,6 +598,12 @@ impl<'g, T> WeakSnapshot<'g, T> {
         // accessed epoch for the pointer.
         self.ptr.ptr_eq(other.ptr)
     }
+
+    /// Returns the identity of the object, ignoring the tags. See [`PtrId`].
+    #[inline]
+    pub fn id(self) -> PtrId {
+        PtrId::from_raw(self.ptr)
+    }
 }
 
 impl<T> Default for WeakSnapshot<'_, T> {
@@ -543,3 +630,248 @@ impl<T> Pointer for WeakSnapshot<'_, T> {
         Pointer::fmt(&self.ptr, f)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::{
+        cs, quiescent_barrier, upgrade_all, upgrade_all_dedup, AtomicRc, AtomicWeak, EdgeTaker, Rc,
+        RcObject, Snapshot, Weak, WeakSnapshot,
+    };
+
+    #[test]
+    fn null_is_null_regardless_of_tag() {
+        let weak = Weak::<u64>::null();
+        assert!(weak.is_null());
+        let weak = weak.with_tag(1);
+        assert!(weak.is_null());
+        assert_eq!(weak.tag(), 1);
+
+        let snapshot = WeakSnapshot::<u64>::null();
+        assert!(snapshot.is_null());
+        let snapshot = snapshot.with_tag(1);
+        assert!(snapshot.is_null());
+        assert_eq!(snapshot.tag(), 1);
+
+        let guard = &cs();
+        assert!(weak.snapshot(guard).is_null());
+        assert!(AtomicWeak::<u64>::null()
+            .load(std::sync::atomic::Ordering::SeqCst, guard)
+            .is_null());
+    }
+
+    #[test]
+    fn upgrade_all_skips_dead() {
+        let live = [Rc::new(()), Rc::new(())];
+        let weaks = {
+            let dead = Rc::new(());
+            let weaks = vec![
+                live[0].downgrade(),
+                dead.downgrade(),
+                Weak::null(),
+                live[1].downgrade(),
+            ];
+            drop(dead);
+            weaks
+        };
+        crate::quiescent_barrier();
+
+        let upgraded = upgrade_all(&weaks, &cs());
+        assert_eq!(upgraded.len(), 2);
+        assert!(upgraded[0].ptr_eq(&live[0]));
+        assert!(upgraded[1].ptr_eq(&live[1]));
+    }
+
+    #[test]
+    fn upgrade_all_dedup_batches_duplicates() {
+        let live = [Rc::new(()), Rc::new(())];
+        let weaks = {
+            let dead = Rc::new(());
+            let weaks = vec![
+                live[0].downgrade(),
+                dead.downgrade(),
+                live[1].downgrade(),
+                Weak::null(),
+                live[0].downgrade(),
+                dead.downgrade(),
+                live[0].downgrade(),
+            ];
+            drop(dead);
+            weaks
+        };
+        crate::quiescent_barrier();
+
+        let strong = |rc: &Rc<()>| unsafe { (*rc.ptr.as_raw()).strong() };
+        let upgraded = upgrade_all_dedup(&weaks, &cs());
+        assert_eq!(upgraded.len(), weaks.len());
+        for (i, target) in [(0, 0), (2, 1), (4, 0), (6, 0)] {
+            assert!(upgraded[i].as_ref().unwrap().ptr_eq(&live[target]));
+        }
+        assert!(upgraded[1].is_none() && upgraded[5].is_none());
+        assert!(upgraded[3].as_ref().unwrap().is_null());
+        assert_eq!((strong(&live[0]), strong(&live[1])), (4, 2));
+    }
+
+    #[test]
+    fn compare_exchange_repairs_prev_concurrently() {
+        const THREADS: usize = 8;
+        #[cfg(not(miri))]
+        const NODES: usize = 256;
+        #[cfg(miri)]
+        const NODES: usize = 16;
+
+        struct Node(usize);
+
+        unsafe impl RcObject for Node {
+            fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+        }
+
+        let nodes = (0..NODES).map(|i| Rc::new(Node(i))).collect::<Vec<_>>();
+        // Like a stale `prev` of a queue node, which each thread tries to move forward to the
+        // latest node it knows.
+        let prev = AtomicWeak::from(&nodes[0]);
+        std::thread::scope(|s| {
+            for t in 0..THREADS {
+                let (nodes, prev) = (&nodes, &prev);
+                s.spawn(move || {
+                    for node in nodes.iter().skip(t) {
+                        let idx = node.as_ref().unwrap().0;
+                        let guard = cs();
+                        let mut curr = prev.load(Ordering::Acquire, &guard);
+                        let mut desired = node.downgrade();
+                        loop {
+                            let curr_idx = curr.upgrade().and_then(Snapshot::as_ref).unwrap().0;
+                            if curr_idx >= idx {
+                                break;
+                            }
+                            match prev.compare_exchange(
+                                curr,
+                                desired,
+                                Ordering::AcqRel,
+                                Ordering::Acquire,
+                                &guard,
+                            ) {
+                                Ok(_) => break,
+                                Err(e) => {
+                                    curr = e.current;
+                                    desired = e.desired;
+                                }
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        let guard = cs();
+        let last = prev.load(Ordering::Acquire, &guard).upgrade().unwrap();
+        assert!(last.ptr_eq(nodes[NODES - 1].snapshot(&guard)));
+        drop(guard);
+
+        // Every weak count taken for the failed and overwritten repairs is released.
+        drop(prev);
+        for node in &nodes {
+            assert_eq!(unsafe { node.ptr.deref() }.weak(), 1);
+        }
+    }
+
+    #[test]
+    fn weak_outlives_payload() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+        struct Payload;
+
+        unsafe impl RcObject for Payload {
+            fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+        }
+
+        impl Drop for Payload {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let rc = Rc::new(Payload);
+        assert_eq!(rc.weak_count(), 0);
+        let weak = rc.downgrade();
+        let atomic = AtomicWeak::from(&rc);
+        assert_eq!(rc.weak_count(), 2);
+        drop(atomic);
+        assert_eq!(rc.weak_count(), 1);
+        assert_eq!(Rc::<Payload>::null().weak_count(), 0);
+
+        // The last strong reference drops the payload, but the allocation is retained.
+        drop(rc);
+        quiescent_barrier();
+        assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+        assert_eq!(unsafe { weak.ptr.deref() }.weak(), 1);
+
+        // The payload is never revived.
+        assert!(weak.upgrade().is_none());
+        assert!(weak.snapshot(&cs()).upgrade().is_none());
+        assert!(weak.upgrade().is_none());
+
+        // The last weak reference frees the allocation (checked by Miri).
+        drop(weak);
+        quiescent_barrier();
+        assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn weak_to_successor_is_not_revived() {
+        const LENGTH: usize = 64;
+        #[cfg(not(miri))]
+        const ROUNDS: usize = 1000;
+        #[cfg(miri)]
+        const ROUNDS: usize = 10;
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+        struct Node {
+            next: AtomicRc<Node>,
+        }
+
+        unsafe impl RcObject for Node {
+            fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+                out.take(&mut self.next);
+            }
+        }
+
+        impl Drop for Node {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let mut weaks = Vec::new();
+        let head = (0..LENGTH).fold(Rc::null(), |next, _| {
+            let node = Rc::new(Node {
+                next: AtomicRc::from(next),
+            });
+            weaks.push(node.downgrade());
+            node
+        });
+
+        // The successors are destructed by IRD, while other threads keep upgrading them. An
+        // upgrade revives a node until its next destruction attempt, so the upgrades are
+        // bounded: otherwise, they could keep reviving some nodes forever.
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..ROUNDS {
+                        for weak in &weaks {
+                            drop(weak.upgrade());
+                        }
+                    }
+                });
+            }
+            drop(head);
+            quiescent_barrier();
+        });
+        while DROPS.load(Ordering::Relaxed) < LENGTH {
+            quiescent_barrier();
+        }
+        assert_eq!(DROPS.load(Ordering::Relaxed), LENGTH);
+        assert!(weaks.iter().all(|weak| weak.upgrade().is_none()));
+    }
+}
@@ -0,0 +1,159 @@
+//! A map of weakly held values, which never keeps them alive.
+
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::hash::Hash;
+use std::mem::replace;
+use std::sync::Mutex;
+
+use crate::{Rc, RcObject, Weak};
+
+/// A thread-safe map which holds its values only with [`Weak`] pointers.
+///
+/// The cache never keeps the values alive by itself: a value is destructed once its last
+/// [`Rc`] outside of the cache is dropped, and then [`WeakCache::get`] returns `None` and
+/// prunes the dead entry. Note that the destruction is deferred by EBR, so a value may still
+/// be obtained for a while after its last `Rc` is dropped.
+///
+/// # Examples
+///
+/// ```
+/// use circ::{EdgeTaker, Rc, RcObject, WeakCache};
+///
+/// struct Texture(Vec<u8>);
+///
+/// unsafe impl RcObject for Texture {
+///     fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+/// }
+///
+/// let cache = WeakCache::new();
+/// let texture = Rc::new(Texture(vec![0; 64]));
+/// cache.insert("grass", &texture);
+/// assert!(cache.get(&"grass").unwrap().ptr_eq(&texture));
+/// ```
+pub struct WeakCache<K, V> {
+    map: Mutex<HashMap<K, Weak<V>>>,
+}
+
+impl<K: Eq + Hash, V: RcObject> WeakCache<K, V> {
+    /// Creates a new empty cache.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            map: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Inserts a weak pointer to `value` with `key`, returning the previous value for the key
+    /// if it is still alive.
+    pub fn insert(&self, key: K, value: &Rc<V>) -> Option<Rc<V>> {
+        let prev = self.map.lock().unwrap().insert(key, value.downgrade());
+        prev.and_then(|weak| Self::upgrade(&weak))
+    }
+
+    /// Returns the value for `key` if it is still alive. If the value has been destructed, the
+    /// entry is removed and `None` is returned.
+    pub fn get(&self, key: &K) -> Option<Rc<V>> {
+        let mut map = self.map.lock().unwrap();
+        let rc = Self::upgrade(map.get(key)?);
+        let dead = if rc.is_none() { map.remove(key) } else { None };
+        // Release the pointer after unlocking, as it may run destructors, which may use this
+        // cache again.
+        drop(map);
+        drop(dead);
+        rc
+    }
+
+    /// Removes the entry for `key`, returning its value if it is still alive.
+    pub fn remove(&self, key: &K) -> Option<Rc<V>> {
+        let weak = self.map.lock().unwrap().remove(key)?;
+        Self::upgrade(&weak)
+    }
+
+    /// Removes all the entries whose values have been destructed.
+    pub fn prune(&self) {
+        // Both the dead `Weak`s and the upgraded `Rc`s are released after unlocking, as in
+        // `WeakCache::get`.
+        let mut dead = Vec::new();
+        let mut alive = Vec::new();
+        self.map
+            .lock()
+            .unwrap()
+            .retain(|_, weak| match Self::upgrade(weak) {
+                Some(rc) => {
+                    alive.push(rc);
+                    true
+                }
+                None => {
+                    dead.push(replace(weak, Weak::null()));
+                    false
+                }
+            });
+        drop(dead);
+        drop(alive);
+    }
+
+    /// Returns the number of the entries, including the ones whose values have been destructed
+    /// but not pruned yet.
+    pub fn len(&self) -> usize {
+        self.map.lock().unwrap().len()
+    }
+
+    /// Returns `true` if there is no entry.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    fn upgrade(weak: &Weak<V>) -> Option<Rc<V>> {
+        weak.upgrade().filter(|rc| !rc.is_null())
+    }
+}
+
+impl<K: Eq + Hash, V: RcObject> Default for WeakCache<K, V> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> Debug for WeakCache<K, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WeakCache")
+            .field("len", &self.map.lock().unwrap().len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WeakCache;
+    use crate::{EdgeTaker, Rc, RcObject};
+
+    struct Value(usize);
+
+    unsafe impl RcObject for Value {
+        fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+    }
+
+    #[test]
+    fn get_prunes_dead_entries() {
+        let cache = WeakCache::new();
+        let alive = Rc::new(Value(1));
+        let dead = Rc::new(Value(2));
+        assert!(cache.insert(1, &alive).is_none());
+        assert!(cache.insert(2, &dead).is_none());
+        assert_eq!(cache.get(&2).unwrap().as_ref().unwrap().0, 2);
+
+        drop(dead);
+        // The destruction is deferred.
+        crate::quiescent_barrier();
+        assert!(cache.get(&2).is_none());
+        assert_eq!(cache.len(), 1);
+
+        assert!(cache.get(&1).unwrap().ptr_eq(&alive));
+        assert!(cache.get(&3).is_none());
+        assert!(cache.remove(&1).unwrap().ptr_eq(&alive));
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,34 @@
+use circ::{
+    allocated_bytes, quiescent_barrier, rc_inner_size, set_alloc_limit, EdgeTaker, Rc, RcObject,
+};
+
+struct Leaf {
+    _value: usize,
+}
+
+unsafe impl RcObject for Leaf {
+    fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+}
+
+#[test]
+fn try_new_respects_limit() {
+    const CAPACITY: usize = 10;
+
+    let base = allocated_bytes();
+    set_alloc_limit(base + CAPACITY * rc_inner_size::<Leaf>());
+
+    let leaves = (0..CAPACITY)
+        .map(|i| Rc::try_new(Leaf { _value: i }).unwrap())
+        .collect::<Vec<_>>();
+    assert!(Rc::try_new(Leaf { _value: CAPACITY }).is_none());
+    // The infallible constructor ignores the limit.
+    drop(Rc::new(Leaf { _value: CAPACITY }));
+
+    // The dropped objects are counted until they are reclaimed.
+    drop(leaves);
+    quiescent_barrier();
+    assert_eq!(allocated_bytes(), base);
+    assert!(Rc::try_new(Leaf { _value: 0 }).is_some());
+
+    set_alloc_limit(usize::MAX);
+}
@@ -0,0 +1,59 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use circ::map::guarded_remove;
+use circ::{cs, is_pinned, quiescent_barrier, EdgeTaker, Rc, RcObject};
+use dashmap::DashMap;
+
+static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+struct Value {
+    key: usize,
+}
+
+unsafe impl RcObject for Value {
+    fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+}
+
+impl Drop for Value {
+    fn drop(&mut self) {
+        DROPS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn removed_values_are_reclaimed() {
+    const COUNT: usize = 1000;
+
+    let map = DashMap::new();
+    let clones = (0..COUNT)
+        .map(|key| {
+            let value = Rc::new(Value { key });
+            map.insert(key, value.clone());
+            value
+        })
+        .collect::<Vec<_>>();
+
+    // Removing a shared value does not reclaim it.
+    assert!(!is_pinned());
+    for key in 0..COUNT / 2 {
+        drop(map.remove(&key));
+    }
+    drop(clones);
+    quiescent_barrier();
+    assert_eq!(DROPS.load(Ordering::Relaxed), COUNT / 2);
+
+    // Releasing the last references, in the shards and under a single guard.
+    for key in COUNT / 2..COUNT * 3 / 4 {
+        drop(map.remove(&key));
+    }
+    {
+        let guard = &cs();
+        for key in COUNT * 3 / 4..COUNT {
+            let (_, value) = guarded_remove(&map, &key, guard).unwrap();
+            assert_eq!(value.as_ref().unwrap().key, key);
+        }
+    }
+    assert!(map.is_empty());
+    quiescent_barrier();
+    assert_eq!(DROPS.load(Ordering::Relaxed), COUNT);
+}
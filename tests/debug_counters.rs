@@ -0,0 +1,64 @@
+use std::sync::atomic::Ordering;
+
+use circ::debug::cas_stats;
+use circ::{cs, AtomicRc, EdgeTaker, Rc, RcObject};
+
+struct Counter {
+    value: usize,
+}
+
+unsafe impl RcObject for Counter {
+    fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+}
+
+#[test]
+fn cas_stats_under_contention() {
+    const THREADS: usize = 8;
+    const INCREMENTS: usize = 1000;
+
+    let before = cas_stats();
+    let counter = &AtomicRc::new(Counter { value: 0 });
+    std::thread::scope(|s| {
+        for _ in 0..THREADS {
+            s.spawn(move || {
+                for _ in 0..INCREMENTS {
+                    let guard = &cs();
+                    let mut curr = counter.load(Ordering::Acquire, guard);
+                    // Let other threads update the counter between the load and the CAS.
+                    std::thread::yield_now();
+                    loop {
+                        let next = Rc::new(Counter {
+                            value: curr.as_ref().unwrap().value + 1,
+                        });
+                        match counter.compare_exchange(
+                            curr,
+                            next,
+                            Ordering::AcqRel,
+                            Ordering::Acquire,
+                            guard,
+                        ) {
+                            Ok(_) => break,
+                            Err(e) => curr = e.current,
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    let guard = &cs();
+    let value = counter
+        .load(Ordering::Acquire, guard)
+        .as_ref()
+        .unwrap()
+        .value;
+    assert_eq!(value, THREADS * INCREMENTS);
+
+    let after = cas_stats();
+    let attempts = after.attempts - before.attempts;
+    let failures = after.failures - before.failures;
+    let epoch_retries = after.epoch_retries - before.epoch_retries;
+    assert!(failures > 0);
+    assert!(attempts >= failures + epoch_retries);
+    assert!(attempts >= (THREADS * INCREMENTS) as u64);
+}
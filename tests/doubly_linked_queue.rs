@@ -54,6 +54,12 @@ pub struct DLQueue<T: Sync + Send> {
     tail: CachePadded<AtomicRc<Node<T>>>,
 }
 
+impl<T: Sync + Send> Default for DLQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T: Sync + Send> DLQueue<T> {
     #[inline]
     pub fn new() -> Self {
@@ -1,7 +1,5 @@
 use bitflags::bitflags;
-use circ::{
-    AtomicRc, AtomicWeak, CompareExchangeError, EdgeTaker, Guard, OwnRc, Rc, RcObject, Snapshot,
-};
+use circ::{AtomicRc, AtomicWeak, CompareExchangeError, EdgeTaker, Guard, Rc, RcObject, Snapshot};
 use std::sync::atomic::Ordering;
 
 bitflags! {
@@ -384,7 +382,7 @@ where
             match p_ref.update.compare_exchange(
                 pupdate_sh,
                 new_op.counted(),
-                Ordering::Release,
+                Ordering::AcqRel,
                 Ordering::Acquire,
                 guard,
             ) {
@@ -480,6 +478,7 @@ where
     }
 
     #[inline]
+    #[allow(clippy::type_complexity)]
     fn cas_child<'g>(
         &'g self,
         parent: Snapshot<'g, Node<K, V>>,
@@ -500,7 +499,7 @@ where
         node_to_cas.compare_exchange(
             old,
             new.counted(),
-            Ordering::Release,
+            Ordering::AcqRel,
             Ordering::Acquire,
             guard,
         )
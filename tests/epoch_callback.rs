@@ -0,0 +1,98 @@
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use circ::{cs, quiescent_barrier, register_epoch_callback, EdgeTaker, Rc, RcObject};
+
+thread_local! {
+    static OBSERVED: RefCell<Vec<u32>> = const { RefCell::new(Vec::new()) };
+}
+
+fn observed() -> Vec<u32> {
+    OBSERVED.with_borrow(Vec::clone)
+}
+
+#[test]
+fn callback_fires_across_repins() {
+    // A callback may be called while a thread exits, after its thread-locals are destroyed.
+    register_epoch_callback(Box::new(|epoch| {
+        let _ = OBSERVED.try_with(|observed| observed.borrow_mut().push(epoch));
+    }));
+
+    let mut guard = cs();
+    for _ in 0..3 {
+        let before = observed().len();
+        // The barrier advances the global epoch while this thread is unpinned, and the thread
+        // observes it at the latest when it is repinned.
+        guard.reactivate_after(quiescent_barrier);
+        assert!(observed().len() > before);
+    }
+    // Each observation reports a new epoch.
+    assert!(observed().windows(2).all(|pair| pair[0] != pair[1]));
+
+    // Nothing is reported without an advance.
+    let before = observed();
+    guard.reactivate();
+    drop(guard);
+    drop(cs());
+    assert_eq!(observed(), before);
+
+    // The first pin of a thread reports nothing, as it has no previous epoch to compare.
+    std::thread::spawn(|| {
+        drop(cs());
+        assert!(observed().is_empty());
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn panicking_callback_is_discarded() {
+    use std::cell::Cell;
+
+    thread_local! {
+        static PANICKING: Cell<bool> = const { Cell::new(false) };
+        static CALLED_AFTER: Cell<usize> = const { Cell::new(0) };
+    }
+
+    register_epoch_callback(Box::new(|_| {
+        if PANICKING.get() {
+            panic!("epoch callback");
+        }
+    }));
+    register_epoch_callback(Box::new(|_| CALLED_AFTER.set(CALLED_AFTER.get() + 1)));
+
+    drop(cs());
+    // Advance the epoch on another thread, so that this one observes it on its next pin.
+    std::thread::spawn(quiescent_barrier).join().unwrap();
+
+    // The pin returns normally, after calling the other callbacks as well.
+    PANICKING.set(true);
+    drop(cs());
+    PANICKING.set(false);
+    assert_eq!(CALLED_AFTER.get(), 1);
+
+    // The internal pin which releases the last `Rc` does not unwind either, so the object is
+    // still destructed.
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    struct Counted;
+
+    unsafe impl RcObject for Counted {
+        fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+    }
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let rc = Rc::new(Counted);
+    std::thread::spawn(quiescent_barrier).join().unwrap();
+    PANICKING.set(true);
+    drop(rc);
+    PANICKING.set(false);
+    assert_eq!(CALLED_AFTER.get(), 2);
+    quiescent_barrier();
+    assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+}
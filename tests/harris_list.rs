@@ -256,10 +256,17 @@ fn smoke() {
     use crossbeam_utils::thread;
     use rand::prelude::*;
 
+    #[cfg(miri)]
+    const THREADS: i32 = 4;
+    #[cfg(not(miri))]
     const THREADS: i32 = 30;
+    #[cfg(miri)]
+    const ELEMENTS_PER_THREADS: i32 = 20;
+    #[cfg(not(miri))]
     const ELEMENTS_PER_THREADS: i32 = 1000;
 
-    let map = &ListMap::new();
+    let list = ListMap::new();
+    let map = &list;
 
     thread::scope(|s| {
         for t in 0..THREADS {
@@ -309,4 +316,12 @@ fn smoke() {
         }
     })
     .unwrap();
+
+    // Every node must be reclaimed once the list is dropped.
+    #[cfg(feature = "test-utils")]
+    {
+        drop(list);
+        circ::quiescent_barrier();
+        assert_eq!(circ::test::live_object_count(), 0);
+    }
 }
@@ -0,0 +1,61 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use circ::{cs, quiescent_barrier, AtomicRc, EdgeTaker, Rc, RcObject};
+
+static LEAF_DROPS: AtomicUsize = AtomicUsize::new(0);
+
+struct Leaf;
+
+unsafe impl RcObject for Leaf {
+    fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+}
+
+impl Drop for Leaf {
+    fn drop(&mut self) {
+        LEAF_DROPS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+struct Bomb {
+    leaf: AtomicRc<Leaf>,
+}
+
+unsafe impl RcObject for Bomb {
+    fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+        out.take(&mut self.leaf);
+    }
+}
+
+impl Drop for Bomb {
+    fn drop(&mut self) {
+        panic!("boom");
+    }
+}
+
+#[test]
+fn panicking_destructor_does_not_double_release() {
+    let leaf = Rc::new(Leaf);
+    let slot = AtomicRc::new(Bomb {
+        leaf: AtomicRc::from(leaf.clone()),
+    });
+
+    // The bomb is destructed during the reclamation, not in `store`.
+    slot.store(Rc::null(), Ordering::Relaxed, &cs());
+    let res = catch_unwind(AssertUnwindSafe(quiescent_barrier));
+    assert!(res.is_err());
+
+    // The edge taken from the bomb is leaked rather than released, so the count of `leaf`
+    // stays intact: releasing `leaf` must neither destruct it nor trip the double free check
+    // (in debug builds).
+    quiescent_barrier();
+    assert!(!leaf.is_unique());
+    drop(leaf);
+    quiescent_barrier();
+    assert_eq!(LEAF_DROPS.load(Ordering::Relaxed), 0);
+
+    // The reclamation keeps working after the panic.
+    drop(Rc::new(Leaf));
+    quiescent_barrier();
+    assert_eq!(LEAF_DROPS.load(Ordering::Relaxed), 1);
+}
@@ -0,0 +1,42 @@
+//! `set_pin_interval` changes a process-wide parameter, so this test runs in its own binary.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use circ::ebr::set_pin_interval;
+use circ::{cs, EdgeTaker, Rc, RcObject};
+
+static DESTROYS: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+unsafe impl RcObject for Counted {
+    fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+}
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DESTROYS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn pin_interval() {
+    const PINS: usize = 100;
+
+    drop(Rc::new(Counted));
+    cs().flush();
+
+    // Pins do not trigger enough collections with a large interval.
+    set_pin_interval(PINS * 10);
+    for _ in 0..PINS {
+        drop(cs());
+    }
+    assert_eq!(DESTROYS.load(Ordering::Relaxed), 0);
+
+    set_pin_interval(1);
+    for _ in 0..PINS {
+        drop(cs());
+    }
+    set_pin_interval(0);
+    assert_eq!(DESTROYS.load(Ordering::Relaxed), 1);
+}
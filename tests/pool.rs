@@ -0,0 +1,31 @@
+use circ::pool::RcPool;
+use circ::{quiescent_barrier, EdgeTaker, RcObject};
+
+struct Node {
+    _item: usize,
+}
+
+unsafe impl RcObject for Node {
+    fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+}
+
+#[test]
+fn reclaimed_blocks_are_reused() {
+    const COUNT: usize = 100;
+
+    let pool = RcPool::new();
+    let nodes = (0..COUNT)
+        .map(|i| pool.alloc(Node { _item: i }))
+        .collect::<Vec<_>>();
+    let addrs = nodes.iter().map(|n| n.id()).collect::<Vec<_>>();
+    assert_eq!(pool.cached(), 0);
+
+    // This is the only thread of the test, so the blocks are returned to its pool.
+    drop(nodes);
+    quiescent_barrier();
+    assert_eq!(pool.cached(), COUNT);
+
+    let node = pool.alloc(Node { _item: COUNT });
+    assert!(addrs.contains(&node.id()));
+    assert_eq!(pool.cached(), COUNT - 1);
+}
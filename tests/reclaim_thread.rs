@@ -0,0 +1,204 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use circ::pool::RcPool;
+use circ::{cs, quiescent_barrier, set_reclaim_thread, AtomicRc, EdgeTaker, Rc, RcObject};
+
+/// The reclaimer thread is global, so the tests must not enable and disable it concurrently.
+static SERIAL: Mutex<()> = Mutex::new(());
+
+fn on_reclaimer() -> bool {
+    thread::current().name() == Some("circ-reclaim")
+}
+
+/// Drives the collections of the current thread until `done` holds.
+fn collect_until(done: impl Fn() -> bool) {
+    for _ in 0..10_000 {
+        if done() {
+            return;
+        }
+        cs().flush();
+        thread::sleep(Duration::from_millis(1));
+    }
+    panic!("the objects are not reclaimed in time");
+}
+
+static NODE_DROPS: AtomicUsize = AtomicUsize::new(0);
+static NODE_DROPS_ON_RECLAIMER: AtomicUsize = AtomicUsize::new(0);
+
+struct Node {
+    next: AtomicRc<Node>,
+}
+
+unsafe impl RcObject for Node {
+    fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+        out.take(&mut self.next);
+    }
+
+    const DEFER_TO_BACKGROUND: bool = true;
+}
+
+impl Drop for Node {
+    fn drop(&mut self) {
+        NODE_DROPS.fetch_add(1, Ordering::Relaxed);
+        if on_reclaimer() {
+            NODE_DROPS_ON_RECLAIMER.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+#[test]
+fn reclaim_on_background_thread() {
+    #[cfg(not(miri))]
+    const LENGTH: usize = 100_000;
+    #[cfg(miri)]
+    const LENGTH: usize = 100;
+
+    let _serial = SERIAL.lock().unwrap();
+    set_reclaim_thread(true);
+
+    let head = (0..LENGTH).fold(Rc::null(), |next, _| {
+        Rc::new(Node {
+            next: AtomicRc::from(next),
+        })
+    });
+    let weak = head.downgrade();
+    drop(head);
+
+    // The collections of this thread only hand the expired nodes to the reclaimer.
+    collect_until(|| NODE_DROPS.load(Ordering::Relaxed) == LENGTH);
+    assert_eq!(NODE_DROPS_ON_RECLAIMER.load(Ordering::Relaxed), LENGTH);
+
+    set_reclaim_thread(false);
+    quiescent_barrier();
+    assert!(weak.upgrade().is_none());
+    drop(weak);
+    quiescent_barrier();
+    assert_eq!(NODE_DROPS.load(Ordering::Relaxed), LENGTH);
+}
+
+static HEAVY_DROPS: AtomicUsize = AtomicUsize::new(0);
+static LIGHT_DROPS: AtomicUsize = AtomicUsize::new(0);
+
+struct Heavy {
+    _buffer: Vec<u8>,
+}
+
+unsafe impl RcObject for Heavy {
+    fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+
+    const DEFER_TO_BACKGROUND: bool = true;
+}
+
+impl Drop for Heavy {
+    fn drop(&mut self) {
+        assert!(on_reclaimer());
+        HEAVY_DROPS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+struct Light;
+
+unsafe impl RcObject for Light {
+    fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+}
+
+impl Drop for Light {
+    fn drop(&mut self) {
+        assert!(!on_reclaimer());
+        LIGHT_DROPS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn reclaim_per_type() {
+    const COUNT: usize = 100;
+
+    let _serial = SERIAL.lock().unwrap();
+    set_reclaim_thread(true);
+
+    for _ in 0..COUNT {
+        drop(Rc::new(Heavy {
+            _buffer: vec![0; 1 << 16],
+        }));
+        drop(Rc::new(Light));
+    }
+    collect_until(|| {
+        HEAVY_DROPS.load(Ordering::Relaxed) == COUNT && LIGHT_DROPS.load(Ordering::Relaxed) == COUNT
+    });
+
+    set_reclaim_thread(false);
+}
+
+static RESTARTED_DROPS: AtomicUsize = AtomicUsize::new(0);
+
+struct Restarted;
+
+unsafe impl RcObject for Restarted {
+    fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+
+    const DEFER_TO_BACKGROUND: bool = true;
+}
+
+impl Drop for Restarted {
+    fn drop(&mut self) {
+        assert!(on_reclaimer());
+        RESTARTED_DROPS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn reclaim_after_restart() {
+    const COUNT: usize = 100;
+
+    let _serial = SERIAL.lock().unwrap();
+    for round in 1..=3 {
+        set_reclaim_thread(true);
+        for _ in 0..COUNT {
+            drop(Rc::new(Restarted));
+        }
+        // The sender cached by this thread in the previous round is stale, and the objects
+        // must reach the new reclaimer instead.
+        collect_until(|| RESTARTED_DROPS.load(Ordering::Relaxed) == round * COUNT);
+        set_reclaim_thread(false);
+    }
+}
+
+static POOLED_DROPS: AtomicUsize = AtomicUsize::new(0);
+static CACHED_ON_RECLAIMER: AtomicUsize = AtomicUsize::new(0);
+
+struct Pooled;
+
+unsafe impl RcObject for Pooled {
+    fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+
+    const DEFER_TO_BACKGROUND: bool = true;
+}
+
+impl Drop for Pooled {
+    fn drop(&mut self) {
+        assert!(on_reclaimer());
+        // The blocks of the objects destructed before this one would be cached by now.
+        CACHED_ON_RECLAIMER.fetch_max(RcPool::<Pooled>::new().cached(), Ordering::Relaxed);
+        POOLED_DROPS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn reclaimer_does_not_pool_blocks() {
+    const COUNT: usize = 100;
+
+    let _serial = SERIAL.lock().unwrap();
+    set_reclaim_thread(true);
+
+    let pool = RcPool::new();
+    for _ in 0..COUNT {
+        drop(pool.alloc(Pooled));
+    }
+    collect_until(|| POOLED_DROPS.load(Ordering::Relaxed) == COUNT);
+    assert_eq!(CACHED_ON_RECLAIMER.load(Ordering::Relaxed), 0);
+
+    set_reclaim_thread(false);
+}
@@ -0,0 +1,93 @@
+use std::sync::atomic::Ordering;
+
+use circ::serde::{GraphDeserializer, GraphSerializer};
+use circ::{cs, AtomicRc, EdgeTaker, Rc, RcObject};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+struct Node {
+    key: u32,
+    children: Vec<AtomicRc<Node>>,
+}
+
+unsafe impl RcObject for Node {
+    fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+        out.take_all(self.children.iter_mut());
+    }
+}
+
+impl Serialize for Node {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.key, &self.children).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Node {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (key, children) = Deserialize::deserialize(deserializer)?;
+        Ok(Self { key, children })
+    }
+}
+
+fn node(key: u32, children: Vec<Rc<Node>>) -> Rc<Node> {
+    Rc::new(Node {
+        key,
+        children: children.into_iter().map(AtomicRc::from).collect(),
+    })
+}
+
+fn child(parent: &Rc<Node>, index: usize) -> Rc<Node> {
+    parent.as_ref().unwrap().children[index].load_owned(Ordering::Acquire, &cs())
+}
+
+#[test]
+fn diamond_round_trip() {
+    //    0
+    //   / \
+    //  1   2
+    //   \ /
+    //    3
+    let bottom = node(3, vec![]);
+    let top = node(
+        0,
+        vec![node(1, vec![bottom.clone()]), node(2, vec![bottom])],
+    );
+
+    let json = serde_json::to_string(&top).unwrap();
+    // The bottom is written only once.
+    assert_eq!(json, "[0,[0,[[1,[1,[[2,[3,[]]]]]],[3,[2,[[2,null]]]]]]]");
+
+    let top = serde_json::from_str::<Rc<Node>>(&json).unwrap();
+    let (left, right) = (child(&top, 0), child(&top, 1));
+    assert_eq!(left.as_ref().unwrap().key, 1);
+    assert_eq!(right.as_ref().unwrap().key, 2);
+    let (left_bottom, right_bottom) = (child(&left, 0), child(&right, 0));
+    assert!(left_bottom.ptr_eq(&right_bottom));
+    assert_eq!(left_bottom.as_ref().unwrap().key, 3);
+}
+
+#[test]
+fn sharing_across_roots() {
+    let shared = node(1, vec![]);
+    let roots = vec![node(0, vec![shared.clone()]), shared, Rc::null()];
+
+    // Without `GraphSerializer`, each root has its own table of ids.
+    let json = serde_json::to_string(&roots).unwrap();
+    let copied = serde_json::from_str::<Vec<Rc<Node>>>(&json).unwrap();
+    assert!(!child(&copied[0], 0).ptr_eq(&copied[1]));
+
+    let json = serde_json::to_string(&GraphSerializer(&roots)).unwrap();
+    let shared = serde_json::from_str::<GraphDeserializer<Vec<Rc<Node>>>>(&json)
+        .unwrap()
+        .into_inner();
+    assert!(child(&shared[0], 0).ptr_eq(&shared[1]));
+    assert!(shared[2].is_null());
+}
+
+#[test]
+fn undefined_reference() {
+    assert!(serde_json::from_str::<Rc<Node>>("[0,null]").is_err());
+    assert!(
+        serde_json::from_str::<GraphDeserializer<Vec<Rc<Node>>>>("[[0,[1,[]]],[0,[1,[]]]]")
+            .is_err()
+    );
+}
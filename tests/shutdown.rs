@@ -0,0 +1,46 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use circ::{shutdown, AtomicRc, EdgeTaker, Rc, RcObject};
+
+static CLOSED: AtomicUsize = AtomicUsize::new(0);
+
+/// A node owning an external resource, which is released by its destructor.
+struct Resource {
+    next: AtomicRc<Resource>,
+}
+
+unsafe impl RcObject for Resource {
+    fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+        out.take(&mut self.next);
+    }
+}
+
+impl Drop for Resource {
+    fn drop(&mut self) {
+        CLOSED.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+fn chain(length: usize) -> Rc<Resource> {
+    (0..length).fold(Rc::null(), |next, _| {
+        Rc::new(Resource {
+            next: AtomicRc::from(next),
+        })
+    })
+}
+
+#[test]
+fn shutdown_runs_pending_destructors() {
+    const THREADS: usize = 4;
+    const LENGTH: usize = 5000;
+
+    // The exiting threads hand their pending tasks over to the global queue.
+    for _ in 0..THREADS {
+        thread::spawn(|| drop(chain(LENGTH))).join().unwrap();
+    }
+    drop(chain(LENGTH));
+
+    shutdown();
+    assert_eq!(CLOSED.load(Ordering::Relaxed), (THREADS + 1) * LENGTH);
+}
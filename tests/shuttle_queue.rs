@@ -0,0 +1,111 @@
+//! Randomized concurrency tests of [`circ::collections::Queue`] with
+//! [Shuttle](https://github.com/awslabs/shuttle).
+//!
+//! With the `shuttle` feature, the queue calls a hook before each access to a shared location,
+//! at which Shuttle switches to another thread. Shuttle then explores random interleavings of
+//! these accesses, including the ones that exercise the "help the previous enqueue" step, which
+//! the smoke test only hits by luck.
+//!
+//! The test models less than it may seem to:
+//!
+//! * Shuttle explores schedules under sequential consistency, so it does not find bugs that only
+//!   appear on weak memory (e.g., a too weak `Ordering`). Use Miri for those.
+//! * Shuttle runs all of its threads on a single OS thread, so they share one thread-local EBR
+//!   participant, and a guard of one Shuttle thread pins all of them. The deferred nodes are
+//!   reclaimed only when no Shuttle thread is in the middle of an operation, so the races
+//!   between the reclamation and the operations are not exercised.
+//! * Only the accesses in `Queue` are scheduling points, not the ones inside CIRC itself (e.g.,
+//!   the reference counts).
+//!
+//! Run with `cargo test --features shuttle --test shuttle_queue`. The number of iterations can
+//! be configured with the `SHUTTLE_ITERATIONS` environment variable.
+
+use std::sync::Arc;
+
+use circ::collections::{set_sched_hook, Queue};
+use circ::cs;
+use shuttle::thread;
+
+const PRODUCERS: usize = 2;
+const ITEMS_PER_PRODUCER: usize = 3;
+
+fn iterations() -> usize {
+    std::env::var("SHUTTLE_ITERATIONS")
+        .ok()
+        .and_then(|iters| iters.parse().ok())
+        .unwrap_or(1000)
+}
+
+/// Runs producers and consumers concurrently, and checks that every item is dequeued exactly
+/// once, in the order of each producer.
+fn enqueue_dequeue(faulty: bool) {
+    set_sched_hook(thread::yield_now);
+    let queue = Arc::new(if faulty {
+        Queue::with_injected_bug()
+    } else {
+        Queue::new()
+    });
+
+    let producers = (0..PRODUCERS)
+        .map(|p| {
+            let queue = queue.clone();
+            thread::spawn(move || {
+                for i in 0..ITEMS_PER_PRODUCER {
+                    queue.push((p, i), &cs());
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    let consumers = (0..2)
+        .map(|_| {
+            let queue = queue.clone();
+            thread::spawn(move || {
+                let mut found = Vec::new();
+                for _ in 0..ITEMS_PER_PRODUCER {
+                    if let Some(item) = queue.pop(&cs()) {
+                        found.push(item);
+                    }
+                }
+                found
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for producer in producers {
+        producer.join().unwrap();
+    }
+    let mut found = Vec::new();
+    let mut in_order = true;
+    for consumer in consumers {
+        let items = consumer.join().unwrap();
+        // Items from the same producer are dequeued in their enqueued order.
+        for p in 0..PRODUCERS {
+            let seq = items.iter().filter(|(q, _)| *q == p).map(|(_, i)| *i);
+            in_order &= seq.clone().zip(seq.skip(1)).all(|(a, b)| a < b);
+        }
+        found.extend(items);
+    }
+    while let Some(item) = queue.pop(&cs()) {
+        found.push(item);
+    }
+
+    found.sort();
+    let expected = (0..PRODUCERS)
+        .flat_map(|p| (0..ITEMS_PER_PRODUCER).map(move |i| (p, i)))
+        .collect::<Vec<_>>();
+    assert!(
+        in_order && found == expected,
+        "the queue lost, duplicated or reordered items: {found:?}"
+    );
+}
+
+#[test]
+fn enqueue_dequeue_random() {
+    shuttle::check_random(|| enqueue_dequeue(false), iterations());
+}
+
+#[test]
+#[should_panic(expected = "the queue lost, duplicated or reordered items")]
+fn injected_bug_is_detected() {
+    shuttle::check_random(|| enqueue_dequeue(true), iterations());
+}
@@ -0,0 +1,32 @@
+use circ::{quiescent_barrier, stats, AtomicRc, EdgeTaker, Rc, RcObject};
+
+// The statistics are global, so this is the only test in its process.
+#[test]
+fn disposed_catches_up_with_allocated() {
+    const COUNT: usize = 1000;
+
+    struct Node {
+        next: AtomicRc<Node>,
+    }
+
+    unsafe impl RcObject for Node {
+        fn pop_edges(&mut self, out: &mut EdgeTaker<'_>) {
+            out.take(&mut self.next);
+        }
+    }
+
+    let chain = (0..COUNT).fold(Rc::null(), |next, _| {
+        Rc::new(Node {
+            next: AtomicRc::from(next),
+        })
+    });
+    assert_eq!(stats().allocated, COUNT);
+    assert_eq!(stats().live(), COUNT);
+
+    drop(chain);
+    quiescent_barrier();
+    let after = stats();
+    assert_eq!(after.disposed, COUNT);
+    assert_eq!(after.allocated - after.disposed, 0);
+    assert!(after.peak_deferred > 0);
+}
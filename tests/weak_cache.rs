@@ -0,0 +1,73 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+use circ::ebr::set_pin_interval;
+use circ::{cs, quiescent_barrier, EdgeTaker, Rc, RcObject, WeakCache};
+
+static CACHE: OnceLock<WeakCache<usize, Entry>> = OnceLock::new();
+static REENTERED: AtomicUsize = AtomicUsize::new(0);
+
+struct Entry {
+    key: usize,
+    reenter: bool,
+}
+
+unsafe impl RcObject for Entry {
+    fn pop_edges(&mut self, _: &mut EdgeTaker<'_>) {}
+}
+
+impl Drop for Entry {
+    fn drop(&mut self) {
+        if self.reenter {
+            // Deadlocks if the destructor runs while the cache is locked.
+            let cache = CACHE.get().unwrap();
+            cache.remove(&self.key);
+            cache.prune();
+            REENTERED.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+#[test]
+fn drop_reenters_cache() {
+    const ROUNDS: usize = 20;
+    const KEYS: usize = 1000;
+
+    // Collect on every pin. This binary runs no other test which it may disturb.
+    set_pin_interval(1);
+    let cache = CACHE.get_or_init(WeakCache::new);
+    for _ in 0..ROUNDS {
+        let cached = (0..KEYS)
+            .map(|key| {
+                Rc::new(Entry {
+                    key,
+                    reenter: false,
+                })
+            })
+            .collect::<Vec<_>>();
+        for (key, value) in cached.iter().enumerate() {
+            cache.insert(key, value);
+        }
+        drop(cached);
+
+        // Release the values which re-enter the cache in a critical section, so that they are
+        // not destructed until the collections in the middle of `prune`, which pins the thread
+        // to release the temporary `Rc`s of the cached values.
+        let guard = cs();
+        drop(
+            (0..KEYS)
+                .map(|key| Rc::new(Entry { key, reenter: true }))
+                .collect::<Vec<_>>(),
+        );
+        guard.flush();
+        drop(guard);
+        cache.prune();
+        for key in 0..KEYS {
+            drop(cache.get(&key));
+        }
+    }
+    set_pin_interval(0);
+
+    quiescent_barrier();
+    assert_eq!(REENTERED.load(Ordering::Relaxed), ROUNDS * KEYS);
+}